@@ -0,0 +1,239 @@
+//Generate jsafe.h in OUT_DIR from the C bindings, so the header can't drift from the Rust signatures.
+//tests/c_header.rs checks that the copy in headers/ matches it.
+//
+//Every #[no_mangle] function in src/c_bindings becomes a declaration, and every #[repr(C)] enum,
+//#[repr(C)] struct and `pub type jsafe_*` function pointer a typedef. The // comments directly above an item are copied into the header.
+
+#![allow(clippy::needless_return)]
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const HEADER_START: &str = "//Generated by build.rs from src/c_bindings. Do not edit.
+#ifndef JSAFE_H
+#define JSAFE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+//Handle to a document. Handles are not pointers and must not be dereferenced.
+typedef struct jsafe_object jsafe_object;
+//Handle to a detached value, made by the jsafe_new_* constructors
+typedef struct jsafe_value jsafe_value;
+//NUL terminated text returned by jsafe, freed with jsafe_free_string
+typedef char jsafe_string;
+//Handle to an iterator over an object or array, freed with jsafe_iter_free
+typedef struct jsafe_iter jsafe_iter;
+";
+
+const HEADER_END: &str = "
+#ifdef __cplusplus
+}
+#endif
+
+#endif
+";
+
+//Translate a Rust type from a C binding signature to C
+fn c_type(rust: &str) -> String {
+	let rust = rust.trim();
+
+	if let Some(x) = rust.strip_prefix("*const ") {
+		return format!("const {}*", c_type(x));
+	}
+	if let Some(x) = rust.strip_prefix("*mut ") {
+		return format!("{}*", c_type(x));
+	}
+
+	let to_return = match rust {
+		"object" => "jsafe_object",
+		"value" => "jsafe_value",
+		"string" => "jsafe_string",
+		"iterator" => "jsafe_iter",
+		"c_char" => "char",
+		"c_int" => "int",
+		"c_uint" => "unsigned int",
+		"c_double" => "double",
+		"c_void" => "void",
+		"usize" => "size_t",
+		"bool" => "bool",
+		x if x.starts_with("jsafe_") => x,
+		x => panic!("build.rs: no C type for {}", x)
+	};
+	return to_return.to_string();
+}
+
+//Translate the Rust parameters `a: T, b: U` to a C parameter list
+fn c_parameters(rust: &str) -> String {
+	let mut to_return: Vec<String> = Vec::new();
+	for argument in rust.split(',').filter(|x| !x.trim().is_empty()) {
+		let (argument_name, rust) = argument.split_at(argument.find(':').unwrap());
+
+		//this is a keyword in C++
+		let argument_name = match argument_name.trim() {
+			"this" => "obj",
+			x => x
+		};
+		to_return.push(format!("{} {}", c_type(&rust[1..]), argument_name));
+	}
+	if to_return.is_empty() {
+		to_return.push(String::from("void"));
+	}
+
+	return to_return.join(", ");
+}
+
+//Translate the `-> R` after a Rust parameter list to a C return type
+fn c_result(rest: &str) -> String {
+	match rest.trim().strip_prefix("->") {
+		Some(x) => c_type(x),
+		None => String::from("void")
+	}
+}
+
+//Translate the struct field `pub name: T,` to a C member. Arrays `[T; N]` become `T name[N]`.
+fn c_field(line: &str) -> String {
+	let line = line.trim_start_matches("pub ").trim_end_matches(',');
+	let (name, rust) = line.split_at(line.find(':').unwrap());
+	let rust = rust[1..].trim();
+
+	if let Some(x) = rust.strip_prefix('[') {
+		let (element, len) = x.trim_end_matches(']').split_at(x.find(';').unwrap());
+		return format!("{} {}[{}];", c_type(element), name, len[1..].trim());
+	}
+	return format!("{} {};", c_type(rust), name);
+}
+
+//Translate `pub unsafe extern "C" fn name(a: T, b: U) -> R {` to a C declaration
+fn c_function(line: &str) -> String {
+	let start = line.find("fn ").expect("build.rs: exported function signatures must be on one line") + 3;
+	let open = line.find('(').unwrap();
+	let close = line.rfind(')').unwrap();
+	let name = &line[start..open];
+
+	let rest = line[close + 1..].trim().trim_end_matches('{');
+	return format!("{} {}({});\n", c_result(rest), name, c_parameters(&line[open + 1..close]));
+}
+
+//Translate `pub type name = Option<unsafe extern "C" fn(a: T) -> R>;` to a function pointer typedef
+fn c_callback(line: &str) -> String {
+	let name = line["pub type ".len()..line.find('=').unwrap()].trim();
+	let open = line.find("fn(").expect("build.rs: callback types must be on one line") + 2;
+	let close = line.rfind(')').unwrap();
+
+	let rest = line[close + 1..].trim().trim_end_matches(';').trim_end_matches('>');
+	return format!("{} (*{})({});\n", c_result(rest), name, c_parameters(&line[open + 1..close]));
+}
+
+//Declarations generated from one source file
+#[derive(Default)]
+struct Declarations {
+	types: String,
+	functions: String
+}
+
+fn scan(source: &str) -> Declarations {
+	let mut to_return = Declarations::default();
+	let mut comments = String::new();
+	let mut exported = false;
+	let mut repr_c = false;
+	let mut lines = source.lines();
+
+	while let Some(line) = lines.next() {
+		let line = line.trim();
+
+		if line.starts_with("//") {
+			comments += line;
+			comments.push('\n');
+			continue;
+		}
+		if line.starts_with("#[") {
+			exported |= line == "#[no_mangle]";
+			repr_c |= line == "#[repr(C)]";
+			continue;
+		}
+
+		if exported && line.contains("extern \"C\" fn ") {
+			to_return.functions += &comments;
+			to_return.functions += &c_function(line);
+		}
+
+		if repr_c && line.starts_with("pub enum ") {
+			let name = line["pub enum ".len()..].trim_end_matches('{').trim();
+			to_return.types += &format!("\n{}typedef enum {{\n", comments);
+
+			//Variants, each with the comments above it
+			for variant in lines.by_ref().map(|x| x.trim()).take_while(|x| *x != "}") {
+				to_return.types += &format!("\t{}\n", variant);
+			}
+			to_return.types += &format!("}} {};\n", name);
+		}
+
+		if repr_c && line.starts_with("pub struct ") {
+			let name = line["pub struct ".len()..].trim_end_matches('{').trim();
+			to_return.types += &format!("\n{}typedef struct {{\n", comments);
+
+			//Fields, each with the comments above it
+			for field in lines.by_ref().map(|x| x.trim()).take_while(|x| *x != "}") {
+				match field.starts_with("//") {
+					true => to_return.types += &format!("\t{}\n", field),
+					false => to_return.types += &format!("\t{}\n", c_field(field))
+				}
+			}
+			to_return.types += &format!("}} {};\n", name);
+		}
+
+		if line.starts_with("pub type jsafe_") && line.contains("extern \"C\" fn(") {
+			to_return.types += &format!("\n{}typedef {}", comments, c_callback(line));
+		}
+
+		comments.clear();
+		exported = false;
+		repr_c = false;
+	}
+
+	return to_return;
+}
+
+fn main() {
+	let root = env::var("CARGO_MANIFEST_DIR").unwrap();
+	let root = Path::new(&root);
+	println!("cargo:rerun-if-changed=build.rs");
+	println!("cargo:rerun-if-changed=src/c_bindings.rs");
+	println!("cargo:rerun-if-changed=src/c_bindings");
+
+	//Modules in the order src/c_bindings.rs declares them
+	let modules = fs::read_to_string(root.join("src/c_bindings.rs")).unwrap();
+	let mut types = String::new();
+	let mut functions: Vec<String> = Vec::new();
+
+	for module in modules.lines().filter_map(|x| x.trim().strip_prefix("mod ")) {
+		let module = module.trim_end_matches(';');
+		let source = fs::read_to_string(root.join("src/c_bindings").join(format!("{}.rs", module))).unwrap();
+
+		let found = scan(&source);
+		types += &found.types;
+		if !found.functions.is_empty() {
+			functions.push(found.functions);
+		}
+	}
+
+	let mut header = String::from(HEADER_START);
+	header += &types;
+	for group in functions {
+		header.push('\n');
+		header += &group;
+	}
+	header += HEADER_END;
+
+	//Only touch the file when it changes, so C builds depending on it aren't redone needlessly
+	let path = Path::new(&env::var("OUT_DIR").unwrap()).join("jsafe.h");
+	if fs::read_to_string(&path).ok().as_deref() != Some(header.as_str()) {
+		fs::write(&path, header).unwrap();
+	}
+}
@@ -1,9 +1,236 @@
-#ifndef JSAFE_C_H
-#define JSAFE_C_H
+//Generated by build.rs from src/c_bindings. Do not edit.
+#ifndef JSAFE_H
+#define JSAFE_H
 
-#include "jsafe_types.h"
-#include "jsafe_parsing.h"
-#include "jsafe_formatting.h"
-#include "jsafe_json.h"
+#include <stdbool.h>
+#include <stddef.h>
 
-#endif
\ No newline at end of file
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+//Handle to a document. Handles are not pointers and must not be dereferenced.
+typedef struct jsafe_object jsafe_object;
+//Handle to a detached value, made by the jsafe_new_* constructors
+typedef struct jsafe_value jsafe_value;
+//NUL terminated text returned by jsafe, freed with jsafe_free_string
+typedef char jsafe_string;
+//Handle to an iterator over an object or array, freed with jsafe_iter_free
+typedef struct jsafe_iter jsafe_iter;
+
+//Status codes returned by C functions
+typedef enum {
+	JSAFE_OK = 0,
+	//A required pointer or handle was NULL
+	JSAFE_ERR_NULL = 1,
+	//The handle was already freed, or never came from this library
+	JSAFE_ERR_INVALID_HANDLE = 2,
+	//A C string passed in wasn't valid UTF-8
+	JSAFE_ERR_UTF8 = 3,
+	//Text to return contains a NUL byte, so it can't be a C string
+	JSAFE_ERR_EMBEDDED_NUL = 4,
+	//The input wasn't valid JSON
+	JSAFE_ERR_PARSE = 5,
+	//The value under the pointer has the wrong type for the operation
+	JSAFE_ERR_TYPE = 6,
+	//An array index or JSON Pointer doesn't address an existing value
+	JSAFE_ERR_NOT_FOUND = 7,
+	//A bug inside the library was caught before it could unwind into C
+	JSAFE_ERR_PANIC = 8,
+	//A file couldn't be read or written
+	JSAFE_ERR_IO = 9,
+	//No handle could be given out because too many are live; free some first
+	JSAFE_ERR_TOO_MANY_HANDLES = 10
+} jsafe_status;
+
+//Options for jsafe_to_file. NULL options write compact JSON.
+typedef struct {
+	//Indent the output as jsafe_to_pretty does
+	bool pretty;
+	//Spaces per level when pretty, or 0 to indent with tabs
+	size_t spaces;
+} jsafe_write_options;
+
+//Called by jsafe_foreach for each member. key is NULL for array elements. member is a cursor on
+//the member that is freed when the callback returns. Return false to stop early.
+typedef bool (*jsafe_foreach_fn)(const char* key, size_t index, jsafe_object* member, void* user_data);
+
+//Options for jsafe_from_buffer and jsafe_from_file. NULL options use the defaults.
+typedef struct {
+	//Deepest nesting of objects and arrays accepted, or 0 for the default of 512
+	size_t max_depth;
+} jsafe_parse_options;
+
+//Where and why parsing failed. Line and column are 0 when the failure isn't in the text, such as
+//a file that can't be read.
+typedef struct {
+	//Byte offset of the problem in the input
+	size_t offset;
+	//Line and column of the problem, both starting at 1. Columns count characters, not bytes.
+	size_t line;
+	size_t column;
+	//NUL terminated description, cut short if it doesn't fit
+	char message[128];
+} jsafe_parse_error;
+
+//Return a new document holding a copy of the value under a cursor. Later changes to either
+//document don't affect the other.
+jsafe_object* jsafe_clone(jsafe_object* obj);
+//Set a member of the object under a cursor to a copy of the value under source. source is not
+//consumed and may be on any document, including this one.
+jsafe_status jsafe_o_add_copy(jsafe_object* obj, const char* key, jsafe_object* source);
+//Add a copy of the value under source to the array under a cursor. source is not consumed and may
+//be on any document, including this one.
+jsafe_status jsafe_a_add_copy(jsafe_object* obj, jsafe_object* source);
+
+//Make another cursor on the same document, starting where this one is. Changes made through
+//either cursor are seen by both. Free it with jsafe_free_value; the document itself is freed with
+//its last cursor.
+jsafe_object* jsafe_cursor_clone(jsafe_object* obj);
+//Move the cursor to the object or array holding its value. Fails with JSAFE_ERR_NOT_FOUND at the root.
+jsafe_status jsafe_parent(jsafe_object* obj);
+//Return the cursor's position as a JSON Pointer ("" at the root)
+jsafe_string* jsafe_current_path(jsafe_object* obj);
+
+//Replace the value under the cursor with a number
+jsafe_status jsafe_set_num(jsafe_object* obj, double val);
+//Replace the value under the cursor with text
+jsafe_status jsafe_set_text(jsafe_object* obj, const char* text);
+//Replace the value under the cursor with a bool
+jsafe_status jsafe_set_bool(jsafe_object* obj, bool val);
+//Replace the value under the cursor with null
+jsafe_status jsafe_set_null(jsafe_object* obj);
+//Remove a member of the object under the cursor. Fails with JSAFE_ERR_NOT_FOUND if the key is missing.
+jsafe_status jsafe_o_remove(jsafe_object* obj, const char* key);
+//Remove an item of the array under the cursor, moving the items after it down by one
+jsafe_status jsafe_a_remove(jsafe_object* obj, size_t index);
+//Insert a value into the array under the cursor before index, or at the end if index is the
+//array's length. The value handle is consumed, even if the call fails, and NULL inserts a null.
+jsafe_status jsafe_a_insert(jsafe_object* obj, size_t index, jsafe_value* val);
+//Remove every member of the object or array under the cursor
+jsafe_status jsafe_clear(jsafe_object* obj);
+//Move the value under the cursor out of its document into a new one, stored in detached (NULL on
+//failure). The cursor moves up to the object or array that held the value. Fails with
+//JSAFE_ERR_NOT_FOUND at the root; free the document instead.
+jsafe_status jsafe_detach(jsafe_object* obj, jsafe_object** detached);
+
+//Status of the last jsafe call made on this thread
+jsafe_status jsafe_last_error(void);
+//Message describing the last failed jsafe call on this thread, or "" if it succeeded. The text
+//belongs to the library and stays valid until the next jsafe call on this thread.
+const char* jsafe_last_error_message(void);
+
+//Return an indented string representation of an object. NULs in strings are escaped as \u0000.
+jsafe_string* jsafe_to_pretty(jsafe_object* obj, size_t spaces);
+//Return an indented string representation of an object and its length in bytes
+jsafe_string* jsafe_to_pretty_len(jsafe_object* obj, size_t spaces, size_t* len);
+//Write the value under a cursor to a file, replacing it, followed by a newline. A file that can't be
+//written gives JSAFE_ERR_IO.
+jsafe_status jsafe_to_file(jsafe_object* obj, const char* path, const jsafe_write_options* options);
+
+//Start iterating over the object or array under a cursor. Object keys are visited in sorted
+//order. Call jsafe_iter_next to move to the first member, and free the iterator with jsafe_iter_free.
+jsafe_iter* jsafe_iter_begin(jsafe_object* obj);
+//Move to the next member. Returns false once every member has been visited, once an array being
+//walked changes length, or on error.
+bool jsafe_iter_next(jsafe_iter* iter);
+//Return the key of the current member. Fails with JSAFE_ERR_TYPE when iterating over an array.
+jsafe_string* jsafe_iter_key(jsafe_iter* iter);
+//Return the position of the current member: its index in an array, or its place in the sorted
+//keys of an object
+size_t jsafe_iter_index(jsafe_iter* iter);
+//Return a new cursor on the current member. Free it with jsafe_free_value.
+jsafe_object* jsafe_iter_enter(jsafe_iter* iter);
+//Free an iterator. The document it walked is not affected.
+jsafe_status jsafe_iter_free(jsafe_iter* iter);
+//Call callback for each member of the object or array under a cursor, in the same order as
+//jsafe_iter_next. Stops early if callback returns false. The callback may use the jsafe API,
+//including changing the document, with the same effect on the remaining members as for
+//jsafe_iter_next: an array stops being walked once its length changes.
+jsafe_status jsafe_foreach(jsafe_object* obj, jsafe_foreach_fn callback, void* user_data);
+
+//Free a document, or one cursor on it if jsafe_cursor_clone made others. Freeing a handle twice
+//returns JSAFE_ERR_INVALID_HANDLE.
+jsafe_status jsafe_free_value(jsafe_object* obj);
+//Free a detached value that was never added to a document
+jsafe_status jsafe_free_detached(jsafe_value* obj);
+//Free a string. Freeing a string twice returns JSAFE_ERR_INVALID_HANDLE.
+jsafe_status jsafe_free_string(jsafe_string* obj);
+//Free all memory
+void jsafe_cleanup(void);
+//Return a new globally allocated object
+jsafe_object* jsafe_new_root(void);
+//Reset an object's pointer to root
+jsafe_status jsafe_reset(jsafe_object* obj);
+//Return a new detached json object
+jsafe_value* jsafe_new_obj(void);
+//Return a new detached json array
+jsafe_value* jsafe_new_arr(void);
+//Return a new text value from a C string. NULL gives empty text; invalid UTF-8 gives NULL.
+jsafe_value* jsafe_new_text(const char* text);
+//Return a new null value
+jsafe_value* jsafe_new_null(void);
+//Return a new bool value
+jsafe_value* jsafe_new_bool(bool val);
+//Return a new number value
+jsafe_value* jsafe_new_num(double val);
+//Pre-allocate slots for the container (to speed up adding values)
+jsafe_status jsafe_prealloc(jsafe_object* obj, size_t amount);
+//Move the pointer to a member of an object, creating an empty object if the key is missing
+jsafe_status jsafe_o_get(jsafe_object* obj, const char* key);
+//Set a value from string index. The value handle is consumed, even if the call fails, and
+//NULL adds a null.
+jsafe_status jsafe_o_add(jsafe_object* obj, const char* key, jsafe_value* val);
+//Move the pointer to an item of an array
+jsafe_status jsafe_a_get(jsafe_object* obj, size_t key);
+//Move the pointer to the value addressed by a JSON Pointer (from the root). If the pointer is
+//invalid or doesn't resolve, JSAFE_ERR_NOT_FOUND is returned and the position is left unchanged.
+jsafe_status jsafe_pointer_get(jsafe_object* obj, const char* pointer);
+//Add a new value to an array. The value handle is consumed, even if the call fails, and NULL
+//adds a null.
+jsafe_status jsafe_a_add(jsafe_object* obj, jsafe_value* to_add);
+//Return a string representation of an object. NULs in strings are escaped as \u0000.
+jsafe_string* jsafe_to_string(jsafe_object* obj);
+//Return a string representation of an object and its length in bytes
+jsafe_string* jsafe_to_string_len(jsafe_object* obj, size_t* len);
+//Get the length of a json array/object
+size_t jsafe_get_len(jsafe_object* obj);
+//Check if a json object has a key
+bool jsafe_has_key(jsafe_object* obj, const char* key);
+//Get a string value from an object. Returns NULL if the value isn't text or contains a NUL.
+jsafe_string* jsafe_get_text(jsafe_object* obj);
+//Get a string value from an object and its length in bytes, which may include NULs. Returns NULL
+//if the value isn't text.
+jsafe_string* jsafe_get_text_len(jsafe_object* obj, size_t* len);
+//Get a number value from an object. Returns 0 if the value isn't a number.
+double jsafe_get_num(jsafe_object* obj);
+//Get a bool value from an object. Returns false if the value isn't a bool.
+bool jsafe_get_bool(jsafe_object* obj);
+//Check if a value is null
+bool jsafe_is_null(jsafe_object* obj);
+//Check if a value is invalid
+bool jsafe_is_valid(jsafe_object* obj);
+//Check if a value is text
+bool jsafe_is_text(jsafe_object* obj);
+//Check if a value is a number
+bool jsafe_is_num(jsafe_object* obj);
+//Check if a value is an object
+bool jsafe_is_obj(jsafe_object* obj);
+//Check if a value is an array
+bool jsafe_is_arr(jsafe_object* obj);
+
+//Get a value from a string. Returns NULL if the text isn't valid JSON, and
+//jsafe_last_error_message says where parsing stopped.
+jsafe_object* jsafe_from_str(const char* text);
+//Get a value from len bytes of text, which needn't be NUL terminated. Returns NULL on failure and
+//describes the problem in error, which may be NULL.
+jsafe_object* jsafe_from_buffer(const char* text, size_t len, const jsafe_parse_options* options, jsafe_parse_error* error);
+//Get a value from a file. Returns NULL on failure and describes the problem in error, which may be
+//NULL. A file that can't be read gives JSAFE_ERR_IO.
+jsafe_object* jsafe_from_file(const char* path, const jsafe_parse_options* options, jsafe_parse_error* error);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
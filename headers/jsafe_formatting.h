@@ -1,8 +0,0 @@
-#ifndef JSAFE_FORMATTING_H
-#define JSAFE_FORMATTING_H
-
-#include "jsafe_types.h"
-
-extern "C" const char** jsafe_to_pretty(jsafe_object* to_print, int spaces);
-
-#endif
\ No newline at end of file
@@ -1,8 +0,0 @@
-#ifndef JSAFE_PARSING_H
-#define JSAFE_PARSING_H
-
-#include "jsafe_types.h"
-
-extern "C" jsafe_object* jsafe_from_str(const char* text);
-
-#endif
\ No newline at end of file
@@ -1,8 +0,0 @@
-#ifndef JSAFE_TYPES_H
-#define JSAFE_TYPES_H
-
-typedef void jsafe_value;
-typedef void jsafe_object;
-typedef const char* jsafe_string;
-
-#endif
\ No newline at end of file
@@ -0,0 +1,377 @@
+//#[derive(ToJson, FromJson)] for jsafe. Code is generated as text and parsed back into tokens,
+//so this crate has no dependencies beyond proc_macro.
+#![allow(clippy::needless_return)]
+
+extern crate proc_macro;
+
+mod parse;
+
+use parse::{Data, Field, Fields, Item, Variant, parse_item};
+use proc_macro::TokenStream;
+
+const JSON: &str = "::jsafe::json";
+const PRIVATE: &str = "::jsafe::json::traits::__private";
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+	let generated = match parse_item(input) {
+		Ok(item) => to_json(&item),
+		Err(e) => Err(e)
+	};
+
+	return finish(generated);
+}
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+	let generated = match parse_item(input) {
+		Ok(item) => from_json(&item),
+		Err(e) => Err(e)
+	};
+
+	return finish(generated);
+}
+
+//Turn generated text (or an error message) back into tokens
+fn finish(generated: Result<String, String>) -> TokenStream {
+	let text = match generated {
+		Ok(x) => x,
+		Err(e) => format!("compile_error!({:?});", e)
+	};
+
+	return text.parse().unwrap();
+}
+
+//How an enum is represented in json
+enum Tagging<'a> {
+	External,
+	Internal(&'a str),
+	Adjacent(&'a str, &'a str),
+	Untagged
+}
+
+fn tagging<'a>(item: &'a Item) -> Result<Tagging<'a>, String> {
+	let attrs = &item.attrs;
+	let is_enum = matches!(item.data, Data::Enum(_));
+
+	if !is_enum && (attrs.tag.is_some() || attrs.content.is_some() || attrs.untagged) {
+		return Err(String::from("tag, content and untagged can only be used on enums"));
+	}
+
+	let tagging = match (&attrs.tag, &attrs.content, attrs.untagged) {
+		(None, None, false) => Tagging::External,
+		(Some(tag), None, false) => Tagging::Internal(tag),
+		(Some(tag), Some(content), false) => Tagging::Adjacent(tag, content),
+		(None, None, true) => Tagging::Untagged,
+		(None, Some(_), _) => return Err(String::from("content requires tag")),
+		_ => return Err(String::from("untagged can't be combined with tag or content"))
+	};
+
+	//The tag is added to the content, so a variant's content has to be an object. Newtype variants
+	//are checked through ToJsonObject; other tuple variants are written as arrays.
+	if let (Tagging::Internal(_), Data::Enum(variants)) = (&tagging, &item.data) {
+		for variant in variants {
+			if let Fields::Unnamed(ref fields) = variant.fields {
+				if fields.len() != 1 {
+					return Err(format!("internally tagged enums can't contain tuple variant {}", variant.name));
+				}
+			}
+		}
+	}
+
+	return Ok(tagging);
+}
+
+//Tuple fields are stored by position, so they can't be renamed, skipped or flattened
+fn check_unnamed(fields: &[Field]) -> Result<(), String> {
+	for field in fields {
+		let attrs = &field.attrs;
+		if attrs.rename.is_some() || attrs.default.is_some() || attrs.skip || attrs.flatten {
+			return Err(String::from("json attributes are not supported on tuple fields"));
+		}
+	}
+
+	return Ok(());
+}
+
+//Expression building the members of a json object from named fields. access gives the expression
+//for a field reference.
+fn members_expr<F: Fn(usize, &Field) -> String>(fields: &[Field], access: F) -> String {
+	let mut to_return = String::from("{ #[allow(unused_mut)] let mut object = ::std::collections::HashMap::new(); ");
+
+	for (i, field) in fields.iter().enumerate() {
+		if field.attrs.skip {
+			continue;
+		}
+
+		//Flattened fields must be objects, or their members would have nowhere to go
+		if field.attrs.flatten {
+			to_return += &format!("object.extend({}::ToJsonObject::to_json_object({})); ", PRIVATE, access(i, field));
+		} else {
+			to_return += &format!("object.insert(::std::string::String::from({:?}), {}::ToJson::to_json({})); ", field.key(), JSON, access(i, field));
+		}
+	}
+
+	to_return += "object }";
+	return to_return;
+}
+
+//Expression building a json array (or the single value of a newtype) from tuple fields
+fn tuple_expr<F: Fn(usize) -> String>(count: usize, access: F) -> String {
+	let values: Vec<String> = (0..count).map(|i| format!("{}::ToJson::to_json({})", JSON, access(i))).collect();
+
+	if count == 1 {
+		return values[0].clone();
+	}
+
+	return format!("{}::Value::Array(vec![{}])", JSON, values.join(", "));
+}
+
+fn to_json(item: &Item) -> Result<String, String> {
+	let tagging = tagging(item)?;
+	let mut to_return = String::new();
+
+	let body = match item.data {
+		Data::Struct(Fields::Named(ref fields)) => {
+			//Structs with named fields are always objects, so they can be flattened or held by an
+			//internally tagged newtype variant
+			to_return += &format!(
+				"{} {{ fn to_json_object(&self) -> ::std::collections::HashMap<::std::string::String, {}::Value> {{ {} }} }}",
+				item.impl_header(&format!("{}::ToJsonObject", PRIVATE), &format!("{}::ToJson", JSON)), JSON,
+				members_expr(fields, |_, field| format!("&self.{}", field.name.as_ref().unwrap()))
+			);
+
+			format!("{}::Value::Object({}::ToJsonObject::to_json_object(self))", JSON, PRIVATE)
+		}
+		Data::Struct(Fields::Unnamed(ref fields)) => {
+			check_unnamed(fields)?;
+			tuple_expr(fields.len(), |i| format!("&self.{}", i))
+		}
+		Data::Struct(Fields::Unit) => format!("{}::Value::Null", JSON),
+		Data::Enum(ref variants) => {
+			let mut arms = String::new();
+			for variant in variants {
+				arms += &variant_to_json(item, variant, &tagging)?;
+			}
+
+			format!("match *self {{ {} }}", arms)
+		}
+	};
+
+	to_return += &format!(
+		"{} {{ fn to_json(&self) -> {}::Value {{ {} }} }}",
+		item.impl_header(&format!("{}::ToJson", JSON), &format!("{}::ToJson", JSON)), JSON, body
+	);
+	return Ok(to_return);
+}
+
+//What a variant holds, as an expression
+enum Content {
+	Nothing,
+	//A HashMap of the members of a json object
+	Members(String),
+	Value(String)
+}
+
+impl Content {
+	//Expression for the content as a json value
+	fn value(self) -> String {
+		match self {
+			Content::Nothing => format!("{}::Value::Null", JSON),
+			Content::Members(x) => format!("{}::Value::Object({})", JSON, x),
+			Content::Value(x) => x
+		}
+	}
+}
+
+//Match arm writing out one enum variant
+fn variant_to_json(item: &Item, variant: &Variant, tagging: &Tagging) -> Result<String, String> {
+	let key = variant.key();
+
+	//Pattern binding every field as __fieldN, and the variant's content
+	let (pattern, content) = match variant.fields {
+		Fields::Named(ref fields) => {
+			let bindings: Vec<String> = fields.iter().enumerate().map(|(i, field)| {
+				format!("{}: ref __field{}", field.name.as_ref().unwrap(), i)
+			}).collect();
+
+			let content = members_expr(fields, |i, _| format!("__field{}", i));
+			(format!("{}::{} {{ {} }}", item.name, variant.name, bindings.join(", ")), Content::Members(content))
+		}
+		Fields::Unnamed(ref fields) => {
+			check_unnamed(fields)?;
+
+			let bindings: Vec<String> = (0..fields.len()).map(|i| format!("ref __field{}", i)).collect();
+			let content = match *tagging {
+				//tagging() only allows newtype variants here, and their type has to be an object
+				Tagging::Internal(_) => Content::Members(format!("{}::ToJsonObject::to_json_object(__field0)", PRIVATE)),
+				_ => Content::Value(tuple_expr(fields.len(), |i| format!("__field{}", i)))
+			};
+			(format!("{}::{}({})", item.name, variant.name, bindings.join(", ")), content)
+		}
+		Fields::Unit => (format!("{}::{}", item.name, variant.name), Content::Nothing)
+	};
+
+	let value = match (tagging, content) {
+		(&Tagging::External, Content::Nothing) => format!("{}::Value::from({:?})", JSON, key),
+		(&Tagging::External, content) => format!(
+			"{{ let mut object = ::std::collections::HashMap::new(); object.insert(::std::string::String::from({:?}), {}); {}::Value::Object(object) }}",
+			key, content.value(), JSON
+		),
+		(&Tagging::Internal(tag), Content::Members(members)) => format!("{}::tagged({:?}, {:?}, {})", PRIVATE, tag, key, members),
+		(&Tagging::Internal(tag), _) | (&Tagging::Adjacent(tag, _), Content::Nothing) => format!(
+			"{}::tagged({:?}, {:?}, ::std::collections::HashMap::new())",
+			PRIVATE, tag, key
+		),
+		(&Tagging::Adjacent(tag, content_key), content) => format!(
+			"{{ let mut object = ::std::collections::HashMap::new(); object.insert(::std::string::String::from({:?}), {}); {}::tagged({:?}, {:?}, object) }}",
+			content_key, content.value(), PRIVATE, tag, key
+		),
+		(&Tagging::Untagged, content) => content.value()
+	};
+
+	return Ok(format!("{} => {},", pattern, value));
+}
+
+//Closure expression reading fields out of the json value named by source and building path
+fn construct(path: &str, fields: &Fields, source: &str) -> Result<String, String> {
+	let body = match *fields {
+		Fields::Named(ref fields) => {
+			let mut values = String::new();
+			for field in fields {
+				let key = field.key();
+				let name = field.name.as_ref().unwrap();
+
+				let value = if field.attrs.flatten {
+					format!("{}::FromJson::from_json({})?", JSON, source)
+				} else if field.attrs.skip {
+					match field.attrs.default {
+						Some(Some(ref func)) => format!("{}()", func),
+						_ => String::from("::std::default::Default::default()")
+					}
+				} else {
+					match field.attrs.default {
+						Some(Some(ref func)) => format!("{}::field_or(object, {:?}, {})?", PRIVATE, key, func),
+						Some(None) => format!("{}::field_or(object, {:?}, ::std::default::Default::default)?", PRIVATE, key),
+						None => format!("{}::field(object, {:?})?", PRIVATE, key)
+					}
+				};
+
+				values += &format!("{}: {}, ", name, value);
+			}
+
+			format!(
+				"#[allow(unused_variables)] let object = {}::object({})?; ::std::result::Result::Ok({} {{ {} }})",
+				PRIVATE, source, path, values
+			)
+		}
+		Fields::Unnamed(ref fields) => {
+			check_unnamed(fields)?;
+
+			if fields.len() == 1 {
+				format!("::std::result::Result::Ok({}({}::FromJson::from_json({})?))", path, JSON, source)
+			} else {
+				let values: Vec<String> = (0..fields.len()).map(|i| format!("{}::element(items, {})?", PRIVATE, i)).collect();
+				format!(
+					"let items = {}::array({}, {})?; ::std::result::Result::Ok({}({}))",
+					PRIVATE, source, fields.len(), path, values.join(", ")
+				)
+			}
+		}
+		Fields::Unit => format!("{}::null({})?; ::std::result::Result::Ok({})", PRIVATE, source, path)
+	};
+
+	return Ok(format!(
+		"(|| -> ::std::result::Result<Self, {}::FromJsonError> {{ {} }})()",
+		JSON, body
+	));
+}
+
+fn from_json(item: &Item) -> Result<String, String> {
+	let tagging = tagging(item)?;
+
+	let body = match item.data {
+		Data::Struct(ref fields) => construct(&item.name, fields, "value")?,
+		Data::Enum(ref variants) => enum_from_json(item, variants, &tagging)?
+	};
+
+	return Ok(format!(
+		"{} {{ fn from_json(value: &{}::Value) -> ::std::result::Result<Self, {}::FromJsonError> {{ {} }} }}",
+		item.impl_header(&format!("{}::FromJson", JSON), &format!("{}::FromJson", JSON)), JSON, JSON, body
+	));
+}
+
+fn enum_from_json(item: &Item, variants: &[Variant], tagging: &Tagging) -> Result<String, String> {
+	let unknown = format!("_ => ::std::result::Result::Err({}::unknown_variant(name))", PRIVATE);
+
+	match *tagging {
+		Tagging::External => {
+			let mut unit_arms = String::new();
+			let mut content_arms = String::new();
+
+			for variant in variants {
+				let path = format!("{}::{}", item.name, variant.name);
+				match variant.fields {
+					Fields::Unit => unit_arms += &format!("{:?} => ::std::result::Result::Ok({}),", variant.key(), path),
+					_ => content_arms += &format!(
+						"{:?} => {}.map_err(|e| e.with_key(name)),",
+						variant.key(), construct(&path, &variant.fields, "content")?
+					)
+				}
+			}
+
+			return Ok(format!(
+				"match *value {{ \
+					{json}::Value::Text(ref name) => match name.as_str() {{ {} {} }}, \
+					{json}::Value::Object(ref object) if object.len() == 1 => {{ \
+						let (name, content) = object.iter().next().unwrap(); \
+						match name.as_str() {{ {} {} }} \
+					}} \
+					_ => ::std::result::Result::Err({json}::FromJsonError::expected(\"variant name or single key object\", value)) \
+				}}",
+				unit_arms, unknown, content_arms, unknown, json = JSON
+			));
+		}
+		Tagging::Internal(tag) | Tagging::Adjacent(tag, _) => {
+			let mut arms = String::new();
+
+			for variant in variants {
+				let path = format!("{}::{}", item.name, variant.name);
+				let value = match (&variant.fields, tagging) {
+					(&Fields::Unit, _) => format!("::std::result::Result::Ok({})", path),
+					(_, &Tagging::Adjacent(_, content)) => format!(
+						"match object.get({content:?}) {{ \
+							::std::option::Option::Some(content) => {}.map_err(|e| e.with_key({content:?})), \
+							::std::option::Option::None => ::std::result::Result::Err({}::FromJsonError::missing_field({content:?})) \
+						}}",
+						construct(&path, &variant.fields, "content")?, JSON, content = content
+					),
+					_ => construct(&path, &variant.fields, "value")?
+				};
+
+				arms += &format!("{:?} => {},", variant.key(), value);
+			}
+
+			return Ok(format!(
+				"let object = {}::object(value)?; let name = {}::tag(object, {:?})?; match name {{ {} {} }}",
+				PRIVATE, PRIVATE, tag, arms, unknown
+			));
+		}
+		Tagging::Untagged => {
+			let mut attempts = String::new();
+
+			for variant in variants {
+				let path = format!("{}::{}", item.name, variant.name);
+				attempts += &format!(
+					"if let ::std::result::Result::Ok(x) = {} {{ return ::std::result::Result::Ok(x); }} ",
+					construct(&path, &variant.fields, "value")?
+				);
+			}
+
+			return Ok(format!(
+				"{} ::std::result::Result::Err({}::FromJsonError::new(\"data did not match any variant of {}\"))",
+				attempts, JSON, item.name
+			));
+		}
+	}
+}
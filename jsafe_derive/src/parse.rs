@@ -0,0 +1,477 @@
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+use std::iter::FromIterator;
+
+//Options given through #[json(...)] on a type, field or variant
+#[derive(Default)]
+pub struct Attrs {
+	pub rename: Option<String>,
+	pub default: Option<Option<String>>,
+	pub skip: bool,
+	pub flatten: bool,
+	pub tag: Option<String>,
+	pub content: Option<String>,
+	pub untagged: bool
+}
+
+pub struct Field {
+	//None for tuple fields
+	pub name: Option<String>,
+	pub attrs: Attrs
+}
+
+impl Field {
+	//Key used for the field in the json object
+	pub fn key(&self) -> String {
+		if let Some(ref x) = self.attrs.rename {
+			return x.clone();
+		}
+
+		let name = self.name.clone().unwrap_or_default();
+		return name.trim_start_matches("r#").to_string();
+	}
+}
+
+pub enum Fields {
+	Named(Vec<Field>),
+	Unnamed(Vec<Field>),
+	Unit
+}
+
+pub struct Variant {
+	pub name: String,
+	pub attrs: Attrs,
+	pub fields: Fields
+}
+
+impl Variant {
+	//Name used for the variant in json
+	pub fn key(&self) -> String {
+		return self.attrs.rename.clone().unwrap_or_else(|| self.name.clone());
+	}
+}
+
+pub enum Data {
+	Struct(Fields),
+	Enum(Vec<Variant>)
+}
+
+//A single generic parameter, split into the declaration for the impl and the argument for the type
+pub struct Param {
+	pub decl: String,
+	pub arg: String,
+	pub is_type: bool
+}
+
+pub struct Item {
+	pub name: String,
+	pub attrs: Attrs,
+	pub params: Vec<Param>,
+	pub where_clause: String,
+	pub data: Data
+}
+
+impl Item {
+	//Build "impl<...> Trait for Name<...> where ..." with every type parameter bound by bound
+	pub fn impl_header(&self, trait_path: &str, bound: &str) -> String {
+		let decls: Vec<&str> = self.params.iter().map(|x| x.decl.as_str()).collect();
+		let args: Vec<&str> = self.params.iter().map(|x| x.arg.as_str()).collect();
+
+		let mut predicates: Vec<String> = Vec::new();
+		let existing = self.where_clause.trim().trim_end_matches(',').trim();
+		if !existing.is_empty() {
+			predicates.push(existing.to_string());
+		}
+		for param in self.params.iter().filter(|x| x.is_type) {
+			predicates.push(format!("{}: {}", param.arg, bound));
+		}
+
+		let mut to_return = format!("impl<{}> {} for {}<{}>", decls.join(", "), trait_path, self.name, args.join(", "));
+		if !predicates.is_empty() {
+			to_return += &format!(" where {}", predicates.join(", "));
+		}
+
+		return to_return;
+	}
+}
+
+fn stream_string(tokens: &[TokenTree]) -> String {
+	return TokenStream::from_iter(tokens.iter().cloned()).to_string();
+}
+
+fn is_punct(token: Option<&TokenTree>, c: char) -> bool {
+	match token {
+		Some(TokenTree::Punct(x)) => x.as_char() == c,
+		_ => false
+	}
+}
+
+fn is_ident(token: Option<&TokenTree>, name: &str) -> bool {
+	match token {
+		Some(TokenTree::Ident(x)) => x.to_string() == name,
+		_ => false
+	}
+}
+
+//Split a token list on commas that are not nested inside <...>
+fn split_commas(tokens: &[TokenTree]) -> Vec<Vec<TokenTree>> {
+	let mut to_return: Vec<Vec<TokenTree>> = Vec::new();
+	let mut current: Vec<TokenTree> = Vec::new();
+	let mut depth = 0;
+	let mut prev_arrow = false;
+
+	for token in tokens {
+		if let TokenTree::Punct(ref x) = *token {
+			match x.as_char() {
+				'<' => depth += 1,
+				'>' if !prev_arrow && depth > 0 => depth -= 1,
+				',' if depth == 0 => {
+					to_return.push(current);
+					current = Vec::new();
+					prev_arrow = false;
+					continue;
+				}
+				_ => ()
+			}
+
+			//Remember "-" of "->" so its ">" isn't counted as a closing bracket
+			prev_arrow = x.as_char() == '-' && x.spacing() == Spacing::Joint;
+		} else {
+			prev_arrow = false;
+		}
+
+		current.push(token.clone());
+	}
+
+	if !current.is_empty() {
+		to_return.push(current);
+	}
+
+	return to_return;
+}
+
+//Read the contents of a string literal token
+fn string_literal(token: Option<&TokenTree>) -> Result<String, String> {
+	if let Some(TokenTree::Literal(x)) = token {
+		let text = x.to_string();
+		if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+			return Ok(text[1..text.len()-1].replace("\\\"", "\"").replace("\\\\", "\\"));
+		}
+	}
+
+	return Err(String::from("expected a string literal in #[json(...)]"));
+}
+
+//Parse the arguments of a #[json(...)] attribute
+fn parse_json_args(tokens: &[TokenTree], attrs: &mut Attrs) -> Result<(), String> {
+	for arg in split_commas(tokens) {
+		let name = match arg.first() {
+			Some(TokenTree::Ident(x)) => x.to_string(),
+			_ => return Err(String::from("expected an identifier in #[json(...)]"))
+		};
+
+		//name = "value"
+		let value = if arg.len() > 1 {
+			if !is_punct(arg.get(1), '=') || arg.len() != 3 {
+				return Err(format!("expected `{} = \"...\"` in #[json(...)]", name));
+			}
+			Some(string_literal(arg.get(2))?)
+		} else {
+			None
+		};
+
+		match (name.as_str(), value) {
+			("rename", Some(x)) => attrs.rename = Some(x),
+			("default", x) => attrs.default = Some(x),
+			("skip", None) => attrs.skip = true,
+			("flatten", None) => attrs.flatten = true,
+			("tag", Some(x)) => attrs.tag = Some(x),
+			("content", Some(x)) => attrs.content = Some(x),
+			("untagged", None) => attrs.untagged = true,
+			_ => return Err(format!("unknown or malformed json attribute `{}`", name))
+		}
+	}
+
+	return Ok(());
+}
+
+//Consume outer attributes, collecting any #[json(...)] options
+fn parse_attrs(tokens: &[TokenTree], pos: &mut usize) -> Result<Attrs, String> {
+	let mut attrs = Attrs::default();
+
+	while is_punct(tokens.get(*pos), '#') {
+		let group = match tokens.get(*pos + 1) {
+			Some(TokenTree::Group(x)) if x.delimiter() == Delimiter::Bracket => x,
+			_ => return Err(String::from("malformed attribute"))
+		};
+		*pos += 2;
+
+		let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+		if !is_ident(inner.first(), "json") {
+			continue;
+		}
+
+		match inner.get(1) {
+			Some(TokenTree::Group(x)) if x.delimiter() == Delimiter::Parenthesis && inner.len() == 2 => {
+				let args: Vec<TokenTree> = x.stream().into_iter().collect();
+				parse_json_args(&args, &mut attrs)?;
+			}
+			_ => return Err(String::from("expected #[json(...)]"))
+		}
+	}
+
+	return Ok(attrs);
+}
+
+//Only tag, content and untagged apply to a whole struct or enum
+fn check_item_attrs(attrs: &Attrs, name: &str) -> Result<(), String> {
+	if attrs.rename.is_some() || attrs.default.is_some() || attrs.skip || attrs.flatten {
+		return Err(format!("only tag, content and untagged are supported on structs and enums, found another json attribute on {}", name));
+	}
+
+	return Ok(());
+}
+
+//tag, content and untagged describe enums, and a flattened field has no key or value of its own
+fn check_field_attrs(attrs: &Attrs, name: &str) -> Result<(), String> {
+	if attrs.tag.is_some() || attrs.content.is_some() || attrs.untagged {
+		return Err(format!("tag, content and untagged are not supported on fields, found one on field {}", name));
+	}
+	if attrs.flatten && (attrs.rename.is_some() || attrs.default.is_some() || attrs.skip) {
+		return Err(format!("flatten can't be combined with rename, default or skip on field {}", name));
+	}
+
+	return Ok(());
+}
+
+//Skip pub, pub(crate) and similar
+fn skip_visibility(tokens: &[TokenTree], pos: &mut usize) {
+	if is_ident(tokens.get(*pos), "pub") {
+		*pos += 1;
+
+		if let Some(TokenTree::Group(x)) = tokens.get(*pos) {
+			if x.delimiter() == Delimiter::Parenthesis {
+				*pos += 1;
+			}
+		}
+	}
+}
+
+fn parse_ident(tokens: &[TokenTree], pos: &mut usize) -> Result<String, String> {
+	match tokens.get(*pos) {
+		Some(TokenTree::Ident(x)) => {
+			*pos += 1;
+			Ok(x.to_string())
+		}
+		_ => Err(String::from("expected an identifier"))
+	}
+}
+
+fn parse_generics(tokens: &[TokenTree], pos: &mut usize) -> Result<Vec<Param>, String> {
+	let mut to_return: Vec<Param> = Vec::new();
+	if !is_punct(tokens.get(*pos), '<') {
+		return Ok(to_return);
+	}
+
+	//Find the closing >
+	let start = *pos + 1;
+	let mut depth = 0;
+	let mut end = None;
+	for i in *pos..tokens.len() {
+		if is_punct(tokens.get(i), '<') {
+			depth += 1;
+		} else if is_punct(tokens.get(i), '>') && !is_punct(tokens.get(i - 1), '-') {
+			depth -= 1;
+			if depth == 0 {
+				end = Some(i);
+				break;
+			}
+		}
+	}
+	let end = end.ok_or_else(|| String::from("unterminated generic parameters"))?;
+	*pos = end + 1;
+
+	for param in split_commas(&tokens[start..end]) {
+		//Drop default values, which aren't allowed on impls
+		let mut decl = param.clone();
+		let mut depth = 0;
+		for (i, token) in param.iter().enumerate() {
+			match *token {
+				TokenTree::Punct(ref x) if x.as_char() == '<' => depth += 1,
+				TokenTree::Punct(ref x) if x.as_char() == '>' => depth -= 1,
+				TokenTree::Punct(ref x) if x.as_char() == '=' && depth == 0 => {
+					decl.truncate(i);
+					break;
+				}
+				_ => ()
+			}
+		}
+
+		if is_punct(param.first(), '\'') {
+			to_return.push(Param {
+				decl: stream_string(&decl),
+				arg: stream_string(&param[..2]),
+				is_type: false
+			});
+		} else if is_ident(param.first(), "const") {
+			to_return.push(Param {
+				decl: stream_string(&decl),
+				arg: param.get(1).map(|x| x.to_string()).unwrap_or_default(),
+				is_type: false
+			});
+		} else {
+			to_return.push(Param {
+				decl: stream_string(&decl),
+				arg: param.first().map(|x| x.to_string()).unwrap_or_default(),
+				is_type: true
+			});
+		}
+	}
+
+	return Ok(to_return);
+}
+
+//Collect a where clause up to the body of the item
+fn parse_where(tokens: &[TokenTree], pos: &mut usize) -> String {
+	if !is_ident(tokens.get(*pos), "where") {
+		return String::new();
+	}
+
+	let start = *pos + 1;
+	while *pos < tokens.len() {
+		match tokens[*pos] {
+			TokenTree::Group(ref x) if x.delimiter() == Delimiter::Brace => break,
+			TokenTree::Punct(ref x) if x.as_char() == ';' => break,
+			_ => *pos += 1
+		}
+	}
+
+	return stream_string(&tokens[start..*pos]);
+}
+
+fn parse_named_fields(tokens: &[TokenTree]) -> Result<Fields, String> {
+	let mut to_return: Vec<Field> = Vec::new();
+
+	for field in split_commas(tokens) {
+		let mut pos = 0;
+		let attrs = parse_attrs(&field, &mut pos)?;
+		skip_visibility(&field, &mut pos);
+		let name = parse_ident(&field, &mut pos)?;
+		if !is_punct(field.get(pos), ':') {
+			return Err(format!("expected a type for field {}", name));
+		}
+
+		check_field_attrs(&attrs, &name)?;
+		to_return.push(Field {
+			name: Some(name),
+			attrs
+		});
+	}
+
+	return Ok(Fields::Named(to_return));
+}
+
+fn parse_unnamed_fields(tokens: &[TokenTree]) -> Result<Fields, String> {
+	let mut to_return: Vec<Field> = Vec::new();
+
+	for field in split_commas(tokens) {
+		let mut pos = 0;
+		let attrs = parse_attrs(&field, &mut pos)?;
+		check_field_attrs(&attrs, &to_return.len().to_string())?;
+
+		to_return.push(Field {
+			name: None,
+			attrs
+		});
+	}
+
+	return Ok(Fields::Unnamed(to_return));
+}
+
+//Parse the fields that follow a struct or variant name
+fn parse_fields(token: Option<&TokenTree>) -> Result<Fields, String> {
+	match token {
+		Some(TokenTree::Group(x)) => {
+			let inner: Vec<TokenTree> = x.stream().into_iter().collect();
+			match x.delimiter() {
+				Delimiter::Brace => parse_named_fields(&inner),
+				Delimiter::Parenthesis => parse_unnamed_fields(&inner),
+				_ => Err(String::from("unexpected fields"))
+			}
+		}
+		_ => Ok(Fields::Unit)
+	}
+}
+
+fn parse_variants(tokens: &[TokenTree]) -> Result<Vec<Variant>, String> {
+	let mut to_return: Vec<Variant> = Vec::new();
+
+	for variant in split_commas(tokens) {
+		let mut pos = 0;
+		let attrs = parse_attrs(&variant, &mut pos)?;
+		let name = parse_ident(&variant, &mut pos)?;
+		let fields = parse_fields(variant.get(pos))?;
+
+		//Only the name of a variant can be changed
+		if attrs.default.is_some() || attrs.skip || attrs.flatten || attrs.tag.is_some() || attrs.content.is_some() || attrs.untagged {
+			return Err(format!("only rename is supported on enum variants, found another json attribute on {}", name));
+		}
+
+		to_return.push(Variant {
+			name,
+			attrs,
+			fields
+		});
+	}
+
+	return Ok(to_return);
+}
+
+//Parse the struct or enum a derive was placed on
+pub fn parse_item(input: TokenStream) -> Result<Item, String> {
+	let tokens: Vec<TokenTree> = input.into_iter().collect();
+	let mut pos = 0;
+
+	let attrs = parse_attrs(&tokens, &mut pos)?;
+	skip_visibility(&tokens, &mut pos);
+	let keyword = parse_ident(&tokens, &mut pos)?;
+	let name = parse_ident(&tokens, &mut pos)?;
+	check_item_attrs(&attrs, &name)?;
+	let params = parse_generics(&tokens, &mut pos)?;
+	let mut where_clause = parse_where(&tokens, &mut pos);
+
+	let data = match keyword.as_str() {
+		"struct" => {
+			let fields = parse_fields(tokens.get(pos))?;
+
+			//Tuple structs put the where clause after the fields
+			if let Fields::Unnamed(_) = fields {
+				pos += 1;
+				let after = parse_where(&tokens, &mut pos);
+				if !after.is_empty() {
+					where_clause = after;
+				}
+			}
+
+			Data::Struct(fields)
+		}
+		"enum" => {
+			match tokens.get(pos) {
+				Some(TokenTree::Group(x)) if x.delimiter() == Delimiter::Brace => {
+					let inner: Vec<TokenTree> = x.stream().into_iter().collect();
+					Data::Enum(parse_variants(&inner)?)
+				}
+				_ => return Err(String::from("expected enum body"))
+			}
+		}
+		_ => return Err(String::from("ToJson and FromJson can only be derived for structs and enums"))
+	};
+
+	return Ok(Item {
+		name,
+		attrs,
+		params,
+		where_clause,
+		data
+	});
+}
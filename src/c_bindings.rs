@@ -1,3 +1,9 @@
+mod c_copying;
+mod c_cursor;
+mod c_editing;
+mod c_errors;
 mod c_formatting;
+mod c_handles;
+mod c_iter;
 mod c_json;
 mod c_parsing;
@@ -0,0 +1,54 @@
+use json::Value;
+use std::ffi::c_char;
+use std::ptr::null_mut;
+
+use super::c_errors::{guard, guard_status, jsafe_status, text_arg};
+use super::c_json::{object, create_object, with_current, wrong_type};
+
+//Copies are taken while only the source document is locked, and added once it is released, so
+//copying between two documents (or within one) can't deadlock.
+
+//Return a new document holding a copy of the value under a cursor. Later changes to either
+//document don't affect the other.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_clone(this: *mut object) -> *mut object {
+	guard(null_mut(), || {
+		let copy = with_current(this, |x| Ok(x.clone()))?;
+		create_object(copy)
+	})
+}
+
+//Set a member of the object under a cursor to a copy of the value under source. source is not
+//consumed and may be on any document, including this one.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_o_add_copy(this: *mut object, key: *const c_char, source: *mut object) -> jsafe_status {
+	guard_status(|| {
+		let str = text_arg(key, "key")?;
+		let copy = with_current(source, |x| Ok(x.clone()))?;
+
+		with_current(this, |x| match x {
+			Value::Object(x) => {
+				x.insert(str.to_string(), copy);
+				Ok(())
+			}
+			x => Err(wrong_type("an object", x))
+		})
+	})
+}
+
+//Add a copy of the value under source to the array under a cursor. source is not consumed and may
+//be on any document, including this one.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_a_add_copy(this: *mut object, source: *mut object) -> jsafe_status {
+	guard_status(|| {
+		let copy = with_current(source, |x| Ok(x.clone()))?;
+
+		with_current(this, |x| match x {
+			Value::Array(x) => {
+				x.push(copy);
+				Ok(())
+			}
+			x => Err(wrong_type("an array", x))
+		})
+	})
+}
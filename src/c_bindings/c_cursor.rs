@@ -0,0 +1,40 @@
+use std::ptr::null_mut;
+
+use super::c_errors::{Failure, c_string, guard, guard_status, jsafe_status};
+use super::c_handles::{self, with_object};
+use super::c_json::{object, string, create_string, path_pointer};
+
+//Make another cursor on the same document, starting where this one is. Changes made through
+//either cursor are seen by both. Free it with jsafe_free_value; the document itself is freed with
+//its last cursor.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_cursor_clone(this: *mut object) -> *mut object {
+	guard(null_mut(), || {
+		let cursor = with_object(this, |this| object {
+			document: this.document.clone(),
+			path: this.path.clone()
+		})?;
+
+		Ok(c_handles::insert_object(Box::new(cursor))?)
+	})
+}
+
+//Move the cursor to the object or array holding its value. Fails with JSAFE_ERR_NOT_FOUND at the root.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_parent(this: *mut object) -> jsafe_status {
+	guard_status(|| {
+		if with_object(this, |this| this.path.pop())?.is_none() {
+			return Err(Failure::new(jsafe_status::JSAFE_ERR_NOT_FOUND, String::from("the cursor is at the root")));
+		}
+		Ok(())
+	})
+}
+
+//Return the cursor's position as a JSON Pointer ("" at the root)
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_current_path(this: *mut object) -> *mut string {
+	guard(null_mut(), || {
+		let path = with_object(this, |this| path_pointer(&this.path))?;
+		Ok(create_string(c_string(path)?))
+	})
+}
@@ -0,0 +1,164 @@
+use json::{PathSegment, Value};
+use std::ffi::{c_char, c_double};
+use std::ptr::null_mut;
+
+use super::c_errors::{Failure, guard_status, jsafe_status, text_arg};
+use super::c_handles::{self, with_object};
+use super::c_json::{object, value, create_object, path_pointer, take_value, with_current, wrong_type};
+
+//Editing functions change the value under a cursor in place. Other cursors address values by
+//path, so removing or inserting array items moves them onto whatever item now has their index.
+
+//Replace the value under the cursor with a number
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_set_num(this: *mut object, val: c_double) -> jsafe_status {
+	guard_status(|| {
+		with_current(this, |x| {
+			*x = Value::Number(val);
+			Ok(())
+		})
+	})
+}
+
+//Replace the value under the cursor with text
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_set_text(this: *mut object, text: *const c_char) -> jsafe_status {
+	guard_status(|| {
+		let str = text_arg(text, "text")?;
+
+		with_current(this, |x| {
+			*x = Value::Text(str.to_string());
+			Ok(())
+		})
+	})
+}
+
+//Replace the value under the cursor with a bool
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_set_bool(this: *mut object, val: bool) -> jsafe_status {
+	guard_status(|| {
+		with_current(this, |x| {
+			*x = Value::Bool(val);
+			Ok(())
+		})
+	})
+}
+
+//Replace the value under the cursor with null
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_set_null(this: *mut object) -> jsafe_status {
+	guard_status(|| {
+		with_current(this, |x| {
+			*x = Value::Null;
+			Ok(())
+		})
+	})
+}
+
+//Remove a member of the object under the cursor. Fails with JSAFE_ERR_NOT_FOUND if the key is missing.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_o_remove(this: *mut object, key: *const c_char) -> jsafe_status {
+	guard_status(|| {
+		let str = text_arg(key, "key")?;
+
+		with_current(this, |x| match x {
+			Value::Object(x) => match x.remove(str) {
+				Some(_) => Ok(()),
+				None => Err(Failure::new(jsafe_status::JSAFE_ERR_NOT_FOUND, format!("the object has no key \"{}\"", str)))
+			},
+			x => Err(wrong_type("an object", x))
+		})
+	})
+}
+
+fn out_of_range(index: usize, len: usize) -> Failure {
+	return Failure::new(jsafe_status::JSAFE_ERR_NOT_FOUND, format!("index {} is out of range for an array of {} items", index, len));
+}
+
+//Remove an item of the array under the cursor, moving the items after it down by one
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_a_remove(this: *mut object, index: usize) -> jsafe_status {
+	guard_status(|| {
+		with_current(this, |x| match x {
+			Value::Array(x) if index < x.len() => {
+				x.remove(index);
+				Ok(())
+			}
+			Value::Array(x) => Err(out_of_range(index, x.len())),
+			x => Err(wrong_type("an array", x))
+		})
+	})
+}
+
+//Insert a value into the array under the cursor before index, or at the end if index is the
+//array's length. The value handle is consumed, even if the call fails, and NULL inserts a null.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_a_insert(this: *mut object, index: usize, val: *mut value) -> jsafe_status {
+	guard_status(|| {
+		let val = take_value(this, val)?;
+
+		with_current(this, |x| match x {
+			Value::Array(x) if index <= x.len() => {
+				x.insert(index, val);
+				Ok(())
+			}
+			Value::Array(x) => Err(out_of_range(index, x.len())),
+			x => Err(wrong_type("an array", x))
+		})
+	})
+}
+
+//Remove every member of the object or array under the cursor
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_clear(this: *mut object) -> jsafe_status {
+	guard_status(|| {
+		with_current(this, |x| match x {
+			Value::Object(x) => {
+				x.clear();
+				Ok(())
+			}
+			Value::Array(x) => {
+				x.clear();
+				Ok(())
+			}
+			x => Err(wrong_type("an object or array", x))
+		})
+	})
+}
+
+//Move the value under the cursor out of its document into a new one, stored in detached (NULL on
+//failure). The cursor moves up to the object or array that held the value. Fails with
+//JSAFE_ERR_NOT_FOUND at the root; free the document instead.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_detach(this: *mut object, detached: *mut *mut object) -> jsafe_status {
+	guard_status(|| {
+		if detached.is_null() {
+			return Err(Failure::new(jsafe_status::JSAFE_ERR_NULL, String::from("detached is NULL")));
+		}
+		*detached = null_mut();
+
+		let taken = with_object(this, |this| -> Result<Value, Failure> {
+			let last = match this.path.last() {
+				Some(x) => x.clone(),
+				None => return Err(Failure::new(jsafe_status::JSAFE_ERR_NOT_FOUND, String::from("the cursor is at the root")))
+			};
+
+			let parent = &this.path[..this.path.len() - 1];
+			let mut root = c_handles::lock(&this.document);
+			let taken = match (root.get_path_mut(parent), last) {
+				(Some(Value::Object(x)), PathSegment::Key(key)) => x.remove(&key),
+				(Some(Value::Array(x)), PathSegment::Index(index)) if index < x.len() => Some(x.remove(index)),
+				_ => None
+			};
+			let taken = taken.ok_or_else(|| Failure::new(jsafe_status::JSAFE_ERR_NOT_FOUND, format!("the value at \"{}\" has been removed", path_pointer(&this.path))))?;
+			drop(root);
+
+			this.path.pop();
+			Ok(taken)
+		})??;
+
+		//The handle table's lock is released before the new document is added to it
+		*detached = create_object(taken)?;
+		Ok(())
+	})
+}
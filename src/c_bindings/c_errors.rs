@@ -0,0 +1,159 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+
+//Status codes returned by C functions
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum jsafe_status {
+	JSAFE_OK = 0,
+	//A required pointer or handle was NULL
+	JSAFE_ERR_NULL = 1,
+	//The handle was already freed, or never came from this library
+	JSAFE_ERR_INVALID_HANDLE = 2,
+	//A C string passed in wasn't valid UTF-8
+	JSAFE_ERR_UTF8 = 3,
+	//Text to return contains a NUL byte, so it can't be a C string
+	JSAFE_ERR_EMBEDDED_NUL = 4,
+	//The input wasn't valid JSON
+	JSAFE_ERR_PARSE = 5,
+	//The value under the pointer has the wrong type for the operation
+	JSAFE_ERR_TYPE = 6,
+	//An array index or JSON Pointer doesn't address an existing value
+	JSAFE_ERR_NOT_FOUND = 7,
+	//A bug inside the library was caught before it could unwind into C
+	JSAFE_ERR_PANIC = 8,
+	//A file couldn't be read or written
+	JSAFE_ERR_IO = 9,
+	//No handle could be given out because too many are live; free some first
+	JSAFE_ERR_TOO_MANY_HANDLES = 10
+}
+
+impl jsafe_status {
+	fn describe(self) -> &'static str {
+		match self {
+			jsafe_status::JSAFE_OK => "",
+			jsafe_status::JSAFE_ERR_NULL => "a required argument was NULL",
+			jsafe_status::JSAFE_ERR_INVALID_HANDLE => "the handle was freed or didn't come from jsafe",
+			jsafe_status::JSAFE_ERR_UTF8 => "text isn't valid UTF-8",
+			jsafe_status::JSAFE_ERR_EMBEDDED_NUL => "text contains a NUL byte",
+			jsafe_status::JSAFE_ERR_PARSE => "text isn't valid JSON",
+			jsafe_status::JSAFE_ERR_TYPE => "the value has the wrong type",
+			jsafe_status::JSAFE_ERR_NOT_FOUND => "no value at that position",
+			jsafe_status::JSAFE_ERR_PANIC => "internal error",
+			jsafe_status::JSAFE_ERR_IO => "a file couldn't be read or written",
+			jsafe_status::JSAFE_ERR_TOO_MANY_HANDLES => "too many jsafe handles are live"
+		}
+	}
+}
+
+//A failed call: the status returned to C and a message for jsafe_last_error_message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+	pub status: jsafe_status,
+	pub message: String
+}
+
+impl Failure {
+	pub fn new(status: jsafe_status, message: String) -> Failure {
+		return Failure { status, message };
+	}
+}
+
+impl From<jsafe_status> for Failure {
+	fn from(status: jsafe_status) -> Failure {
+		return Failure::new(status, status.describe().to_string());
+	}
+}
+
+struct LastError {
+	status: jsafe_status,
+	message: CString
+}
+
+thread_local! {
+	static LAST_ERROR: RefCell<LastError> = RefCell::new(LastError {
+		status: jsafe_status::JSAFE_OK,
+		message: CString::default()
+	});
+}
+
+fn set_last_error(failure: Failure) {
+	//Embedded NULs would cut the message short, so they are written out
+	let message = CString::new(failure.message.replace('\0', "\\0")).unwrap_or_default();
+
+	LAST_ERROR.with(|x| *x.borrow_mut() = LastError { status: failure.status, message });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+	if let Some(x) = payload.downcast_ref::<&str>() {
+		return format!("internal error: {}", x);
+	}
+	if let Some(x) = payload.downcast_ref::<String>() {
+		return format!("internal error: {}", x);
+	}
+	return String::from("internal error");
+}
+
+//Run the body of an exported function, catching panics so they never unwind into C and recording
+//the outcome for jsafe_last_error
+fn run<T, F: FnOnce() -> Result<T, Failure>>(f: F) -> Result<T, jsafe_status> {
+	let result = match panic::catch_unwind(AssertUnwindSafe(f)) {
+		Ok(x) => x,
+		Err(payload) => Err(Failure::new(jsafe_status::JSAFE_ERR_PANIC, panic_message(payload.as_ref())))
+	};
+
+	match result {
+		Ok(x) => {
+			set_last_error(Failure::from(jsafe_status::JSAFE_OK));
+			return Ok(x);
+		}
+		Err(failure) => {
+			let status = failure.status;
+			set_last_error(failure);
+			return Err(status);
+		}
+	}
+}
+
+//Run the body of an exported function, returning fallback if it fails
+pub fn guard<T, F: FnOnce() -> Result<T, Failure>>(fallback: T, f: F) -> T {
+	return run(f).unwrap_or(fallback);
+}
+
+//Run the body of an exported function that only reports a status
+pub fn guard_status<F: FnOnce() -> Result<(), Failure>>(f: F) -> jsafe_status {
+	match run(f) {
+		Ok(()) => jsafe_status::JSAFE_OK,
+		Err(x) => x
+	}
+}
+
+//Borrow a C string argument as UTF-8. what names the argument in error messages.
+pub unsafe fn text_arg<'a>(text: *const c_char, what: &str) -> Result<&'a str, Failure> {
+	if text.is_null() {
+		return Err(Failure::new(jsafe_status::JSAFE_ERR_NULL, format!("{} is NULL", what)));
+	}
+
+	return CStr::from_ptr(text).to_str().map_err(|e| Failure::new(jsafe_status::JSAFE_ERR_UTF8, format!("{} isn't valid UTF-8: {}", what, e)));
+}
+
+//Convert text going back to C, failing on embedded NULs
+pub fn c_string(text: String) -> Result<CString, Failure> {
+	return CString::new(text).map_err(|e| Failure::new(jsafe_status::JSAFE_ERR_EMBEDDED_NUL, format!("text contains a NUL byte at offset {}", e.nul_position())));
+}
+
+//Status of the last jsafe call made on this thread
+#[no_mangle]
+pub extern "C" fn jsafe_last_error() -> jsafe_status {
+	LAST_ERROR.with(|x| x.borrow().status)
+}
+
+//Message describing the last failed jsafe call on this thread, or "" if it succeeded. The text
+//belongs to the library and stays valid until the next jsafe call on this thread.
+#[no_mangle]
+pub extern "C" fn jsafe_last_error_message() -> *const c_char {
+	LAST_ERROR.with(|x| x.borrow().message.as_ptr())
+}
@@ -1,14 +1,53 @@
 use json::formatting;
 
-use std::ffi::{CString};
+use std::ffi::c_char;
+use std::fs;
+use std::ptr::null_mut;
 
-use super::c_json::{object,create_string,object_is_null};
+use super::c_errors::{Failure, c_string, guard, guard_status, jsafe_status, text_arg};
+use super::c_json::{object, string, create_string, create_string_len, with_current};
 
+//Options for jsafe_to_file. NULL options write compact JSON.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+pub struct jsafe_write_options {
+	//Indent the output as jsafe_to_pretty does
+	pub pretty: bool,
+	//Spaces per level when pretty, or 0 to indent with tabs
+	pub spaces: usize
+}
+
+//Return an indented string representation of an object. NULs in strings are escaped as \u0000.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_to_pretty(this: *mut object, spaces: usize) -> *mut string {
+	guard(null_mut(), || {
+		let text = with_current(this, |x| Ok(formatting::prettify(x, spaces)))?;
+		Ok(create_string(c_string(text)?))
+	})
+}
+
+//Return an indented string representation of an object and its length in bytes
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_to_pretty(this: *mut object, spaces: usize) -> *mut CString {
-	if object_is_null(this) {
-		return create_string(CString::new("").unwrap());
-	}
+pub unsafe extern "C" fn jsafe_to_pretty_len(this: *mut object, spaces: usize, len: *mut usize) -> *mut string {
+	guard(null_mut(), || {
+		let text = with_current(this, |x| Ok(formatting::prettify(x, spaces)))?;
+		create_string_len(text, len)
+	})
+}
+
+//Write the value under a cursor to a file, replacing it, followed by a newline. A file that can't be
+//written gives JSAFE_ERR_IO.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_to_file(this: *mut object, path: *const c_char, options: *const jsafe_write_options) -> jsafe_status {
+	guard_status(|| {
+		let file = text_arg(path, "path")?;
+
+		let mut text = with_current(this, |x| match options.as_ref() {
+			Some(options) if options.pretty => Ok(formatting::prettify(x, options.spaces)),
+			_ => Ok(formatting::serialize(x))
+		})?;
+		text.push('\n');
 
-	return create_string(CString::new(formatting::prettify((*(*this).current).as_ref(), spaces).as_bytes()).unwrap());
-}
\ No newline at end of file
+		fs::write(file, text).map_err(|e| Failure::new(jsafe_status::JSAFE_ERR_IO, format!("can't write {}: {}", file, e)))
+	})
+}
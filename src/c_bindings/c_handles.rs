@@ -0,0 +1,227 @@
+use super::c_errors::jsafe_status;
+use super::c_iter::iterator;
+use super::c_json::object;
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ffi::c_char;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+//Handles given to C are not pointers. They pack a shard, the kind of table, a slot and a
+//generation into a pointer sized integer, so a freed, forged or mixed up handle is detected
+//instead of being dereferenced:
+//
+//| generation (upper half) | slot + 1 | kind (KIND_BITS) | shard (SHARD_BITS) |
+//
+//Handles are pointer sized, so the limits depend on the target. With 64 bit pointers a shard holds
+//2^27 - 1 live items and a slot can be reused 2^32 times; with 32 bit pointers that drops to 2047
+//live items and 2^16 reuses. A slot whose generation has run out is retired rather than wrapped, so
+//a stale handle can never match a new item. Once a shard has no slot left, insert fails with
+//JSAFE_ERR_TOO_MANY_HANDLES.
+const SHARD_BITS: u32 = 4;
+const SHARDS: usize = 1 << SHARD_BITS;
+const KIND_BITS: u32 = 1;
+const LOW_BITS: u32 = SHARD_BITS + KIND_BITS;
+const HALF_BITS: u32 = usize::BITS / 2;
+const SLOT_MASK: usize = (1 << (HALF_BITS - LOW_BITS)) - 1;
+const GENERATION_MASK: usize = (1 << HALF_BITS) - 1;
+
+struct Slot<T> {
+	//Bumped every time the slot is freed, so old handles stop matching
+	generation: usize,
+	item: Option<Box<T>>
+}
+
+struct Slab<T> {
+	slots: Vec<Slot<T>>,
+	free: Vec<usize>
+}
+
+impl<T> Slab<T> {
+	//Make an emptied slot available again under a new generation, unless its generations have
+	//run out
+	fn release(&mut self, slot: usize) {
+		if self.slots[slot].generation == GENERATION_MASK {
+			return;
+		}
+
+		self.slots[slot].generation += 1;
+		self.free.push(slot);
+	}
+}
+
+//Items are spread over shards so threads working on different documents rarely share a lock
+struct Table<T> {
+	kind: usize,
+	shards: [Mutex<Slab<T>>; SHARDS]
+}
+
+impl<T> Table<T> {
+	const fn new(kind: usize) -> Table<T> {
+		return Table {
+			kind,
+			shards: [const { Mutex::new(Slab { slots: Vec::new(), free: Vec::new() }) }; SHARDS]
+		};
+	}
+
+	fn encode(&self, shard: usize, slot: usize, generation: usize) -> *mut T {
+		return ((generation << HALF_BITS) | ((slot + 1) << LOW_BITS) | (self.kind << SHARD_BITS) | shard) as *mut T;
+	}
+
+	//Split a handle into shard, slot and generation. NULL and handles from other tables are rejected.
+	fn decode(&self, handle: *mut T) -> Result<(usize, usize, usize), jsafe_status> {
+		let bits = handle as usize;
+		if bits == 0 {
+			return Err(jsafe_status::JSAFE_ERR_NULL);
+		}
+
+		let slot = (bits >> LOW_BITS) & SLOT_MASK;
+		if slot == 0 || (bits >> SHARD_BITS) & ((1 << KIND_BITS) - 1) != self.kind {
+			return Err(jsafe_status::JSAFE_ERR_INVALID_HANDLE);
+		}
+
+		return Ok((bits & (SHARDS - 1), slot - 1, bits >> HALF_BITS));
+	}
+
+	fn insert(&self, to_add: Box<T>) -> Result<*mut T, jsafe_status> {
+		let shard = THREAD_SHARD.with(|x| x.get());
+		let mut slab = lock(&self.shards[shard]);
+
+		let slot = match slab.free.pop() {
+			Some(x) => x,
+			//slot + 1 has to fit in SLOT_MASK
+			None if slab.slots.len() < SLOT_MASK => {
+				slab.slots.push(Slot { generation: 0, item: None });
+				slab.slots.len() - 1
+			}
+			None => return Err(jsafe_status::JSAFE_ERR_TOO_MANY_HANDLES)
+		};
+
+		slab.slots[slot].item = Some(to_add);
+		return Ok(self.encode(shard, slot, slab.slots[slot].generation));
+	}
+
+	fn with<R, F: FnOnce(&mut T) -> R>(&self, handle: *mut T, f: F) -> Result<R, jsafe_status> {
+		let (shard, slot, generation) = self.decode(handle)?;
+		let mut slab = lock(&self.shards[shard]);
+
+		match slab.slots.get_mut(slot) {
+			Some(Slot { generation: g, item: Some(x) }) if *g == generation => Ok(f(x)),
+			_ => Err(jsafe_status::JSAFE_ERR_INVALID_HANDLE)
+		}
+	}
+
+	fn remove(&self, handle: *mut T) -> Result<Box<T>, jsafe_status> {
+		let (shard, slot, generation) = self.decode(handle)?;
+		let mut slab = lock(&self.shards[shard]);
+
+		let to_return = match slab.slots.get_mut(slot) {
+			Some(x) if x.generation == generation && x.item.is_some() => x.item.take().unwrap(),
+			_ => return Err(jsafe_status::JSAFE_ERR_INVALID_HANDLE)
+		};
+
+		slab.release(slot);
+		return Ok(to_return);
+	}
+
+	fn clear(&self) {
+		for shard in self.shards.iter() {
+			let mut slab = lock(shard);
+
+			for i in 0..slab.slots.len() {
+				if slab.slots[i].item.take().is_some() {
+					slab.release(i);
+				}
+			}
+		}
+	}
+}
+
+static OBJECTS: Table<object> = Table::new(0);
+static ITERATORS: Table<iterator> = Table::new(1);
+
+//Strings are NUL terminated bytes keyed by the address C holds
+type StringTable = Option<HashMap<usize, Box<[u8]>>>;
+static STRINGS: [Mutex<StringTable>; SHARDS] = [const { Mutex::new(None) }; SHARDS];
+
+//Each thread allocates from its own shard
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+thread_local! {
+	static THREAD_SHARD: Cell<usize> = Cell::new(NEXT_SHARD.fetch_add(1, Ordering::Relaxed) % SHARDS);
+}
+
+//A panic while a lock was held doesn't leave the table itself inconsistent, so poisoning is ignored
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	return mutex.lock().unwrap_or_else(|e| e.into_inner());
+}
+
+//Add an object to the table and return its handle
+pub fn insert_object(to_add: Box<object>) -> Result<*mut object, jsafe_status> {
+	return OBJECTS.insert(to_add);
+}
+
+//Run f on the object behind a handle, holding its shard's lock
+pub fn with_object<T, F: FnOnce(&mut object) -> T>(handle: *mut object, f: F) -> Result<T, jsafe_status> {
+	return OBJECTS.with(handle, f);
+}
+
+//Remove an object from the table. Freeing twice is reported rather than undefined.
+pub fn remove_object(handle: *mut object) -> Result<Box<object>, jsafe_status> {
+	return OBJECTS.remove(handle);
+}
+
+pub fn insert_iterator(to_add: Box<iterator>) -> Result<*mut iterator, jsafe_status> {
+	return ITERATORS.insert(to_add);
+}
+
+pub fn with_iterator<T, F: FnOnce(&mut iterator) -> T>(handle: *mut iterator, f: F) -> Result<T, jsafe_status> {
+	return ITERATORS.with(handle, f);
+}
+
+pub fn remove_iterator(handle: *mut iterator) -> Result<Box<iterator>, jsafe_status> {
+	return ITERATORS.remove(handle);
+}
+
+fn string_shard(address: usize) -> usize {
+	//Allocations are aligned, so skip the low bits that are always zero
+	return (address >> 4) % SHARDS;
+}
+
+//Keep a string alive until C frees it, returning the address of its first character. bytes must
+//end with a NUL.
+pub fn insert_string(mut bytes: Box<[u8]>) -> *mut c_char {
+	let to_return = bytes.as_mut_ptr() as *mut c_char;
+
+	let mut strings = lock(&STRINGS[string_shard(to_return as usize)]);
+	strings.get_or_insert_with(HashMap::new).insert(to_return as usize, bytes);
+
+	return to_return;
+}
+
+pub fn remove_string(this: *mut c_char) -> Result<(), jsafe_status> {
+	if this.is_null() {
+		return Err(jsafe_status::JSAFE_ERR_NULL);
+	}
+
+	let mut strings = lock(&STRINGS[string_shard(this as usize)]);
+	if strings.as_mut().and_then(|x| x.remove(&(this as usize))).is_none() {
+		return Err(jsafe_status::JSAFE_ERR_INVALID_HANDLE);
+	}
+
+	//Give the table back once its last string is freed
+	if strings.as_ref().is_some_and(|x| x.is_empty()) {
+		*strings = None;
+	}
+	return Ok(());
+}
+
+//Free every object, iterator and string. Outstanding handles become invalid.
+pub fn clear() {
+	OBJECTS.clear();
+	ITERATORS.clear();
+
+	for shard in STRINGS.iter() {
+		*lock(shard) = None;
+	}
+}
@@ -0,0 +1,204 @@
+use json::{PathSegment, Value};
+use std::ffi::{CString, c_char, c_void};
+use std::ptr::{null, null_mut};
+use std::sync::{Arc, Mutex};
+
+use super::c_errors::{Failure, c_string, guard, guard_status, jsafe_status};
+use super::c_handles::{self, with_iterator, with_object};
+use super::c_json::{object, string, create_string, with_current, wrong_type};
+
+//Walks the members of an object or array. The members are listed when iteration starts, so
+//changing the document while iterating is safe. Object members removed since then are skipped and
+//new ones aren't visited. Array elements are walked by index, so iteration over an array ends as
+//soon as its length changes; elements replaced in place are visited with their new value.
+#[allow(non_camel_case_types)]
+pub struct iterator {
+	document: Arc<Mutex<Value>>,
+	//Path to the object or array being walked
+	path: Vec<PathSegment>,
+	//Object keys in sorted order, or array indexes
+	members: Vec<PathSegment>,
+	//Length of the array when iteration started, or None for an object
+	length: Option<usize>,
+	//None before the first call to jsafe_iter_next
+	position: Option<usize>
+}
+
+impl iterator {
+	//Path to the member the iterator is on
+	fn member_path(&self) -> Result<Vec<PathSegment>, Failure> {
+		match self.position.and_then(|x| self.members.get(x)) {
+			Some(x) => {
+				let mut to_return = self.path.clone();
+				to_return.push(x.clone());
+				Ok(to_return)
+			}
+			None => Err(Failure::new(jsafe_status::JSAFE_ERR_NOT_FOUND, String::from("the iterator isn't on a member; call jsafe_iter_next first")))
+		}
+	}
+}
+
+//Whether the array being walked has been resized (or replaced) since iteration started
+fn resized(root: &Value, path: &[PathSegment], length: Option<usize>) -> bool {
+	match length {
+		Some(n) => !matches!(root.get_path(path), Some(Value::Array(x)) if x.len() == n),
+		None => false
+	}
+}
+
+//Called by jsafe_foreach for each member. key is NULL for array elements. member is a cursor on
+//the member that is freed when the callback returns. Return false to stop early.
+#[allow(non_camel_case_types)]
+pub type jsafe_foreach_fn = Option<unsafe extern "C" fn(key: *const c_char, index: usize, member: *mut object, user_data: *mut c_void) -> bool>;
+
+//List the members of the value under a cursor, giving an iterator before the first of them
+unsafe fn members(this: *mut object) -> Result<iterator, Failure> {
+	let (document, path) = with_object(this, |this| (this.document.clone(), this.path.clone()))?;
+
+	let (members, length) = with_current(this, |x| match x {
+		Value::Object(x) => {
+			let mut keys: Vec<&String> = x.keys().collect();
+			keys.sort();
+			Ok((keys.into_iter().map(|x| PathSegment::Key(x.clone())).collect(), None))
+		}
+		Value::Array(x) => Ok(((0..x.len()).map(PathSegment::Index).collect(), Some(x.len()))),
+		x => Err(wrong_type("an object or array", x))
+	})?;
+
+	return Ok(iterator { document, path, members, length, position: None });
+}
+
+//Start iterating over the object or array under a cursor. Object keys are visited in sorted
+//order. Call jsafe_iter_next to move to the first member, and free the iterator with jsafe_iter_free.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_iter_begin(this: *mut object) -> *mut iterator {
+	guard(null_mut(), || {
+		Ok(c_handles::insert_iterator(Box::new(members(this)?))?)
+	})
+}
+
+//Move to the next member. Returns false once every member has been visited, once an array being
+//walked changes length, or on error.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_iter_next(iter: *mut iterator) -> bool {
+	guard(false, || {
+		let found = with_iterator(iter, |iter| {
+			let root = c_handles::lock(&iter.document);
+			let mut next = iter.position.map_or(0, |x| x + 1);
+			if resized(&root, &iter.path, iter.length) {
+				next = iter.members.len();
+			}
+
+			//Skip members removed since iteration started
+			while next < iter.members.len() {
+				let mut path = iter.path.clone();
+				path.push(iter.members[next].clone());
+				if root.get_path(&path).is_some() {
+					break;
+				}
+				next += 1;
+			}
+
+			iter.position = Some(next.min(iter.members.len()));
+			next < iter.members.len()
+		})?;
+
+		Ok(found)
+	})
+}
+
+//Return the key of the current member. Fails with JSAFE_ERR_TYPE when iterating over an array.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_iter_key(iter: *mut iterator) -> *mut string {
+	guard(null_mut(), || {
+		let key = with_iterator(iter, |iter| match iter.member_path()?.pop() {
+			Some(PathSegment::Key(x)) => Ok(x),
+			_ => Err(Failure::new(jsafe_status::JSAFE_ERR_TYPE, String::from("array elements don't have keys")))
+		})??;
+
+		Ok(create_string(c_string(key)?))
+	})
+}
+
+//Return the position of the current member: its index in an array, or its place in the sorted
+//keys of an object
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_iter_index(iter: *mut iterator) -> usize {
+	guard(0, || {
+		let index = with_iterator(iter, |iter| -> Result<usize, Failure> {
+			match iter.member_path()?.pop() {
+				Some(PathSegment::Index(x)) => Ok(x),
+				_ => Ok(iter.position.unwrap())
+			}
+		})??;
+
+		Ok(index)
+	})
+}
+
+//Return a new cursor on the current member. Free it with jsafe_free_value.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_iter_enter(iter: *mut iterator) -> *mut object {
+	guard(null_mut(), || {
+		let cursor = with_iterator(iter, |iter| -> Result<object, Failure> {
+			Ok(object {
+				document: iter.document.clone(),
+				path: iter.member_path()?
+			})
+		})??;
+
+		Ok(c_handles::insert_object(Box::new(cursor))?)
+	})
+}
+
+//Free an iterator. The document it walked is not affected.
+#[no_mangle]
+pub extern "C" fn jsafe_iter_free(iter: *mut iterator) -> jsafe_status {
+	guard_status(|| {
+		c_handles::remove_iterator(iter)?;
+		Ok(())
+	})
+}
+
+//Call callback for each member of the object or array under a cursor, in the same order as
+//jsafe_iter_next. Stops early if callback returns false. The callback may use the jsafe API,
+//including changing the document, with the same effect on the remaining members as for
+//jsafe_iter_next: an array stops being walked once its length changes.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_foreach(this: *mut object, callback: jsafe_foreach_fn, user_data: *mut c_void) -> jsafe_status {
+	guard_status(|| {
+		let callback = callback.ok_or_else(|| Failure::new(jsafe_status::JSAFE_ERR_NULL, String::from("callback is NULL")))?;
+		let iterator { document, path, members, length, .. } = members(this)?;
+
+		for (i, member) in members.into_iter().enumerate() {
+			let (key, index) = match &member {
+				PathSegment::Key(x) => (Some(c_string(x.clone())?), i),
+				PathSegment::Index(x) => (None, *x)
+			};
+
+			let mut member_path = path.clone();
+			member_path.push(member);
+			{
+				let root = c_handles::lock(&document);
+				if resized(&root, &path, length) {
+					break;
+				}
+				if root.get_path(&member_path).is_none() {
+					continue;
+				}
+			}
+
+			//No locks are held while the callback runs, so it can call back into the library
+			let cursor = c_handles::insert_object(Box::new(object { document: document.clone(), path: member_path }))?;
+			let go_on = callback(key.as_ref().map_or(null(), |x: &CString| x.as_ptr()), index, cursor, user_data);
+
+			//The callback may have freed the cursor itself
+			let _ = c_handles::remove_object(cursor);
+			if !go_on {
+				break;
+			}
+		}
+
+		Ok(())
+	})
+}
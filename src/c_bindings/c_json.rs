@@ -1,389 +1,442 @@
-use json::Value;
-use std::ffi::{CStr, CString, c_char, c_int, c_double, c_uint};
+use json::{PathSegment, Value};
+use json::formatting;
+use json::pointer;
+use std::ffi::{CString, c_char, c_double};
 use std::ptr::{null_mut};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
-//keep track of objects allocated from our library
-pub static OBJECTS: Mutex<Vec<Box<object>>> = Mutex::new(Vec::new());
-pub static STRINGS: Mutex<Vec<Box<CString>>> = Mutex::new(Vec::new());
+use super::c_errors::{Failure, c_string, guard, guard_status, jsafe_status, text_arg};
+use super::c_handles::{self, with_object};
 
-//We do not want objects to spawn pointers that will become invalid when the object is deleted
+//A cursor on a document. The cursor stores the path to its value rather than a pointer, so a
+//change made through another cursor on the same document can't leave it dangling.
 #[allow(non_camel_case_types)]
 pub struct object {
-	pub root: Value,
-	pub current: *mut Value
+	//Shared by every cursor on the document
+	pub document: Arc<Mutex<Value>>,
+	//Keys and indexes leading from the root to the value under the cursor
+	pub path: Vec<PathSegment>
 }
 
-//tell rust that we are only referencing values in our struct
-unsafe impl Send for object {}
-
-//Helper function to add a new object to the global pool
-pub fn create_object(to_add: Value) -> *mut object {
-	let mut val = Box::new(object {
-		root: to_add,
-		current: null_mut()
-	});
+impl object {
+	//Run f on the value under the cursor. Fails if the path no longer leads anywhere.
+	pub fn current<T, F: FnOnce(&mut Value) -> Result<T, Failure>>(&self, f: F) -> Result<T, Failure> {
+		let mut root = c_handles::lock(&self.document);
+		match root.get_path_mut(&self.path) {
+			Some(x) => f(x),
+			None => Err(Failure::new(jsafe_status::JSAFE_ERR_NOT_FOUND, format!("the value at \"{}\" has been removed", path_pointer(&self.path))))
+		}
+	}
+}
 
-	//initialize current
-	val.current = &mut val.root;
+//Detached values live in the same handle table as documents, but the header gives them their own type
+#[allow(non_camel_case_types)]
+pub type value = object;
 
-	//get a pointer to our object
-	let to_return: *mut object = val.as_mut();
+//Strings handed to C: NUL terminated characters, freed with jsafe_free_string
+#[allow(non_camel_case_types)]
+pub type string = c_char;
+
+//Helper function to add a new document to the handle table. The returned handle is not a real
+//pointer and must only be passed back to this library.
+pub fn create_object(to_add: Value) -> Result<*mut object, Failure> {
+	return Ok(c_handles::insert_object(Box::new(object {
+		document: Arc::new(Mutex::new(to_add)),
+		path: Vec::new()
+	}))?);
+}
 
-	//append object to our array
-	OBJECTS.lock().unwrap().push(val);
+//Run f on the value under a cursor
+pub unsafe fn with_current<T, F: FnOnce(&mut Value) -> Result<T, Failure>>(this: *mut object, f: F) -> Result<T, Failure> {
+	return with_object(this, |this| this.current(f))?;
+}
 
-	//send our object to C
+//Write a cursor's path as a JSON Pointer
+pub fn path_pointer(path: &[PathSegment]) -> String {
+	let mut to_return = String::new();
+	for segment in path {
+		match segment {
+			PathSegment::Key(x) => to_return += &format!("/{}", pointer::escape(x)),
+			PathSegment::Index(x) => to_return += &format!("/{}", x)
+		}
+	}
 	return to_return;
 }
 
-//Helper function to initialize a new string to the global pool
-pub fn create_string(to_add: CString) -> *mut CString {
-	let mut val = Box::new(to_add);
+//Turn a JSON Pointer into a cursor path, checking that it leads to a value
+fn pointer_path(root: &Value, text: &str) -> Result<Vec<PathSegment>, Failure> {
+	root.pointer(text).map_err(|e| Failure::new(jsafe_status::JSAFE_ERR_NOT_FOUND, e.to_string()))?;
+
+	let mut to_return = Vec::new();
+	let mut current = root;
+	for token in pointer::parse(text).unwrap() {
+		let segment = match current {
+			Value::Array(_) => match pointer::array_index(&token) {
+				Some(pointer::ArrayIndex::At(i)) => PathSegment::Index(i),
+				_ => unreachable!()
+			},
+			_ => PathSegment::Key(token)
+		};
+		current = current.get_path(std::slice::from_ref(&segment)).unwrap();
+		to_return.push(segment);
+	}
+
+	return Ok(to_return);
+}
 
-	let to_return: *mut CString = val.as_mut();
+//Helper function to hand a string to C. It stays valid until C frees it with jsafe_free_string.
+pub fn create_string(to_add: CString) -> *mut string {
+	return c_handles::insert_string(to_add.into_bytes_with_nul().into_boxed_slice());
+}
 
-	STRINGS.lock().unwrap().push(val);
+//Hand text that may contain NULs to C, storing its length (without the terminating NUL) in len
+pub unsafe fn create_string_len(to_add: String, len: *mut usize) -> Result<*mut string, Failure> {
+	if len.is_null() {
+		return Err(Failure::new(jsafe_status::JSAFE_ERR_NULL, String::from("len is NULL")));
+	}
 
-	return to_return;
+	let mut bytes = to_add.into_bytes();
+	*len = bytes.len();
+	bytes.push(0);
+	return Ok(c_handles::insert_string(bytes.into_boxed_slice()));
 }
 
-//Helper function to check if an object is null
-pub unsafe fn object_is_null(this: *mut object) -> bool{
-	this.is_null() || (*this).current.is_null()
+//Free a document, or one cursor on it if jsafe_cursor_clone made others. Freeing a handle twice
+//returns JSAFE_ERR_INVALID_HANDLE.
+#[no_mangle]
+pub extern "C" fn jsafe_free_value(this: *mut object) -> jsafe_status {
+	guard_status(|| {
+		c_handles::remove_object(this)?;
+		Ok(())
+	})
 }
 
-//Free a value
+//Free a detached value that was never added to a document
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_free_value(this: *mut object) {
-	let objects = &mut OBJECTS.lock().unwrap();
-
-	for i in 0..objects.len() {
-		let cur: *mut object = objects[i].as_mut();
-
-		//we are referencing the same object
-		if std::ptr::eq(this, cur) {
-
-			//delete the object from the global reference pool
-			objects.remove(i);
-			break;
-		}
-	}
+pub extern "C" fn jsafe_free_detached(this: *mut value) -> jsafe_status {
+	return jsafe_free_value(this);
 }
 
-//Free a string
+//Free a string. Freeing a string twice returns JSAFE_ERR_INVALID_HANDLE.
 #[no_mangle]
-pub extern "C" fn jsafe_free_string(this: *mut CString) {
-	let strings = &mut STRINGS.lock().unwrap();
-
-	for i in 0..strings.len() {
-		let cur: *mut CString = strings[i].as_mut();
-
-		//we are referencing the same object
-		if std::ptr::eq(this, cur) {
-
-			//delete the object from the global reference pool
-			strings.remove(i);
-			break;
-		}
-	}
+pub extern "C" fn jsafe_free_string(this: *mut string) -> jsafe_status {
+	guard_status(|| {
+		c_handles::remove_string(this)?;
+		Ok(())
+	})
 }
 
 //Free all memory
 #[no_mangle]
 pub extern "C" fn jsafe_cleanup() {
-	let mut objects = OBJECTS.lock().unwrap();
-	objects.clear();
-	objects.shrink_to(0);
-
-	let mut strings = STRINGS.lock().unwrap();
-	strings.clear();
-	strings.shrink_to(0);
+	guard((), || {
+		c_handles::clear();
+		Ok(())
+	})
 }
 
 //Return a new globally allocated object
 #[no_mangle]
 pub extern "C" fn jsafe_new_root() -> *mut object {
-	create_object(Value::obj())
+	guard(null_mut(), || create_object(Value::obj()))
 }
 
 //Reset an object's pointer to root
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_reset(this: *mut object) {
-	if object_is_null(this) {
-		return;
-	}
-
-	//reset pointer to root
-	(*this).current = (*this).root.as_mut();
+pub unsafe extern "C" fn jsafe_reset(this: *mut object) -> jsafe_status {
+	guard_status(|| {
+		with_object(this, |this| this.path.clear())?;
+		Ok(())
+	})
 }
 
-//Return a reference to a new json object
+//Constructors return detached values: handles like documents, owned by the caller until they are
+//freed with jsafe_free_detached or moved into a document with jsafe_o_add or jsafe_a_add.
+
+//Return a new detached json object
 #[no_mangle]
-pub extern "C" fn jsafe_new_obj() -> Box<Value> {
-	Box::new(Value::obj())
+pub extern "C" fn jsafe_new_obj() -> *mut value {
+	guard(null_mut(), || create_object(Value::obj()))
 }
 
-//Return a reference to a new json array
+//Return a new detached json array
 #[no_mangle]
-pub extern "C" fn jsafe_new_arr() -> Box<Value> {
-	Box::new(Value::arr())
+pub extern "C" fn jsafe_new_arr() -> *mut value {
+	guard(null_mut(), || create_object(Value::arr()))
 }
 
-//Return a new text object from a C string
+//Return a new text value from a C string. NULL gives empty text; invalid UTF-8 gives NULL.
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_new_text(text: *const c_char) -> Box<Value> {
-
-	if text.is_null() {
-		return Box::new(Value::Text("".to_string()));
-	}
+pub unsafe extern "C" fn jsafe_new_text(text: *const c_char) -> *mut value {
+	guard(null_mut(), || {
+		if text.is_null() {
+			return create_object(Value::Text("".to_string()));
+		}
 
-	//convert text to a string
-	let str = CStr::from_ptr(text).to_str().unwrap();
-	Box::new(Value::Text(str.to_string()))
+		//convert text to a string
+		let str = text_arg(text, "text")?;
+		create_object(Value::Text(str.to_string()))
+	})
 }
 
-//Return a new null object
+//Return a new null value
 #[no_mangle]
-pub extern "C" fn jsafe_new_null() -> Box<Value> {
-	Box::new(Value::Null)
+pub extern "C" fn jsafe_new_null() -> *mut value {
+	guard(null_mut(), || create_object(Value::Null))
 }
 
-//Return a new bool object
+//Return a new bool value
 #[no_mangle]
-pub extern "C" fn jsafe_new_bool(bool: c_int) -> Box<Value> {
-	Box::new(Value::Bool(!(bool == 0)))
+pub extern "C" fn jsafe_new_bool(val: bool) -> *mut value {
+	guard(null_mut(), || create_object(Value::Bool(val)))
 }
 
-//Return a new number object
+//Return a new number value
 #[no_mangle]
-pub extern "C" fn jsafe_new_num(val: c_double) -> Box<Value> {
-	Box::new(Value::Number(val))
+pub extern "C" fn jsafe_new_num(val: c_double) -> *mut value {
+	guard(null_mut(), || create_object(Value::Number(val)))
 }
 
-//Pre-allocate slots for the container (to speed up adding values)
-#[no_mangle]
-pub unsafe extern "C" fn jsafe_prealloc(this: *mut object, amount: usize) {
-	if object_is_null(this) {
-		return;
+//Take the value out of a handle passed to an add function. NULL stands for a null value.
+pub fn take_value(this: *mut object, to_add: *mut value) -> Result<Value, Failure> {
+	if to_add.is_null() {
+		return Ok(Value::Null);
+	}
+	if to_add == this {
+		return Err(Failure::new(jsafe_status::JSAFE_ERR_INVALID_HANDLE, String::from("a value can't be added to itself")));
 	}
 
-	(*(*this).current).pre_alloc(amount);
-}
+	let mut taken = *c_handles::remove_object(to_add)?;
 
-//Get a pointer to a Value from a string index
-#[no_mangle]
-pub unsafe extern "C" fn jsafe_o_get(this: *mut object, key: *const c_char) {
-	if  object_is_null(this) {
-		return;
+	//The only cursor on a whole document can hand the document over; otherwise its value is copied
+	if taken.path.is_empty() {
+		match Arc::try_unwrap(taken.document) {
+			Ok(x) => return Ok(x.into_inner().unwrap_or_else(|e| e.into_inner())),
+			Err(x) => taken.document = x
+		}
 	}
 
-	let str = CStr::from_ptr(key).to_str().unwrap();
-
-	//replace current with new value
-	(*this).current = (*(*this).current)[str].as_mut();
+	return taken.current(|x| Ok(x.clone()));
 }
 
-//Set a value from string index. This will free the value passed to it
+//Pre-allocate slots for the container (to speed up adding values)
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_o_add(this: *mut object, key: *const c_char, val: Option<Box<Value>>) {
-	if object_is_null(this) {
-		return;
-	}
+pub unsafe extern "C" fn jsafe_prealloc(this: *mut object, amount: usize) -> jsafe_status {
+	guard_status(|| {
+		with_current(this, |x| {
+			x.pre_alloc(amount);
+			Ok(())
+		})
+	})
+}
 
-	//accounting for NULL
-	let str = CStr::from_ptr(key).to_str().unwrap();
-	if val.is_none() {
-		(*(*this).current)[str] = Value::Null;
-	} else {
-		(*(*this).current)[str] = *val.unwrap();
-	}
+//Failure for an operation that needs a different type of value under the pointer
+pub fn wrong_type(expected: &str, found: &Value) -> Failure {
+	return Failure::new(jsafe_status::JSAFE_ERR_TYPE, format!("expected {}, found {}", expected, found.type_name()));
 }
 
-//Get a pointer to a value from a number index
+//Move the pointer to a member of an object, creating an empty object if the key is missing
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_a_get(this: *mut object, key: usize) {
-	if object_is_null(this) {
-		return;
-	}
-
-	if key >= (*(*this).current).len() {
-		return;
-	}
+pub unsafe extern "C" fn jsafe_o_get(this: *mut object, key: *const c_char) -> jsafe_status {
+	guard_status(|| {
+		let str = text_arg(key, "key")?;
+
+		with_object(this, |this| {
+			this.current(|x| match x {
+				Value::Object(x) => {
+					x.entry(str.to_string()).or_insert_with(Value::obj);
+					Ok(())
+				}
+				x => Err(wrong_type("an object", x))
+			})?;
+
+			this.path.push(PathSegment::Key(str.to_string()));
+			Ok(())
+		})?
+	})
+}
 
-	(*this).current = (*(*this).current)[key].as_mut();
+//Set a value from string index. The value handle is consumed, even if the call fails, and
+//NULL adds a null.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_o_add(this: *mut object, key: *const c_char, val: *mut value) -> jsafe_status {
+	guard_status(|| {
+		let val = take_value(this, val)?;
+		let str = text_arg(key, "key")?;
+
+		with_current(this, |x| match x {
+			Value::Object(x) => {
+				x.insert(str.to_string(), val);
+				Ok(())
+			}
+			x => Err(wrong_type("an object", x))
+		})
+	})
 }
 
-//Add a new value to an array. This will take ownership of the pointer.
+//Move the pointer to an item of an array
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_a_add(this: *mut object, to_add: Option<Box<Value>>) {
-	if object_is_null(this) {
-		return;
-	}
+pub unsafe extern "C" fn jsafe_a_get(this: *mut object, key: usize) -> jsafe_status {
+	guard_status(|| {
+		with_object(this, |this| {
+			let len = this.current(|x| match x {
+				Value::Array(x) => Ok(x.len()),
+				x => Err(wrong_type("an array", x))
+			})?;
+			if key >= len {
+				return Err(Failure::new(jsafe_status::JSAFE_ERR_NOT_FOUND, format!("index {} is out of range for an array of {} items", key, len)));
+			}
+
+			this.path.push(PathSegment::Index(key));
+			Ok(())
+		})?
+	})
+}
 
-	//shorthand to add a null if you run jsafe_add(val, NULL)
-	if to_add.is_none() {
-		(*(*this).current).add(Value::Null);
-	}
+//Move the pointer to the value addressed by a JSON Pointer (from the root). If the pointer is
+//invalid or doesn't resolve, JSAFE_ERR_NOT_FOUND is returned and the position is left unchanged.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_pointer_get(this: *mut object, pointer: *const c_char) -> jsafe_status {
+	guard_status(|| {
+		let str = text_arg(pointer, "pointer")?;
+
+		with_object(this, |this| {
+			this.path = pointer_path(&c_handles::lock(&this.document), str)?;
+			Ok(())
+		})?
+	})
+}
 
-	//add item to list
-	(*(*this).current).add(*to_add.unwrap());
+//Add a new value to an array. The value handle is consumed, even if the call fails, and NULL
+//adds a null.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_a_add(this: *mut object, to_add: *mut value) -> jsafe_status {
+	guard_status(|| {
+		let to_add = take_value(this, to_add)?;
+
+		with_current(this, |x| match x {
+			Value::Array(x) => {
+				x.push(to_add);
+				Ok(())
+			}
+			x => Err(wrong_type("an array", x))
+		})
+	})
 }
 
-//Return a string representation of an object
+//Return a string representation of an object. NULs in strings are escaped as \u0000.
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_to_string(this: *mut object) -> *mut CString {
-	if object_is_null(this) {
-		return create_string(CString::new("Null").unwrap());
-	}
+pub unsafe extern "C" fn jsafe_to_string(this: *mut object) -> *mut string {
+	guard(null_mut(), || {
+		let str = with_current(this, |x| Ok(formatting::serialize(x)))?;
+		Ok(create_string(c_string(str)?))
+	})
+}
 
-	let str = (*(*this).current).to_string();
-	create_string(CString::new(str).unwrap())
+//Return a string representation of an object and its length in bytes
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_to_string_len(this: *mut object, len: *mut usize) -> *mut string {
+	guard(null_mut(), || {
+		let str = with_current(this, |x| Ok(formatting::serialize(x)))?;
+		create_string_len(str, len)
+	})
 }
 
 //Get the length of a json array/object
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_get_len(this: *mut object) -> c_uint {
-	if object_is_null(this) {
-		return 0;
-	}
-
-	(*(*this).current).len() as u32
+pub unsafe extern "C" fn jsafe_get_len(this: *mut object) -> usize {
+	guard(0, || with_current(this, |x| Ok(x.len())))
 }
 
 //Check if a json object has a key
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_has_key(this: *mut object, key: *const c_char) -> c_int {
-	if object_is_null(this) || key.is_null() {
-		return 0;
-	}
+pub unsafe extern "C" fn jsafe_has_key(this: *mut object, key: *const c_char) -> bool {
+	guard(false, || {
+		let str = text_arg(key, "key")?;
+		with_current(this, |x| Ok(x.has(str)))
+	})
+}
 
-	let str = CStr::from_ptr(key).to_str().unwrap();
-	(*(*this).current).has(str) as c_int
+//Text of the value under the pointer
+unsafe fn current_text(this: *mut object) -> Result<String, Failure> {
+	return with_current(this, |x| match x {
+		Value::Text(x) => Ok(x.clone()),
+		x => Err(wrong_type("text", x))
+	});
 }
 
-//Get a string value from an object
+//Get a string value from an object. Returns NULL if the value isn't text or contains a NUL.
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_get_text(this: *mut object) -> *mut CString {
-	if object_is_null(this) {
-		return null_mut();
-	}
+pub unsafe extern "C" fn jsafe_get_text(this: *mut object) -> *mut string {
+	guard(null_mut(), || Ok(create_string(c_string(current_text(this)?)?)))
+}
 
-	let val = (*this).current.as_ref().unwrap();
-	match val {
-		Value::Text(x) => create_string(CString::new(x.as_bytes()).unwrap()),
-		_ => null_mut()
-	}
+//Get a string value from an object and its length in bytes, which may include NULs. Returns NULL
+//if the value isn't text.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_get_text_len(this: *mut object, len: *mut usize) -> *mut string {
+	guard(null_mut(), || create_string_len(current_text(this)?, len))
 }
 
-//Get a number value from an object
+//Get a number value from an object. Returns 0 if the value isn't a number.
 #[no_mangle]
 pub unsafe extern "C" fn jsafe_get_num(this: *mut object) -> c_double {
-	if object_is_null(this) {
-		return 0.0;
-	}
-
-	let val = (*this).current.as_ref().unwrap();
-	match val {
-		Value::Number(x) => *x,
-		_ => 0.0
-	}
+	guard(0.0, || {
+		with_current(this, |x| match x {
+			Value::Number(x) => Ok(*x),
+			x => Err(wrong_type("a number", x))
+		})
+	})
 }
 
-//Get a number value from an object
+//Get a bool value from an object. Returns false if the value isn't a bool.
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_get_bool(this: *mut object) -> c_int {
-	if object_is_null(this) {
-		return 0;
-	}
+pub unsafe extern "C" fn jsafe_get_bool(this: *mut object) -> bool {
+	guard(false, || {
+		with_current(this, |x| match x {
+			Value::Bool(x) => Ok(*x),
+			x => Err(wrong_type("a bool", x))
+		})
+	})
+}
 
-	let val = (*this).current.as_ref().unwrap();
-	match val {
-		Value::Bool(x) => *x as c_int,
-		_ => 0
-	}
+//Check the type of the value under the pointer. Invalid handles give false.
+unsafe fn check_type(this: *mut object, check: fn(&Value) -> bool) -> bool {
+	guard(false, || with_current(this, |x| Ok(check(x))))
 }
 
 //Check if a value is null
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_is_null(this: *mut object) -> c_int {
-	if object_is_null(this) {
-		return 0;
-	}
-
-	let val = (*this).current.as_ref().unwrap();
-	match val {
-		Value::Null => 1,
-		_ => 0
-	}
+pub unsafe extern "C" fn jsafe_is_null(this: *mut object) -> bool {
+	check_type(this, |x| matches!(x, Value::Null))
 }
 
 //Check if a value is invalid
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_is_valid(this: *mut object) -> c_int {
-	if object_is_null(this) {
-		return 0;
-	}
-
-	let val = (*this).current.as_ref().unwrap();
-	match val {
-		Value::Invalid => 0,
-		_ => 1
-	}
+pub unsafe extern "C" fn jsafe_is_valid(this: *mut object) -> bool {
+	check_type(this, |x| !matches!(x, Value::Invalid))
 }
 
 //Check if a value is text
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_is_text(this: *mut object) -> c_int {
-	if object_is_null(this) {
-		return 1;
-	}
-
-	let val = (*this).current.as_ref().unwrap();
-	match val {
-		Value::Text(_) => 1,
-		_ => 0
-	}
+pub unsafe extern "C" fn jsafe_is_text(this: *mut object) -> bool {
+	check_type(this, |x| matches!(x, Value::Text(_)))
 }
 
 //Check if a value is a number
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_is_num(this: *mut object) -> c_int {
-	if object_is_null(this) {
-		return 1;
-	}
-
-	let val = (*this).current.as_ref().unwrap();
-	match val {
-		Value::Number(_) => 1,
-		_ => 0
-	}
+pub unsafe extern "C" fn jsafe_is_num(this: *mut object) -> bool {
+	check_type(this, |x| matches!(x, Value::Number(_)))
 }
 
 //Check if a value is an object
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_is_obj(this: *mut object) -> c_int {
-	if object_is_null(this) {
-		return 1;
-	}
-
-	let val = (*this).current.as_ref().unwrap();
-	match val {
-		Value::Object(_) => 1,
-		_ => 0
-	}
+pub unsafe extern "C" fn jsafe_is_obj(this: *mut object) -> bool {
+	check_type(this, |x| matches!(x, Value::Object(_)))
 }
 
 //Check if a value is an array
 #[no_mangle]
-pub unsafe extern "C" fn jsafe_is_arr(this: *mut object) -> c_int {
-	if object_is_null(this) {
-		return 1;
-	}
-
-	let val = (*this).current.as_ref().unwrap();
-	match val {
-		Value::Array(_) => 1,
-		_ => 0
-	}
-}
\ No newline at end of file
+pub unsafe extern "C" fn jsafe_is_arr(this: *mut object) -> bool {
+	check_type(this, |x| matches!(x, Value::Array(_)))
+}
@@ -1,17 +1,118 @@
-use json::Value;
-use json::parsing;
+use json::parsing::{self, ParseError, ParseOptions};
 
-use std::ffi::{CStr, c_char};
+use std::ffi::c_char;
+use std::fs;
+use std::ptr::null_mut;
+use std::slice;
+
+use super::c_errors::{Failure, guard, jsafe_status, text_arg};
 use super::c_json::{object, create_object};
 
-//Get a value from a string
+//Options for jsafe_from_buffer and jsafe_from_file. NULL options use the defaults.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+pub struct jsafe_parse_options {
+	//Deepest nesting of objects and arrays accepted, or 0 for the default of 512
+	pub max_depth: usize
+}
+
+//Where and why parsing failed. Line and column are 0 when the failure isn't in the text, such as
+//a file that can't be read.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+pub struct jsafe_parse_error {
+	//Byte offset of the problem in the input
+	pub offset: usize,
+	//Line and column of the problem, both starting at 1. Columns count characters, not bytes.
+	pub line: usize,
+	pub column: usize,
+	//NUL terminated description, cut short if it doesn't fit
+	pub message: [c_char; 128]
+}
+
+unsafe fn parse_options(options: *const jsafe_parse_options) -> ParseOptions {
+	let mut to_return = ParseOptions::default();
+	if let Some(x) = options.as_ref() {
+		if x.max_depth != 0 {
+			to_return.max_depth = x.max_depth;
+		}
+	}
+	return to_return;
+}
+
+//Fill in the caller's error struct, if they passed one. Success gives zeros and an empty message.
+unsafe fn report(error: *mut jsafe_parse_error, found: Option<&ParseError>, message: &str) {
+	let error = match error.as_mut() {
+		Some(x) => x,
+		None => return
+	};
+
+	error.offset = found.map_or(0, |x| x.offset);
+	error.line = found.map_or(0, |x| x.line);
+	error.column = found.map_or(0, |x| x.column);
+
+	//Cut on a character boundary so the message stays valid UTF-8
+	let mut len = message.len().min(error.message.len() - 1);
+	while !message.is_char_boundary(len) {
+		len -= 1;
+	}
+	for (to, from) in error.message.iter_mut().zip(message[..len].bytes()) {
+		*to = from as c_char;
+	}
+	error.message[len] = 0;
+}
+
+unsafe fn parse_bytes(bytes: &[u8], options: *const jsafe_parse_options, error: *mut jsafe_parse_error) -> Result<*mut object, Failure> {
+	match parsing::parse_bytes(bytes, &parse_options(options)) {
+		Ok(x) => {
+			report(error, None, "");
+			create_object(x)
+		}
+		Err(e) => {
+			report(error, Some(&e), &e.message);
+			Err(Failure::new(jsafe_status::JSAFE_ERR_PARSE, e.to_string()))
+		}
+	}
+}
+
+//Get a value from a string. Returns NULL if the text isn't valid JSON, and
+//jsafe_last_error_message says where parsing stopped.
 #[no_mangle]
 pub unsafe extern "C" fn jsafe_from_str(text: *const c_char) -> *mut object {
-	let str = CStr::from_ptr(text).to_str();
-	if str.is_err() {
-		return create_object(Value::Invalid);
-	}
+	guard(null_mut(), || {
+		let str = text_arg(text, "text")?;
+		let value = parsing::parse(str).map_err(|e| Failure::new(jsafe_status::JSAFE_ERR_PARSE, e.to_string()))?;
+		create_object(value)
+	})
+}
+
+//Get a value from len bytes of text, which needn't be NUL terminated. Returns NULL on failure and
+//describes the problem in error, which may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_from_buffer(text: *const c_char, len: usize, options: *const jsafe_parse_options, error: *mut jsafe_parse_error) -> *mut object {
+	guard(null_mut(), || {
+		if text.is_null() {
+			report(error, None, "text is NULL");
+			return Err(Failure::new(jsafe_status::JSAFE_ERR_NULL, String::from("text is NULL")));
+		}
+
+		parse_bytes(slice::from_raw_parts(text as *const u8, len), options, error)
+	})
+}
+
+//Get a value from a file. Returns NULL on failure and describes the problem in error, which may be
+//NULL. A file that can't be read gives JSAFE_ERR_IO.
+#[no_mangle]
+pub unsafe extern "C" fn jsafe_from_file(path: *const c_char, options: *const jsafe_parse_options, error: *mut jsafe_parse_error) -> *mut object {
+	guard(null_mut(), || {
+		let file = text_arg(path, "path").inspect_err(|e| report(error, None, &e.message))?;
+
+		let bytes = fs::read(file).map_err(|e| {
+			let message = format!("can't read {}: {}", file, e);
+			report(error, None, &message);
+			Failure::new(jsafe_status::JSAFE_ERR_IO, message)
+		})?;
 
-	let str = str.unwrap();
-	create_object(parsing::from_str(str))
-}
\ No newline at end of file
+		parse_bytes(&bytes, options, error)
+	})
+}
@@ -1,10 +1,57 @@
+#[macro_use]
+mod macros;
+pub mod codegen;
+pub mod conversion;
+pub mod diff;
 pub mod formatting;
+pub mod interpolate;
+pub mod merge;
 pub mod parsing;
+pub mod patch;
+pub mod pointer;
+pub mod query;
+pub mod regex;
+pub mod resolve;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serde_support;
+pub mod traits;
+
+pub use self::traits::{ToJson, FromJson, FromJsonError};
+pub use jsafe_derive::{ToJson, FromJson};
+pub use self::diff::diff;
+#[cfg(feature = "serde")]
+pub use self::serde_support::{to_value, from_value};
 
 use std::collections::HashMap;
+use std::fmt;
 use std::ops::Index;
 use std::ops::IndexMut;
 
+//One step of a path into a Value: an object key or an array index
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+	Key(String),
+	Index(usize)
+}
+
+impl<'a> From<&'a str> for PathSegment {
+	fn from(input: &'a str) -> Self {
+		return PathSegment::Key(input.to_string());
+	}
+}
+impl From<String> for PathSegment {
+	fn from(input: String) -> Self {
+		return PathSegment::Key(input);
+	}
+}
+impl From<usize> for PathSegment {
+	fn from(input: usize) -> Self {
+		return PathSegment::Index(input);
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Value {
 	Invalid,
 	Null,
@@ -26,9 +73,9 @@ impl From<i64> for Value {
 		return Value::Number(input as f64);
 	}
 }
-impl Into<f64> for &Value {
-	fn into(self) -> f64{
-		match self {
+impl<'a> From<&'a Value> for f64 {
+	fn from(input: &'a Value) -> f64{
+		match input {
 			Value::Number(x) => *x,
 			_ => {
 				println!("WARNING: value is not a number and has been converted to 0 by default");
@@ -37,9 +84,9 @@ impl Into<f64> for &Value {
 		}
 	}
 }
-impl Into<i64> for &Value {
-	fn into(self) -> i64{
-		match self {
+impl<'a> From<&'a Value> for i64 {
+	fn from(input: &'a Value) -> i64{
+		match input {
 			Value::Number(x) => *x as i64,
 			_ => {
 				println!("WARNING: value is not a number and has been converted to 0 by default");
@@ -55,9 +102,9 @@ impl From<String> for Value {
 		return Value::Text(input);
 	}
 }
-impl Into<String> for &Value {
-	fn into(self) -> String {
-		match self {
+impl<'a> From<&'a Value> for String {
+	fn from(input: &'a Value) -> String {
+		match input {
 			Value::Text(x) => String::clone(x),
 			_ => {
 				println!("WARNING: value is not a string and has been converted to empty str by default");
@@ -78,9 +125,9 @@ impl From<bool> for Value {
 		return Value::Bool(input);
 	}
 }
-impl Into<bool> for &Value {
-	fn into(self) -> bool {
-		match self {
+impl<'a> From<&'a Value> for bool {
+	fn from(input: &'a Value) -> bool {
+		match input {
 			Value::Bool(x) => *x,
 			_ => {
 				println!("WARNING: value is not a bool and has been converted to false by default");
@@ -90,20 +137,6 @@ impl Into<bool> for &Value {
 	}
 }
 
-//Easy convert from hashmap(object)
-impl From<HashMap<String, Value>> for Value {
-	fn from(input: HashMap<String, Value>) -> Self {
-		return Value::Object(input);
-	}
-}
-
-//Easy convert from Vec(array)
-impl From<Vec<Value>> for Value {
-	fn from(input: Vec<Value>) -> Self {
-		return Value::Array(input);
-	}
-}
-
 impl Value {
 	//Create new empty object
 	pub fn obj() -> Value {
@@ -115,7 +148,21 @@ impl Value {
 		return Value::Array(Vec::new());
 	}
 
+	//Get the name of the value's type (used in error messages)
+	pub fn type_name(&self) -> &'static str {
+		match self {
+			Value::Invalid => "invalid",
+			Value::Null => "null",
+			Value::Number(_) => "number",
+			Value::Text(_) => "text",
+			Value::Bool(_) => "bool",
+			Value::Object(_) => "object",
+			Value::Array(_) => "array"
+		}
+	}
+
 	//Get number of items held within array or object
+	#[allow(clippy::len_without_is_empty)]
 	pub fn len(&self) -> usize {
 		match self {
     		Value::Array(x) => x.len(),
@@ -132,6 +179,65 @@ impl Value {
 		}
 	}
 
+	//Follow a path of keys and indexes without changing anything. Returns None if any step is missing.
+	pub fn get_path(&self, path: &[PathSegment]) -> Option<&Value> {
+		let mut current = self;
+
+		for segment in path {
+			current = match (current, segment) {
+				(Value::Object(x), PathSegment::Key(key)) => x.get(key)?,
+				(Value::Array(x), PathSegment::Index(index)) => x.get(*index)?,
+				_ => return None
+			};
+		}
+
+		return Some(current);
+	}
+
+	//Mutable version of get_path. Missing steps are never created.
+	pub fn get_path_mut(&mut self, path: &[PathSegment]) -> Option<&mut Value> {
+		let mut current = self;
+
+		for segment in path {
+			current = match (current, segment) {
+				(Value::Object(x), PathSegment::Key(key)) => x.get_mut(key)?,
+				(Value::Array(x), PathSegment::Index(index)) => x.get_mut(*index)?,
+				_ => return None
+			};
+		}
+
+		return Some(current);
+	}
+
+	//Follow a path, creating whatever is missing along the way. Null values become objects or arrays as needed,
+	//arrays are padded with nulls, and a newly created leaf is null.
+	//Returns None if an existing value of the wrong type is in the way.
+	pub fn ensure_path(&mut self, path: &[PathSegment]) -> Option<&mut Value> {
+		let mut current = self;
+
+		for segment in path {
+			if let Value::Null = current {
+				*current = match segment {
+					PathSegment::Key(_) => Value::obj(),
+					PathSegment::Index(_) => Value::arr()
+				};
+			}
+
+			current = match (current, segment) {
+				(Value::Object(x), PathSegment::Key(key)) => x.entry(key.clone()).or_insert(Value::Null),
+				(Value::Array(x), PathSegment::Index(index)) => {
+					if x.len() <= *index {
+						x.resize(*index + 1, Value::Null);
+					}
+					&mut x[*index]
+				}
+				_ => return None
+			};
+		}
+
+		return Some(current);
+	}
+
 	//Append a new value to an array
 	pub fn add(&mut self, val: Value) {
 		match self {
@@ -149,65 +255,11 @@ impl Value {
 		}
 	}
 
-	//Convert to text
-	pub fn to_string(&self) -> String {
-		let mut to_return = String::from("");
-
-		match self {
-			Value::Invalid => to_return += "INVALID",
-			Value::Null => to_return += "null",
-			Value::Number(x) => to_return += &x.to_string(),
-
-			Value::Text(x) => {
-				to_return.push('\"');
-				to_return.push_str(x);
-				to_return.push('\"');
-			},
-
-			Value::Bool(x) => if *x {to_return += "true"} else {to_return += "false"},
-
-			Value::Object(x) => {
-				to_return += "{";
-
-				for value in x {
-					to_return.push('\"');
-					to_return.push_str(value.0);
-					to_return.push('\"');
-
-					to_return.push(':');
-					to_return.push_str(&Value::to_string(value.1));
-					to_return.push(',');
-				}
-
-				//pop the last comma off the end
-				if x.len() > 0 {
-					to_return.pop();
-				}
-				
-				to_return += "}";
-			},
-
-			Value::Array(x) => {
-				to_return += "[";
-				for value in x {
-					to_return.push_str(&Value::to_string(value));
-					to_return.push(',');
-				}
-
-				if x.len() > 0 {
-					to_return.pop();
-				}
-				
-				to_return += "]";
-			}
-		}
-
-		return to_return;
-	}
-
+	#[allow(clippy::should_implement_trait)]
 	pub fn as_ref(&self) -> &Value {
 		return self;
 	}
+	#[allow(clippy::should_implement_trait)]
 	pub fn as_mut(&mut self) -> &mut Value {
 		return self;
 	}
@@ -277,4 +329,11 @@ impl IndexMut<&str> for Value {
 		println!("JSON: mutable reference from Value that is not an Object. Object has been overwritten");
 		return self;
 	}
+}
+
+//Compact JSON text, with strings and keys escaped
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&formatting::serialize(self))
+	}
 }
\ No newline at end of file
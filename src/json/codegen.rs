@@ -0,0 +1,262 @@
+//Generate Rust types that model a sample document, ready for #[derive(ToJson, FromJson)].
+//
+//Objects become structs named after the key they were found under, arrays become Vec, members
+//that are null or missing in some array items become Option, and positions holding several kinds
+//of value become untagged enums.
+
+use crate::json::Value;
+use crate::json::schema::Shape;
+
+use std::collections::HashSet;
+
+//Strict and reserved keywords, including those reserved by later editions
+const KEYWORDS: [&str; 52] = [
+	"as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+	"let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+	"trait", "true", "type", "unsafe", "use", "where", "while", "async", "await", "dyn", "abstract", "become", "box",
+	"do", "final", "gen", "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield"
+];
+
+//Type names the generated file imports or relies on from the prelude, which generated types must not shadow
+const RESERVED_TYPES: [&str; 15] = [
+	"FromJson", "ToJson", "Value", "String", "Option", "Some", "None", "Vec", "Box", "Result", "Ok", "Err",
+	"Debug", "Clone", "PartialEq"
+];
+
+//Split a key into lowercase words at separators and lower-to-upper case changes
+fn words(key: &str) -> Vec<String> {
+	let mut to_return: Vec<String> = Vec::new();
+	let mut current = String::new();
+	let mut prev_lower = false;
+
+	for c in key.chars() {
+		if !c.is_alphanumeric() {
+			if !current.is_empty() {
+				to_return.push(current.clone());
+				current.clear();
+			}
+			prev_lower = false;
+			continue;
+		}
+
+		if c.is_uppercase() && prev_lower && !current.is_empty() {
+			to_return.push(current.clone());
+			current.clear();
+		}
+		prev_lower = c.is_lowercase() || c.is_numeric();
+		current.extend(c.to_lowercase());
+	}
+
+	if !current.is_empty() {
+		to_return.push(current);
+	}
+
+	return to_return;
+}
+
+//Convert a key to a snake_case field name that is a valid identifier
+fn field_name(key: &str) -> String {
+	let mut to_return = words(key).join("_");
+
+	if to_return.is_empty() || to_return.starts_with(|c: char| c.is_numeric()) {
+		to_return.insert_str(0, "field_");
+	}
+	if KEYWORDS.contains(&to_return.as_str()) {
+		to_return.push('_');
+	}
+
+	return to_return;
+}
+
+//Convert a key to a PascalCase type name
+fn type_name(key: &str) -> String {
+	let mut to_return = String::new();
+	for word in words(key) {
+		let mut chars = word.chars();
+		if let Some(first) = chars.next() {
+			to_return.extend(first.to_uppercase());
+			to_return.push_str(chars.as_str());
+		}
+	}
+
+	if to_return.is_empty() || to_return.starts_with(|c: char| c.is_numeric()) {
+		to_return.insert_str(0, "Type");
+	}
+	if KEYWORDS.contains(&to_return.as_str()) {
+		to_return.push('_');
+	}
+
+	return to_return;
+}
+
+//Name for the items of an array found under key: "users" gives "User", "entries" gives "Entry"
+fn singular(name: &str) -> String {
+	if name.len() > 3 && name.ends_with("ies") {
+		return format!("{}y", &name[..name.len() - 3]);
+	}
+	if name.len() > 1 && name.ends_with('s') && !name.ends_with("ss") {
+		return name[..name.len() - 1].to_string();
+	}
+	return format!("{}Item", name);
+}
+
+struct Generator {
+	//Generated type definitions, in the order they were named
+	definitions: Vec<String>,
+	names: HashSet<String>,
+	uses_value: bool
+}
+
+impl Generator {
+	//Find a type name based on hint that isn't taken yet
+	fn unused(&self, hint: &str) -> String {
+		let mut name = hint.to_string();
+		let mut n = 2;
+		while self.names.contains(&name) {
+			name = format!("{}{}", hint, n);
+			n += 1;
+		}
+
+		return name;
+	}
+
+	//Pick an unused type name based on hint
+	fn claim(&mut self, hint: &str) -> String {
+		let name = self.unused(hint);
+		self.names.insert(name.clone());
+		return name;
+	}
+
+	fn value_type(&mut self) -> String {
+		self.uses_value = true;
+		return String::from("Value");
+	}
+
+	//Rust type for every non-null value seen in shape
+	fn rust_type(&mut self, shape: &Shape, hint: &str) -> String {
+		let mut kinds: Vec<(&str, String)> = Vec::new();
+
+		if shape.boolean {
+			kinds.push(("Bool", String::from("bool")));
+		}
+		if shape.integer && !shape.number {
+			kinds.push(("Integer", String::from("i64")));
+		}
+		if shape.number {
+			kinds.push(("Number", String::from("f64")));
+		}
+		if shape.string {
+			kinds.push(("Text", String::from("String")));
+		}
+		if shape.objects > 0 {
+			let object = self.struct_type(shape, hint);
+			kinds.push(("Object", object));
+		}
+		if shape.array {
+			let items = match &shape.items {
+				Some(x) => self.field_type(x, &singular(hint), false),
+				None => self.value_type()
+			};
+			kinds.push(("Array", format!("Vec<{}>", items)));
+		}
+
+		match kinds.len() {
+			0 => self.value_type(),
+			1 => kinds.pop().unwrap().1,
+			_ => self.enum_type(kinds, hint)
+		}
+	}
+
+	//Rust type for a member or array item, wrapped in Option when it can be null or missing
+	fn field_type(&mut self, shape: &Shape, hint: &str, missing: bool) -> String {
+		let inner = self.rust_type(shape, hint);
+		if shape.null || missing {
+			return format!("Option<{}>", inner);
+		}
+		return inner;
+	}
+
+	fn struct_type(&mut self, shape: &Shape, hint: &str) -> String {
+		//Objects without members are modelled as plain values
+		if shape.properties.is_empty() {
+			return self.value_type();
+		}
+
+		let name = self.claim(hint);
+		let slot = self.definitions.len();
+		self.definitions.push(String::new());
+
+		let mut keys: Vec<&String> = shape.properties.keys().collect();
+		keys.sort();
+
+		let mut to_return = String::from("#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]\n");
+		to_return += &format!("pub struct {} {{\n", name);
+
+		let mut fields: Vec<String> = Vec::new();
+		let mut used: HashSet<String> = HashSet::new();
+		for key in keys {
+			let (count, member) = &shape.properties[key];
+			let rust = self.field_type(member, &type_name(key), *count < shape.objects);
+
+			let mut field = field_name(key);
+			while used.contains(&field) {
+				field.push('_');
+			}
+			used.insert(field.clone());
+
+			let mut text = String::new();
+			if field != *key {
+				text += &format!("\t#[json(rename = {:?})]\n", key);
+			}
+			text += &format!("\tpub {}: {}", field, rust);
+			fields.push(text);
+		}
+
+		to_return += &fields.join(",\n");
+		to_return += "\n}\n";
+
+		self.definitions[slot] = to_return;
+		return name;
+	}
+
+	fn enum_type(&mut self, kinds: Vec<(&str, String)>, hint: &str) -> String {
+		let name = self.claim(&format!("{}Value", hint));
+
+		let mut to_return = String::from("#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]\n#[json(untagged)]\n");
+		to_return += &format!("pub enum {} {{\n", name);
+
+		let variants: Vec<String> = kinds.iter().map(|(variant, rust)| format!("\t{}({})", variant, rust)).collect();
+		to_return += &variants.join(",\n");
+		to_return += "\n}\n";
+
+		self.definitions.push(to_return);
+		return name;
+	}
+}
+
+//Generate Rust source for types that model sample. The top level type is called root_name; when
+//the sample isn't an object, a type alias with that name is generated instead.
+pub fn generate_rust(sample: &Value, root_name: &str) -> String {
+	let mut shape = Shape::default();
+	shape.add(sample);
+
+	let mut generator = Generator {
+		definitions: Vec::new(),
+		names: RESERVED_TYPES.iter().map(|x| x.to_string()).collect(),
+		uses_value: false
+	};
+
+	let root = generator.unused(&type_name(root_name));
+	let rust = generator.field_type(&shape, &root, false);
+	if rust != root {
+		generator.definitions.insert(0, format!("pub type {} = {};\n", root, rust));
+	}
+
+	let mut to_return = String::from(if generator.uses_value { "use jsafe::json::{FromJson, ToJson, Value};\n" } else { "use jsafe::json::{FromJson, ToJson};\n" });
+	for definition in &generator.definitions {
+		to_return.push('\n');
+		to_return += definition;
+	}
+
+	return to_return;
+}
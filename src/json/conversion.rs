@@ -0,0 +1,362 @@
+use crate::json::Value;
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::{Infallible, TryFrom, TryInto};
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+
+//Error returned when a Value can't be converted into the requested Rust type
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionError {
+	pub expected: String,
+	pub found: String
+}
+
+impl ConversionError {
+	pub fn new(expected: &str, found: &Value) -> ConversionError {
+		return ConversionError {
+			expected: expected.to_string(),
+			found: found.type_name().to_string()
+		};
+	}
+}
+
+impl fmt::Display for ConversionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "expected {}, found {}", self.expected, self.found)
+	}
+}
+
+impl Error for ConversionError {}
+
+//Value converts into itself without failing, which lets containers of Value use the impls below
+impl From<Infallible> for ConversionError {
+	fn from(input: Infallible) -> Self {
+		match input {}
+	}
+}
+
+//Convert one element of a container, whatever error type its conversion uses
+fn element<T>(input: Value) -> Result<T, ConversionError>
+where
+	T: TryFrom<Value>,
+	T::Error: Into<ConversionError>
+{
+	return T::try_from(input).map_err(Into::into);
+}
+
+//Numbers. Every numeric type is stored as an f64.
+macro_rules! number_conversions {
+	($($t:ty),*) => {
+		$(
+			impl From<$t> for Value {
+				fn from(input: $t) -> Self {
+					return Value::Number(input as f64);
+				}
+			}
+		)*
+	};
+}
+number_conversions!(i8, i16, i32, isize, u8, u16, u32, u64, usize, f32);
+
+//Integers only convert back if the number is whole and in range
+macro_rules! integer_try_conversions {
+	($($t:ty),*) => {
+		$(
+			impl TryFrom<Value> for $t {
+				type Error = ConversionError;
+
+				fn try_from(input: Value) -> Result<Self, Self::Error> {
+					if let Value::Number(x) = input {
+						if x.fract() == 0.0 && x >= <$t>::MIN as f64 && x < <$t>::MAX as f64 + 1.0 {
+							return Ok(x as $t);
+						}
+
+						return Err(ConversionError {
+							expected: stringify!($t).to_string(),
+							found: if !x.is_finite() {
+								"non-finite number"
+							} else if x.fract() == 0.0 {
+								"number out of range"
+							} else {
+								"fractional number"
+							}.to_string()
+						});
+					}
+
+					return Err(ConversionError::new(stringify!($t), &input));
+				}
+			}
+		)*
+	};
+}
+integer_try_conversions!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl TryFrom<Value> for f64 {
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		match input {
+			Value::Number(x) => Ok(x),
+			_ => Err(ConversionError::new("f64", &input))
+		}
+	}
+}
+
+impl TryFrom<Value> for f32 {
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		match input {
+			Value::Number(x) => Ok(x as f32),
+			_ => Err(ConversionError::new("f32", &input))
+		}
+	}
+}
+
+//Text
+impl From<char> for Value {
+	fn from(input: char) -> Self {
+		return Value::Text(input.to_string());
+	}
+}
+
+impl<'a> From<&'a String> for Value {
+	fn from(input: &'a String) -> Self {
+		return Value::Text(input.clone());
+	}
+}
+
+impl TryFrom<Value> for String {
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		match input {
+			Value::Text(x) => Ok(x),
+			_ => Err(ConversionError::new("text", &input))
+		}
+	}
+}
+
+//A char converts back only from text holding exactly one character
+impl TryFrom<Value> for char {
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		if let Value::Text(ref x) = input {
+			let mut chars = x.chars();
+			if let (Some(c), None) = (chars.next(), chars.next()) {
+				return Ok(c);
+			}
+
+			return Err(ConversionError {
+				expected: "text of length 1".to_string(),
+				found: format!("text of length {}", x.chars().count())
+			});
+		}
+
+		return Err(ConversionError::new("char", &input));
+	}
+}
+
+//Bool
+impl TryFrom<Value> for bool {
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		match input {
+			Value::Bool(x) => Ok(x),
+			_ => Err(ConversionError::new("bool", &input))
+		}
+	}
+}
+
+//Unit and Option map to null
+impl From<()> for Value {
+	fn from(_: ()) -> Self {
+		return Value::Null;
+	}
+}
+
+impl TryFrom<Value> for () {
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		match input {
+			Value::Null => Ok(()),
+			_ => Err(ConversionError::new("null", &input))
+		}
+	}
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+	fn from(input: Option<T>) -> Self {
+		match input {
+			Some(x) => x.into(),
+			None => Value::Null
+		}
+	}
+}
+
+//Option<Value> isn't covered: std already converts any Value into Some(value)
+impl<T: TryFrom<Value, Error = ConversionError>> TryFrom<Value> for Option<T> {
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		match input {
+			Value::Null => Ok(None),
+			_ => T::try_from(input).map(Some)
+		}
+	}
+}
+
+//Arrays
+impl<T: Into<Value>> From<Vec<T>> for Value {
+	fn from(input: Vec<T>) -> Self {
+		return Value::Array(input.into_iter().map(Into::into).collect());
+	}
+}
+
+impl<'a, T: Clone + Into<Value>> From<&'a [T]> for Value {
+	fn from(input: &'a [T]) -> Self {
+		return Value::Array(input.iter().cloned().map(Into::into).collect());
+	}
+}
+
+impl<T: Into<Value>, const N: usize> From<[T; N]> for Value {
+	fn from(input: [T; N]) -> Self {
+		return Value::Array(IntoIterator::into_iter(input).map(Into::into).collect());
+	}
+}
+
+//Collecting an iterator always produces an array
+impl<T: Into<Value>> FromIterator<T> for Value {
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+		return Value::Array(iter.into_iter().map(Into::into).collect());
+	}
+}
+
+impl<T> TryFrom<Value> for Vec<T>
+where
+	T: TryFrom<Value>,
+	T::Error: Into<ConversionError>
+{
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		match input {
+			Value::Array(x) => x.into_iter().map(element).collect(),
+			_ => Err(ConversionError::new("array", &input))
+		}
+	}
+}
+
+impl<T, const N: usize> TryFrom<Value> for [T; N]
+where
+	T: TryFrom<Value>,
+	T::Error: Into<ConversionError>
+{
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		let items = Vec::<T>::try_from(input)?;
+		let len = items.len();
+
+		return items.try_into().map_err(|_| ConversionError {
+			expected: format!("array of length {}", N),
+			found: format!("array of length {}", len)
+		});
+	}
+}
+
+//Tuples are stored as fixed length arrays
+macro_rules! tuple_conversions {
+	($len:expr => $($name:ident)+) => {
+		impl<$($name: Into<Value>),+> From<($($name,)+)> for Value {
+			#[allow(non_snake_case)]
+			fn from(input: ($($name,)+)) -> Self {
+				let ($($name,)+) = input;
+				return Value::Array(vec![$($name.into()),+]);
+			}
+		}
+
+		impl<$($name: TryFrom<Value>),+> TryFrom<Value> for ($($name,)+)
+		where
+			$(<$name as TryFrom<Value>>::Error: Into<ConversionError>),+
+		{
+			type Error = ConversionError;
+
+			fn try_from(input: Value) -> Result<Self, Self::Error> {
+				if let Value::Array(x) = input {
+					if x.len() != $len {
+						return Err(ConversionError {
+							expected: format!("array of length {}", $len),
+							found: format!("array of length {}", x.len())
+						});
+					}
+
+					let mut items = x.into_iter();
+					return Ok(($(element::<$name>(items.next().unwrap())?,)+));
+				}
+
+				return Err(ConversionError::new(concat!("array of length ", $len), &input));
+			}
+		}
+	};
+}
+tuple_conversions!(1 => A);
+tuple_conversions!(2 => A B);
+tuple_conversions!(3 => A B C);
+tuple_conversions!(4 => A B C D);
+tuple_conversions!(5 => A B C D E);
+tuple_conversions!(6 => A B C D E F);
+tuple_conversions!(7 => A B C D E F G);
+tuple_conversions!(8 => A B C D E F G H);
+
+//Objects
+impl<K: Into<String>, V: Into<Value>, S> From<HashMap<K, V, S>> for Value {
+	fn from(input: HashMap<K, V, S>) -> Self {
+		return Value::Object(input.into_iter().map(|(k, v)| (k.into(), v.into())).collect());
+	}
+}
+
+impl<K: Into<String>, V: Into<Value>> From<BTreeMap<K, V>> for Value {
+	fn from(input: BTreeMap<K, V>) -> Self {
+		return Value::Object(input.into_iter().map(|(k, v)| (k.into(), v.into())).collect());
+	}
+}
+
+impl<K, V, S> TryFrom<Value> for HashMap<K, V, S>
+where
+	K: From<String> + Eq + Hash,
+	V: TryFrom<Value>,
+	V::Error: Into<ConversionError>,
+	S: BuildHasher + Default
+{
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		match input {
+			Value::Object(x) => x.into_iter().map(|(k, v)| Ok((K::from(k), element(v)?))).collect(),
+			_ => Err(ConversionError::new("object", &input))
+		}
+	}
+}
+
+impl<K, V> TryFrom<Value> for BTreeMap<K, V>
+where
+	K: From<String> + Ord,
+	V: TryFrom<Value>,
+	V::Error: Into<ConversionError>
+{
+	type Error = ConversionError;
+
+	fn try_from(input: Value) -> Result<Self, Self::Error> {
+		match input {
+			Value::Object(x) => x.into_iter().map(|(k, v)| Ok((K::from(k), element(v)?))).collect(),
+			_ => Err(ConversionError::new("object", &input))
+		}
+	}
+}
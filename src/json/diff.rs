@@ -0,0 +1,222 @@
+//Structural comparison of two values, for test failures and change reports.
+//
+//Changes are keyed by the JSON Pointer of the value that differs. Object keys are visited in
+//sorted order so the same inputs always produce the same report.
+
+use crate::json::Value;
+use crate::json::formatting::{prettify, serialize};
+use crate::json::pointer;
+
+use std::fmt;
+
+//One difference between the old and new value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+	Added { pointer: String, value: Value },
+	Removed { pointer: String, value: Value },
+	ChangedType { pointer: String, from: Value, to: Value },
+	ChangedValue { pointer: String, from: Value, to: Value }
+}
+
+impl Change {
+	pub fn pointer(&self) -> &str {
+		match self {
+			Change::Added { pointer, .. } => pointer,
+			Change::Removed { pointer, .. } => pointer,
+			Change::ChangedType { pointer, .. } => pointer,
+			Change::ChangedValue { pointer, .. } => pointer
+		}
+	}
+}
+
+impl fmt::Display for Change {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Change::Added { pointer, value } => write!(f, "added \"{}\": {}", pointer, serialize(value)),
+			Change::Removed { pointer, value } => write!(f, "removed \"{}\": {}", pointer, serialize(value)),
+			Change::ChangedType { pointer, from, to } => {
+				write!(f, "changed type of \"{}\" from {} to {}: {} -> {}", pointer, from.type_name(), to.type_name(), serialize(from), serialize(to))
+			}
+			Change::ChangedValue { pointer, from, to } => write!(f, "changed \"{}\": {} -> {}", pointer, serialize(from), serialize(to))
+		}
+	}
+}
+
+//Options for diff_with
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffOptions {
+	//Numbers closer together than this are considered equal
+	pub float_tolerance: f64,
+	//Compare arrays as multisets. Unmatched items are reported at their own index.
+	pub ignore_array_order: bool
+}
+
+impl Default for DiffOptions {
+	fn default() -> DiffOptions {
+		return DiffOptions {
+			float_tolerance: 0.0,
+			ignore_array_order: false
+		};
+	}
+}
+
+//List the changes that turn a into b
+pub fn diff(a: &Value, b: &Value) -> Vec<Change> {
+	return diff_with(a, b, &DiffOptions::default());
+}
+
+//List the changes that turn a into b, using options to decide what counts as equal
+pub fn diff_with(a: &Value, b: &Value, options: &DiffOptions) -> Vec<Change> {
+	let mut to_return: Vec<Change> = Vec::new();
+	diff_into(&mut to_return, String::new(), a, b, options);
+	return to_return;
+}
+
+//Equality under the tolerance and ordering options
+fn equivalent(a: &Value, b: &Value, options: &DiffOptions) -> bool {
+	match (a, b) {
+		(Value::Number(x), Value::Number(y)) => x == y || (x - y).abs() <= options.float_tolerance,
+		(Value::Object(x), Value::Object(y)) => {
+			x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| equivalent(v, w, options)))
+		}
+		(Value::Array(x), Value::Array(y)) => {
+			if x.len() != y.len() {
+				return false;
+			}
+			if options.ignore_array_order {
+				return unordered_matches(x, y, options).iter().all(|m| m.is_some());
+			}
+			x.iter().zip(y).all(|(v, w)| equivalent(v, w, options))
+		}
+		_ => a == b
+	}
+}
+
+//For each item of a, the index of a distinct equivalent item in b
+fn unordered_matches(a: &[Value], b: &[Value], options: &DiffOptions) -> Vec<Option<usize>> {
+	let mut used = vec![false; b.len()];
+	let mut to_return: Vec<Option<usize>> = Vec::with_capacity(a.len());
+
+	for item in a {
+		let found = (0..b.len()).find(|&j| !used[j] && equivalent(item, &b[j], options));
+		if let Some(j) = found {
+			used[j] = true;
+		}
+		to_return.push(found);
+	}
+
+	return to_return;
+}
+
+fn diff_into(changes: &mut Vec<Change>, pointer: String, a: &Value, b: &Value, options: &DiffOptions) {
+	if equivalent(a, b, options) {
+		return;
+	}
+
+	match (a, b) {
+		(Value::Object(x), Value::Object(y)) => {
+			let mut keys: Vec<&String> = x.keys().chain(y.keys().filter(|k| !x.contains_key(*k))).collect();
+			keys.sort();
+
+			for key in keys {
+				let child = format!("{}/{}", pointer, pointer::escape(key));
+				match (x.get(key), y.get(key)) {
+					(Some(v), Some(w)) => diff_into(changes, child, v, w, options),
+					(Some(v), None) => changes.push(Change::Removed { pointer: child, value: v.clone() }),
+					(None, Some(w)) => changes.push(Change::Added { pointer: child, value: w.clone() }),
+					(None, None) => {}
+				}
+			}
+		}
+		(Value::Array(x), Value::Array(y)) if options.ignore_array_order => {
+			let matches = unordered_matches(x, y, options);
+
+			for (i, m) in matches.iter().enumerate() {
+				if m.is_none() {
+					changes.push(Change::Removed { pointer: format!("{}/{}", pointer, i), value: x[i].clone() });
+				}
+			}
+			for (j, item) in y.iter().enumerate() {
+				if !matches.contains(&Some(j)) {
+					changes.push(Change::Added { pointer: format!("{}/{}", pointer, j), value: item.clone() });
+				}
+			}
+		}
+		(Value::Array(x), Value::Array(y)) => {
+			for i in 0..x.len().max(y.len()) {
+				let child = format!("{}/{}", pointer, i);
+				match (x.get(i), y.get(i)) {
+					(Some(v), Some(w)) => diff_into(changes, child, v, w, options),
+					(Some(v), None) => changes.push(Change::Removed { pointer: child, value: v.clone() }),
+					(None, Some(w)) => changes.push(Change::Added { pointer: child, value: w.clone() }),
+					(None, None) => {}
+				}
+			}
+		}
+		_ if a.type_name() != b.type_name() => changes.push(Change::ChangedType { pointer, from: a.clone(), to: b.clone() }),
+		_ => changes.push(Change::ChangedValue { pointer, from: a.clone(), to: b.clone() })
+	}
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+//Add a value to the report, one prefixed line per line of its pretty printed form. Strings are
+//escaped, so a line break inside one never starts a new report line.
+fn push_lines(output: &mut String, prefix: char, value: &Value, color: Option<&str>) {
+	for line in prettify(value, 2).lines() {
+		if let Some(x) = color {
+			output.push_str(x);
+		}
+		output.push(prefix);
+		output.push(' ');
+		output.push_str(line);
+		if color.is_some() {
+			output.push_str(RESET);
+		}
+		output.push('\n');
+	}
+}
+
+//Render changes as a unified-diff style report, with ANSI colors if color is set.
+//
+//--- expected
+//+++ actual
+//@@ /users/0/name @@
+//- "ana"
+//+ "bob"
+pub fn render(changes: &[Change], color: bool) -> String {
+	let mut to_return = String::new();
+	if changes.is_empty() {
+		return to_return;
+	}
+
+	let (red, green, cyan) = if color { (Some(RED), Some(GREEN), Some(CYAN)) } else { (None, None, None) };
+	let paint = |text: &str, code: Option<&str>| match code {
+		Some(x) => format!("{}{}{}", x, text, RESET),
+		None => text.to_string()
+	};
+
+	to_return += &paint("--- expected", red);
+	to_return.push('\n');
+	to_return += &paint("+++ actual", green);
+	to_return.push('\n');
+
+	for change in changes {
+		to_return += &paint(&format!("@@ {} @@", if change.pointer().is_empty() { "(root)" } else { change.pointer() }), cyan);
+		to_return.push('\n');
+
+		match change {
+			Change::Added { value, .. } => push_lines(&mut to_return, '+', value, green),
+			Change::Removed { value, .. } => push_lines(&mut to_return, '-', value, red),
+			Change::ChangedType { from, to, .. } | Change::ChangedValue { from, to, .. } => {
+				push_lines(&mut to_return, '-', from, red);
+				push_lines(&mut to_return, '+', to, green);
+			}
+		}
+	}
+
+	return to_return;
+}
@@ -1,7 +1,6 @@
 use crate::json::Value;
 
 //Helper function to make indenting easier
-#[allow(unused)]
 fn indent(input: &mut String, indent_level: usize, spaces: bool) {
 	for _ in 0..indent_level {
 		if spaces {
@@ -12,106 +11,121 @@ fn indent(input: &mut String, indent_level: usize, spaces: bool) {
 	}
 }
 
-//Format a json object from condensed text to human-readable format.
-#[allow(unused)]
-pub fn prettify(to_print: &Value, spaces: usize) -> String {
-	let mut to_return = String::from("");
-
-	//Check if the object we are trying to format is an object or array
-	match to_print {
-		Value::Object(_) => (),
-		Value::Array(_) => (),
-
-		//If it isn't, return the object converted to a string
-		_ => {return to_print.to_string();}
+//Append text as a quoted JSON string, escaping quotes, backslashes and control characters
+pub fn push_quoted(output: &mut String, text: &str) {
+	output.push('"');
+	for c in text.chars() {
+		match c {
+			'"' => output.push_str("\\\""),
+			'\\' => output.push_str("\\\\"),
+			'\n' => output.push_str("\\n"),
+			'\r' => output.push_str("\\r"),
+			'\t' => output.push_str("\\t"),
+			'\u{8}' => output.push_str("\\b"),
+			'\u{c}' => output.push_str("\\f"),
+			c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+			c => output.push(c)
+		}
 	}
+	output.push('"');
+}
 
-	//Convert value to string
-	let obj_str = to_print.to_string();
-	//Whether we're in a quote or not
-	let mut in_quote = false;
-	//Previous character
-	let mut prev = ' ';
-	//How many tabs/spaces to put before a line
-	let mut indent_level = 0;
-
-	//Calculate how big a tab step should be
-	let mut tab_width = 1;
-	if spaces > 0 {
-		tab_width = spaces;
+//Append the text of a value that isn't an object or array
+fn push_scalar(output: &mut String, value: &Value) {
+	match value {
+		Value::Invalid => output.push_str("INVALID"),
+		Value::Null => output.push_str("null"),
+		Value::Number(x) => output.push_str(&x.to_string()),
+		Value::Text(x) => push_quoted(output, x),
+		Value::Bool(x) => output.push_str(if *x {"true"} else {"false"}),
+		Value::Object(_) | Value::Array(_) => ()
 	}
+}
 
-	for c in obj_str.chars() {
-		match c {
-
-			//Text block
-			'"' => {
-				if prev != '\\' {
-					in_quote = !in_quote;
+fn push_compact(output: &mut String, value: &Value) {
+	match value {
+		Value::Object(x) => {
+			output.push('{');
+			for (i, (key, value)) in x.iter().enumerate() {
+				if i > 0 {
+					output.push(',');
 				}
-
-				to_return.push(c);
+				push_quoted(output, key);
+				output.push(':');
+				push_compact(output, value);
 			}
+			output.push('}');
+		}
 
-			//Object start
-			'{' => {
-				if !in_quote {
-					indent_level += tab_width;
-					to_return.push(c);
-					to_return.push('\n');
-
-					indent(&mut to_return, indent_level, spaces > 0);
+		Value::Array(x) => {
+			output.push('[');
+			for (i, value) in x.iter().enumerate() {
+				if i > 0 {
+					output.push(',');
 				}
+				push_compact(output, value);
 			}
+			output.push(']');
+		}
 
-			//Object end
-			'}' => {
-				if !in_quote {
-					indent_level -= tab_width;
-					to_return.push('\n');
-					indent(&mut to_return, indent_level, spaces > 0);
-					to_return.push(c);
-					
-				}
-			}
+		_ => push_scalar(output, value)
+	}
+}
 
-			//Array Start
-			'[' => {
-				if !in_quote {
-					indent_level += tab_width;
-					to_return.push(c);
-					to_return.push('\n');
+//Convert a value to compact JSON text
+pub fn serialize(value: &Value) -> String {
+	let mut to_return = String::new();
+	push_compact(&mut to_return, value);
+	return to_return;
+}
 
-					indent(&mut to_return, indent_level, spaces > 0);
+fn push_pretty(output: &mut String, value: &Value, indent_level: usize, tab_width: usize, spaces: bool) {
+	match value {
+		Value::Object(x) if !x.is_empty() => {
+			output.push_str("{\n");
+			for (i, (key, value)) in x.iter().enumerate() {
+				if i > 0 {
+					output.push_str(",\n");
 				}
+				indent(output, indent_level + tab_width, spaces);
+				push_quoted(output, key);
+				output.push(':');
+				push_pretty(output, value, indent_level + tab_width, tab_width, spaces);
 			}
+			output.push('\n');
+			indent(output, indent_level, spaces);
+			output.push('}');
+		}
 
-			//Array end
-			']' => {
-				if !in_quote {
-					indent_level -= tab_width;
-					to_return.push('\n');
-					indent(&mut to_return, indent_level, spaces > 0);
-					to_return.push(c);
-					
+		Value::Array(x) if !x.is_empty() => {
+			output.push_str("[\n");
+			for (i, value) in x.iter().enumerate() {
+				if i > 0 {
+					output.push_str(",\n");
 				}
+				indent(output, indent_level + tab_width, spaces);
+				push_pretty(output, value, indent_level + tab_width, tab_width, spaces);
 			}
+			output.push('\n');
+			indent(output, indent_level, spaces);
+			output.push(']');
+		}
 
-			//Value separator
-			',' => {
-				if !in_quote {
-					to_return.push(c);
-					to_return.push('\n');
-					indent(&mut to_return, indent_level, spaces > 0);
-				}
-			}
+		_ => push_compact(output, value)
+	}
+}
 
-			//Generic character
-			_ => {to_return.push(c);}
-		}
+//Format a value as indented, human-readable JSON text. Each level is indented by the given number
+//of spaces, or by one tab if spaces is 0.
+pub fn prettify(to_print: &Value, spaces: usize) -> String {
+	let mut to_return = String::new();
 
-		prev = c;
+	//Calculate how big a tab step should be
+	let mut tab_width = 1;
+	if spaces > 0 {
+		tab_width = spaces;
 	}
 
+	push_pretty(&mut to_return, to_print, 0, tab_width, spaces > 0);
 	return to_return;
-}
\ No newline at end of file
+}
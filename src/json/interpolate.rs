@@ -0,0 +1,193 @@
+//Replace ${VAR} placeholders in text values with environment variables or caller-provided values.
+//
+//${NAME}           the variable, which must be set
+//${NAME:-default}  the variable, or default when it is unset or empty
+//${NAME:?message}  the variable, or an error with message when it is unset or empty
+//$$                a literal $
+//
+//A $ that doesn't start a placeholder is kept as is. Object keys are never changed.
+
+use crate::json::Value;
+use crate::json::pointer;
+
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+
+//Where placeholder values come from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source<'a> {
+	Environment,
+	Map(&'a HashMap<String, String>)
+}
+
+impl<'a> Source<'a> {
+	fn get(&self, name: &str) -> Option<String> {
+		match self {
+			Source::Environment => env::var(name).ok(),
+			Source::Map(x) => x.get(name).cloned()
+		}
+	}
+}
+
+//Options for interpolate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterpolateOptions<'a> {
+	pub source: Source<'a>,
+	//When a whole text value is one placeholder, turn "true"/"false" into a bool and numbers into a number
+	pub coerce: bool
+}
+
+impl<'a> Default for InterpolateOptions<'a> {
+	fn default() -> InterpolateOptions<'a> {
+		return InterpolateOptions {
+			source: Source::Environment,
+			coerce: false
+		};
+	}
+}
+
+//Error returned when a placeholder can't be filled in
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpolateError {
+	//Pointer to the text value holding the placeholder
+	pub pointer: String,
+	//Name of the variable, empty if the placeholder itself is malformed
+	pub variable: String,
+	pub message: String
+}
+
+impl fmt::Display for InterpolateError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "\"{}\": {}", self.pointer, self.message)
+	}
+}
+
+impl Error for InterpolateError {}
+
+//Result of expanding one text value
+struct Expanded {
+	text: String,
+	//Set when the whole text was a single placeholder
+	whole: bool
+}
+
+fn expand(text: &str, source: &Source, at: &str) -> Result<Expanded, InterpolateError> {
+	let error = |variable: &str, message: String| InterpolateError {
+		pointer: at.to_string(),
+		variable: variable.to_string(),
+		message
+	};
+
+	let mut to_return = String::with_capacity(text.len());
+	let mut placeholders = 0;
+	let mut rest = text;
+
+	while let Some(i) = rest.find('$') {
+		to_return.push_str(&rest[..i]);
+		rest = &rest[i..];
+
+		if rest.starts_with("$$") {
+			to_return.push('$');
+			rest = &rest[2..];
+			continue;
+		}
+		if !rest.starts_with("${") {
+			to_return.push('$');
+			rest = &rest[1..];
+			continue;
+		}
+
+		let end = rest.find('}').ok_or_else(|| error("", String::from("unterminated ${")))?;
+		let inner = &rest[2..end];
+		rest = &rest[end + 1..];
+		placeholders += 1;
+
+		let (name, operator, argument) = match inner.find(':') {
+			Some(i) => (&inner[..i], inner[i + 1..].chars().next(), &inner[(i + 2).min(inner.len())..]),
+			None => (inner, None, "")
+		};
+
+		if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+			return Err(error(name, format!("invalid variable name \"{}\"", name)));
+		}
+
+		let value = source.get(name);
+		let value = match (operator, value) {
+			(None, Some(x)) => x,
+			(None, None) => return Err(error(name, format!("variable {} is not set", name))),
+			(Some('-'), Some(ref x)) if !x.is_empty() => x.clone(),
+			(Some('-'), _) => argument.to_string(),
+			(Some('?'), Some(ref x)) if !x.is_empty() => x.clone(),
+			(Some('?'), _) if argument.is_empty() => return Err(error(name, format!("variable {} is not set", name))),
+			(Some('?'), _) => return Err(error(name, format!("{}: {}", name, argument))),
+			(Some(_), _) => return Err(error(name, format!("unknown operator in ${{{}}}", inner)))
+		};
+		to_return.push_str(&value);
+	}
+	to_return.push_str(rest);
+
+	let whole = placeholders == 1 && text.starts_with("${") && text.ends_with('}') && text.find('}') == Some(text.len() - 1);
+	return Ok(Expanded { text: to_return, whole });
+}
+
+//Convert the text of a whole-string placeholder to a bool or number when it looks like one
+fn coerce(text: String) -> Value {
+	match text.as_str() {
+		"true" => return Value::Bool(true),
+		"false" => return Value::Bool(false),
+		_ => {}
+	}
+
+	//Only plain decimal numbers, so "inf" and "NaN" stay text
+	let numeric = !text.is_empty() && text.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E');
+	match text.parse::<f64>() {
+		Ok(x) if numeric => Value::Number(x),
+		_ => Value::Text(text)
+	}
+}
+
+fn walk(value: &mut Value, options: &InterpolateOptions, at: &mut String) -> Result<(), InterpolateError> {
+	match value {
+		Value::Text(x) => {
+			if !x.contains('$') {
+				return Ok(());
+			}
+
+			let expanded = expand(x, &options.source, at)?;
+			*value = if options.coerce && expanded.whole { coerce(expanded.text) } else { Value::Text(expanded.text) };
+		}
+		Value::Object(x) => {
+			for (key, member) in x.iter_mut() {
+				let len = at.len();
+				at.push('/');
+				at.push_str(&pointer::escape(key));
+				walk(member, options, at)?;
+				at.truncate(len);
+			}
+		}
+		Value::Array(x) => {
+			for (i, item) in x.iter_mut().enumerate() {
+				let len = at.len();
+				at.push_str(&format!("/{}", i));
+				walk(item, options, at)?;
+				at.truncate(len);
+			}
+		}
+		_ => {}
+	}
+
+	return Ok(());
+}
+
+impl Value {
+	//Fill in placeholders in every text value of the tree. On error the value is left unchanged.
+	pub fn interpolate(&mut self, options: &InterpolateOptions) -> Result<(), InterpolateError> {
+		let mut working = self.clone();
+		walk(&mut working, options, &mut String::new())?;
+
+		*self = working;
+		return Ok(());
+	}
+}
@@ -0,0 +1,113 @@
+//Build a json::Value from JSON-like syntax. Any Rust expression can be used as a value
+//as long as it converts into a Value.
+//
+//let value = json!({"id": id, "tags": ["a", name], "ok": true, "x": null});
+#[macro_export]
+macro_rules! json {
+	//Null
+	(null) => {
+		$crate::json::Value::Null
+	};
+
+	//Array
+	([]) => {
+		$crate::json::Value::arr()
+	};
+	([ $($tt:tt)+ ]) => {{
+		let mut array: ::std::vec::Vec<$crate::json::Value> = ::std::vec::Vec::new();
+		$crate::json_internal!(@array array ($($tt)+));
+		$crate::json::Value::Array(array)
+	}};
+
+	//Object
+	({}) => {
+		$crate::json::Value::obj()
+	};
+	({ $($tt:tt)+ }) => {{
+		let mut object: ::std::collections::HashMap<::std::string::String, $crate::json::Value> = ::std::collections::HashMap::new();
+		$crate::json_internal!(@object object ($($tt)+));
+		$crate::json::Value::Object(object)
+	}};
+
+	//Any other expression goes through the From impls
+	($other:expr) => {
+		$crate::json::Value::from($other)
+	};
+}
+
+//Token muncher used by json!. Not part of the public interface.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+	//Array elements
+	(@array $array:ident ()) => {};
+	(@array $array:ident (null , $($rest:tt)*)) => {
+		$array.push($crate::json!(null));
+		$crate::json_internal!(@array $array ($($rest)*));
+	};
+	(@array $array:ident (null)) => {
+		$array.push($crate::json!(null));
+	};
+	(@array $array:ident ([ $($inner:tt)* ] , $($rest:tt)*)) => {
+		$array.push($crate::json!([ $($inner)* ]));
+		$crate::json_internal!(@array $array ($($rest)*));
+	};
+	(@array $array:ident ([ $($inner:tt)* ])) => {
+		$array.push($crate::json!([ $($inner)* ]));
+	};
+	(@array $array:ident ({ $($inner:tt)* } , $($rest:tt)*)) => {
+		$array.push($crate::json!({ $($inner)* }));
+		$crate::json_internal!(@array $array ($($rest)*));
+	};
+	(@array $array:ident ({ $($inner:tt)* })) => {
+		$array.push($crate::json!({ $($inner)* }));
+	};
+	(@array $array:ident ($value:expr , $($rest:tt)*)) => {
+		$array.push($crate::json!($value));
+		$crate::json_internal!(@array $array ($($rest)*));
+	};
+	(@array $array:ident ($value:expr)) => {
+		$array.push($crate::json!($value));
+	};
+
+	//Object members
+	(@object $object:ident ()) => {};
+	(@object $object:ident ($key:tt : null , $($rest:tt)*)) => {
+		$object.insert(::std::string::String::from($key), $crate::json!(null));
+		$crate::json_internal!(@object $object ($($rest)*));
+	};
+	(@object $object:ident ($key:tt : null)) => {
+		$object.insert(::std::string::String::from($key), $crate::json!(null));
+	};
+	(@object $object:ident ($key:tt : [ $($inner:tt)* ] , $($rest:tt)*)) => {
+		$object.insert(::std::string::String::from($key), $crate::json!([ $($inner)* ]));
+		$crate::json_internal!(@object $object ($($rest)*));
+	};
+	(@object $object:ident ($key:tt : [ $($inner:tt)* ])) => {
+		$object.insert(::std::string::String::from($key), $crate::json!([ $($inner)* ]));
+	};
+	(@object $object:ident ($key:tt : { $($inner:tt)* } , $($rest:tt)*)) => {
+		$object.insert(::std::string::String::from($key), $crate::json!({ $($inner)* }));
+		$crate::json_internal!(@object $object ($($rest)*));
+	};
+	(@object $object:ident ($key:tt : { $($inner:tt)* })) => {
+		$object.insert(::std::string::String::from($key), $crate::json!({ $($inner)* }));
+	};
+	(@object $object:ident ($key:tt : $value:expr , $($rest:tt)*)) => {
+		$object.insert(::std::string::String::from($key), $crate::json!($value));
+		$crate::json_internal!(@object $object ($($rest)*));
+	};
+	(@object $object:ident ($key:tt : $value:expr)) => {
+		$object.insert(::std::string::String::from($key), $crate::json!($value));
+	};
+}
+
+//Build a path for Value::get_path and friends from keys and indexes.
+//
+//let name = value.get_path(&path!["users", 0, "name"]);
+#[macro_export]
+macro_rules! path {
+	($($segment:expr),* $(,)*) => {
+		[$($crate::json::PathSegment::from($segment)),*]
+	};
+}
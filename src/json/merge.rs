@@ -0,0 +1,120 @@
+//Merging values: JSON Merge Patch (RFC 7396) and a configurable deep merge for layered configuration
+
+use crate::json::Value;
+
+//How deep_merge combines two arrays
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayStrategy {
+	//The incoming array replaces the existing one
+	Replace,
+	//The incoming items are appended
+	Concat,
+	//Items at the same index are merged, extra incoming items are appended
+	MergeByIndex,
+	//Objects with the same value for this field are merged, other incoming items are appended
+	MergeByKey(String)
+}
+
+//Options for deep_merge
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeOptions {
+	pub arrays: ArrayStrategy,
+	//Whether a null in the incoming value removes the key, as in a merge patch
+	pub null_deletes: bool
+}
+
+impl Default for MergeOptions {
+	fn default() -> MergeOptions {
+		return MergeOptions {
+			arrays: ArrayStrategy::Replace,
+			null_deletes: false
+		};
+	}
+}
+
+impl Value {
+	//Apply a JSON Merge Patch. Objects are merged recursively, null removes a key and anything
+	//else (arrays included) replaces the existing value.
+	pub fn merge_patch(&mut self, patch: &Value) {
+		let members = match patch {
+			Value::Object(x) => x,
+			_ => {
+				*self = patch.clone();
+				return;
+			}
+		};
+
+		if !matches!(self, Value::Object(_)) {
+			*self = Value::obj();
+		}
+
+		if let Value::Object(target) = self {
+			for (key, value) in members {
+				match value {
+					Value::Null => {
+						target.remove(key);
+					}
+					_ => target.entry(key.clone()).or_insert(Value::Null).merge_patch(value)
+				}
+			}
+		}
+	}
+
+	//Merge other into this value. Objects are merged recursively, arrays follow options.arrays
+	//and any other value replaces the existing one.
+	pub fn deep_merge(&mut self, other: &Value, options: &MergeOptions) {
+		match (self, other) {
+			(Value::Object(target), Value::Object(members)) => {
+				for (key, value) in members {
+					if options.null_deletes && *value == Value::Null {
+						target.remove(key);
+						continue;
+					}
+
+					match target.get_mut(key) {
+						Some(x) => x.deep_merge(value, options),
+						None => {
+							target.insert(key.clone(), value.clone());
+						}
+					}
+				}
+			}
+			(Value::Array(target), Value::Array(items)) => merge_arrays(target, items, options),
+			(target, _) => *target = other.clone()
+		}
+	}
+}
+
+fn merge_arrays(target: &mut Vec<Value>, items: &[Value], options: &MergeOptions) {
+	match &options.arrays {
+		ArrayStrategy::Replace => *target = items.to_vec(),
+		ArrayStrategy::Concat => target.extend_from_slice(items),
+		ArrayStrategy::MergeByIndex => {
+			for (i, item) in items.iter().enumerate() {
+				if i < target.len() {
+					target[i].deep_merge(item, options);
+				} else {
+					target.push(item.clone());
+				}
+			}
+		}
+		ArrayStrategy::MergeByKey(field) => {
+			for item in items {
+				let key = match item {
+					Value::Object(x) => x.get(field),
+					_ => None
+				};
+
+				let existing = key.and_then(|key| target.iter().position(|x| match x {
+					Value::Object(x) => x.get(field) == Some(key),
+					_ => false
+				}));
+
+				match existing {
+					Some(i) => target[i].deep_merge(item, options),
+					None => target.push(item.clone())
+				}
+			}
+		}
+	}
+}
@@ -1,303 +1,345 @@
 use crate::json::Value;
 
-//Remove whitespace from a string (except for in quotes)
-#[allow(unused)]
-fn remove_whitespace(input: &str) -> String {
-	let mut to_return = String::from("");
-
-	//Flag that tells us if we're in a quote block or not
-	let mut in_quote = false;
-	//Previous character (so we can know if there was a \" or not)
-	let mut prev = ' ';
-
-	//Loop through every character of the array
-	for c in input.chars() {
-		match c {
-			
-			//Ignore whitespace unless it is in a quote block
-			c if c.is_whitespace() => {
-				if in_quote {
-					to_return.push(c);
-				} else {
-					continue;
-				}
-			}
-
-			//Make sure we know if we're in a quote or not
-			'"' => {
-				if prev != '\\' {
-					in_quote = !in_quote;
-				}
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+//Options for parse_with
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+	//Deeper documents are rejected rather than risking a stack overflow
+	pub max_depth: usize
+}
 
-				to_return.push(c);
-			}
+impl Default for ParseOptions {
+	fn default() -> ParseOptions {
+		return ParseOptions {
+			max_depth: 512
+		};
+	}
+}
 
-			//Every other character is okay to push directly onto the string
-			_ => {to_return.push(c);}
-		}
+//Error returned when text isn't valid JSON
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+	//Byte offset of the problem in the input
+	pub offset: usize,
+	//Line and column of the problem, both starting at 1. Columns count characters, not bytes.
+	pub line: usize,
+	pub column: usize,
+	pub message: String
+}
 
-		prev = c;
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} at line {}, column {}", self.message, self.line, self.column)
 	}
-
-	return to_return;
 }
 
-//Convert a string to a json::Value object
-#[allow(unused)]
-pub fn from_str(value: &str) -> Value {
+impl Error for ParseError {}
 
-	//Check what the first character of the string is to see what the value should be
-	let first = value.chars().nth(0).unwrap();
-	match first {
+struct Parser<'a> {
+	text: &'a str,
+	bytes: &'a [u8],
+	position: usize,
+	depth: usize,
+	max_depth: usize
+}
 
-		//Text
-		'"' => {
-			let value = &value[1..value.len()-1];
-			value.into()
-		}
+impl<'a> Parser<'a> {
+	fn error_at(&self, offset: usize, message: &str) -> ParseError {
+		let before = &self.text[..offset];
+		let line_start = before.rfind('\n').map(|x| x + 1).unwrap_or(0);
+
+		return ParseError {
+			offset,
+			line: before.matches('\n').count() + 1,
+			column: before[line_start..].chars().count() + 1,
+			message: message.to_string()
+		};
+	}
 
-		//Object
-		'{' => {
-			parse_object(value)
-		}
+	fn error(&self, message: &str) -> ParseError {
+		return self.error_at(self.position, message);
+	}
 
-		//Array
-		'[' => {
-			parse_array(value)
+	fn peek(&self) -> Option<u8> {
+		return self.bytes.get(self.position).cloned();
+	}
+
+	fn skip_whitespace(&mut self) {
+		while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+			self.position += 1;
 		}
+	}
 
-		//Boolean (true/false)
-		't' | 'f' => {
-			Value::Bool(value == "true")
+	fn expect(&mut self, c: u8, message: &str) -> Result<(), ParseError> {
+		if self.peek() != Some(c) {
+			return Err(self.error(message));
 		}
 
-		//Null
-		'n' => {
-			if value == "null" {
-				return Value::Null
-			}
+		self.position += 1;
+		return Ok(());
+	}
 
-			//Improperly formatted json will break in this way.
-			println!("Value is n but not null: {}", value);
-			Value::Invalid
-		}
+	fn value(&mut self) -> Result<Value, ParseError> {
+		self.skip_whitespace();
 
-		//Number
-		c if c.is_numeric() => {
-			Value::Number(value.parse::<f64>().unwrap())
-		}
+		match self.peek() {
+			Some(b'{') | Some(b'[') => {
+				if self.depth == self.max_depth {
+					return Err(self.error("nesting is too deep"));
+				}
 
-		//Miscellaneous invalid text
-		_ => {
-			println!("Invalid value: {}", value);
-			Value::Invalid
+				self.depth += 1;
+				let to_return = if self.peek() == Some(b'{') { self.object() } else { self.array() };
+				self.depth -= 1;
+				to_return
+			}
+			Some(b'"') => Ok(Value::Text(self.string()?)),
+			Some(b't') => self.literal("true", Value::Bool(true)),
+			Some(b'f') => self.literal("false", Value::Bool(false)),
+			Some(b'n') => self.literal("null", Value::Null),
+			Some(b'-') | Some(b'0'..=b'9') => self.number(),
+			Some(_) => Err(self.error("expected a value")),
+			None => Err(self.error("unexpected end of input"))
 		}
 	}
-}
 
-//Parse a keypair into a (key, value) tuple
-#[allow(unused)]
-fn parse_keypair(keypair: &str) -> (&str, Value) {
+	fn literal(&mut self, word: &str, value: Value) -> Result<Value, ParseError> {
+		if !self.text[self.position..].starts_with(word) {
+			return Err(self.error("expected a value"));
+		}
 
-	//split keypair value
-	let val = keypair.split_at(keypair.find(':').unwrap());
-	let key = val.0;
-	let value = &val.1[1..];
+		self.position += word.len();
+		return Ok(value);
+	}
 
-	//remove quotes from key value
-	let key = &key[1..key.len()-1];
+	fn digits(&mut self) -> usize {
+		let start = self.position;
+		while let Some(b'0'..=b'9') = self.peek() {
+			self.position += 1;
+		}
+		return self.position - start;
+	}
 
-	//parse value
-	(key, from_str(value))
-}
+	fn number(&mut self) -> Result<Value, ParseError> {
+		let start = self.position;
 
-//Get a list of where we should split a string
-#[allow(unused)]
-fn get_splits(input: &str) -> Option<Vec<usize>> {
+		if self.peek() == Some(b'-') {
+			self.position += 1;
+		}
 
-	//The list of indexes we should split on
-	let mut split_indexes: Vec<usize> = Vec::new();
+		//No leading zeros, so "01" is rejected
+		let integer_start = self.position;
+		match self.digits() {
+			0 => return Err(self.error("expected a digit")),
+			1 => {}
+			_ if self.bytes[integer_start] == b'0' => return Err(self.error_at(integer_start, "numbers can't have leading zeros")),
+			_ => {}
+		}
 
-	//Are we in a quote block or not
-	let mut in_quote = false;
+		if self.peek() == Some(b'.') {
+			self.position += 1;
+			if self.digits() == 0 {
+				return Err(self.error("expected a digit after the decimal point"));
+			}
+		}
 
-	//How many objects deep are we
-	let mut obj_level = 0;
+		if let Some(b'e') | Some(b'E') = self.peek() {
+			self.position += 1;
+			if let Some(b'+') | Some(b'-') = self.peek() {
+				self.position += 1;
+			}
+			if self.digits() == 0 {
+				return Err(self.error("expected a digit in the exponent"));
+			}
+		}
 
-	//How many arrays deep are we
-	let mut arr_level = 0;
-	
-	//Previous character (for '\"')
-	let mut prev: char = ' ';
+		match self.text[start..self.position].parse::<f64>() {
+			Ok(x) if x.is_finite() => Ok(Value::Number(x)),
+			_ => Err(self.error_at(start, "number is out of range"))
+		}
+	}
 
-	//Loop through every character in the string. We need the index to push to the list.
-	for i in 0..input.len() {
-		let cur = input.chars().nth(i).unwrap();
+	fn hex4(&mut self) -> Result<u32, ParseError> {
+		let digits = self.text.get(self.position..self.position + 4).unwrap_or("");
+		if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+			return Err(self.error("expected four hex digits"));
+		}
 
-		match cur {
-			//Quote start/end
-			'"' => {
-				if prev != '\\' {
-					in_quote = !in_quote;
-				}
-			}
-			
-			//Object start
-			'{' => {
-				if !in_quote {
-					obj_level += 1;
-				}
-			}
-			
-			//Object end
-			'}' => {
-				if !in_quote {
-					obj_level -= 1;
-				}
-			}
+		self.position += 4;
+		return Ok(u32::from_str_radix(digits, 16).unwrap());
+	}
 
-			//Array start
-			'[' => {
-				if !in_quote {
-					arr_level += 1;
+	fn string(&mut self) -> Result<String, ParseError> {
+		let start = self.position;
+		self.expect(b'"', "expected a string")?;
+
+		let mut to_return = String::new();
+		loop {
+			//Copy everything up to the next quote, backslash or control character in one go
+			let run = self.position;
+			while let Some(c) = self.peek() {
+				if c == b'"' || c == b'\\' || c < 0x20 {
+					break;
 				}
+				self.position += 1;
 			}
+			to_return.push_str(&self.text[run..self.position]);
 
-			//Array end
-			']' => {
-				if !in_quote {
-					arr_level -= 1;
+			match self.peek() {
+				Some(b'"') => {
+					self.position += 1;
+					return Ok(to_return);
 				}
-			}
-
-			//New split index for the current object
-			',' => {
-				if (obj_level + arr_level == 0) && !in_quote {
-					split_indexes.push(i);
+				Some(b'\\') => {
+					self.position += 1;
+					let escape = self.position;
+					let c = match self.peek() {
+						Some(b'"') => '"',
+						Some(b'\\') => '\\',
+						Some(b'/') => '/',
+						Some(b'b') => '\u{8}',
+						Some(b'f') => '\u{c}',
+						Some(b'n') => '\n',
+						Some(b'r') => '\r',
+						Some(b't') => '\t',
+						Some(b'u') => {
+							self.position += 1;
+							let mut code = self.hex4()?;
+
+							//Characters outside the basic plane are written as a surrogate pair
+							if (0xD800..0xDC00).contains(&code) {
+								if !self.text[self.position..].starts_with("\\u") {
+									return Err(self.error_at(escape - 1, "unpaired surrogate"));
+								}
+								self.position += 2;
+								let low = self.hex4()?;
+								if !(0xDC00..0xE000).contains(&low) {
+									return Err(self.error_at(escape - 1, "unpaired surrogate"));
+								}
+								code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+							}
+
+							to_return.push(std::char::from_u32(code).ok_or_else(|| self.error_at(escape - 1, "unpaired surrogate"))?);
+							continue;
+						}
+						_ => return Err(self.error_at(escape - 1, "invalid escape"))
+					};
+					to_return.push(c);
+					self.position += 1;
 				}
+				Some(_) => return Err(self.error("control characters in strings must be escaped")),
+				None => return Err(self.error_at(start, "unterminated string"))
 			}
-
-			_ => {}
 		}
-
-		prev = cur;
 	}
 
-	//We know that if these conditions are not met, the JSON object is invalid
-	if obj_level != 0 || arr_level != 0 || in_quote {
-		println!("JSON: Split was unable to properly parse value.");
-		return None;
-	}
+	fn object(&mut self) -> Result<Value, ParseError> {
+		self.position += 1;
+		let mut to_return = HashMap::new();
 
-	return Some(split_indexes);
-}
+		self.skip_whitespace();
+		if self.peek() == Some(b'}') {
+			self.position += 1;
+			return Ok(Value::Object(to_return));
+		}
 
-//Parse a JSON object from a string
-#[allow(unused)]
-fn parse_object(input: &str) -> Value {
-	let mut to_return = Value::obj();
+		loop {
+			self.skip_whitespace();
+			let key = self.string()?;
 
-	//We know if the first character isn't an opening bracket, our json object is invalid
-	if input.chars().nth(0).unwrap() != '{' {
-		println!("Object does not start with {{");
-		return Value::Invalid;
-	}
+			self.skip_whitespace();
+			self.expect(b':', "expected ':'")?;
 
-	//Remove whitespace and {} from our object to make parsing easier.
-	let trimmed = remove_whitespace(input);
-	let trimmed_len = trimmed.len();
-	let trimmed = &trimmed[1..trimmed_len-1];
+			let value = self.value()?;
+			to_return.insert(key, value);
 
-	//Get list of split indexes
-	let split_indexes = get_splits(trimmed);
-	if split_indexes.is_none() {
-		return Value::Invalid;
-	}
-	let split_indexes = split_indexes.unwrap();
-
-	//Split along all key:value pairs
-	let mut prev_index = 0;
-	let mut keypairs: Vec<&str> = Vec::new();
-	for i in split_indexes {
-		let first = trimmed.split_at(prev_index).1;
-		let second = first.split_at(i - prev_index);
-		let data = second.0;
-
-		keypairs.push(&data);
-		prev_index = i;
-	}
-	keypairs.push(trimmed.split_at(prev_index).1);
-
-	//Remove ,
-	for i in 1..keypairs.len() {
-		keypairs[i] = &keypairs[i][1..];
+			self.skip_whitespace();
+			match self.peek() {
+				Some(b',') => self.position += 1,
+				Some(b'}') => {
+					self.position += 1;
+					return Ok(Value::Object(to_return));
+				}
+				_ => return Err(self.error("expected ',' or '}'"))
+			}
+		}
 	}
 
-	//Parse all keypairs
-	for pair in keypairs {
-		let result = parse_keypair(pair);
+	fn array(&mut self) -> Result<Value, ParseError> {
+		self.position += 1;
+		let mut to_return = Vec::new();
 
-		//Some part of the data is invalid
-		if let Value::Invalid = result.1 {
-			return Value::Invalid;
+		self.skip_whitespace();
+		if self.peek() == Some(b']') {
+			self.position += 1;
+			return Ok(Value::Array(to_return));
 		}
 
-		to_return[result.0] = result.1;
-	}
+		loop {
+			to_return.push(self.value()?);
 
-	return to_return;
+			self.skip_whitespace();
+			match self.peek() {
+				Some(b',') => self.position += 1,
+				Some(b']') => {
+					self.position += 1;
+					return Ok(Value::Array(to_return));
+				}
+				_ => return Err(self.error("expected ',' or ']'"))
+			}
+		}
+	}
 }
 
+//Parse text into a Value, describing where the text stops being valid JSON on failure
+pub fn parse(text: &str) -> Result<Value, ParseError> {
+	return parse_with(text, &ParseOptions::default());
+}
 
-//Parse a string into an array
-#[allow(unused)]
-fn parse_array(input: &str) -> Value{
-	let mut to_return = Value::arr();
-	
-	//We know if the first character of our array isn't [, it is invalid.
-	if input.chars().nth(0).unwrap() != '[' {
-		println!("JSON: first character of array is not [.");
-		return Value::Invalid;
-	}
-
-	//Trim whitespace and [] from input
-	let trimmed = remove_whitespace(input);
-	let trimmed_len = trimmed.len();
-	let trimmed = &trimmed[1..trimmed_len-1];
+fn parser<'a>(text: &'a str, options: &ParseOptions) -> Parser<'a> {
+	return Parser {
+		text,
+		bytes: text.as_bytes(),
+		position: 0,
+		depth: 0,
+		max_depth: options.max_depth
+	};
+}
 
-	//Get split indexes
-	let split_indexes = get_splits(trimmed);
-	if split_indexes.is_none() {
-		return Value::Invalid;
-	}
-	let split_indexes = split_indexes.unwrap();
-
-	//Split into value strings
-	let mut prev_index = 0;
-	let mut values: Vec<&str> = Vec::new();
-	for i in split_indexes {
-		let temp = trimmed.split_at(i);
-		values.push(&temp.0);
-		prev_index = i;
-	}
-	values.push(trimmed.split_at(prev_index).1);
+//Parse text into a Value with options
+pub fn parse_with(text: &str, options: &ParseOptions) -> Result<Value, ParseError> {
+	let mut parser = parser(text, options);
+	let to_return = parser.value()?;
 
-	//Remove ,
-	for i in 1..values.len() {
-		values[i] = &values[i][1..];
+	parser.skip_whitespace();
+	if parser.position != text.len() {
+		return Err(parser.error("unexpected text after the value"));
 	}
 
-	//Parse values
-	for value in values {
-		let text = remove_whitespace(value);
-		let val = from_str(text.as_str());
+	return Ok(to_return);
+}
 
-		if let Value::Invalid = val {
-			return Value::Invalid;
+//Parse bytes that should be UTF-8 JSON, such as the contents of a file. Invalid UTF-8 is reported
+//like any other parse error.
+pub fn parse_bytes(bytes: &[u8], options: &ParseOptions) -> Result<Value, ParseError> {
+	match std::str::from_utf8(bytes) {
+		Ok(x) => parse_with(x, options),
+		Err(e) => {
+			//Everything before the bad byte is valid, so it can be used to find the line and column
+			let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap();
+			Err(parser(valid, options).error_at(e.valid_up_to(), "invalid UTF-8"))
 		}
-
-		to_return.add(val);
 	}
+}
 
-	return to_return;
-}
\ No newline at end of file
+//Convert a string to a json::Value object. Text that isn't valid JSON gives Value::Invalid;
+//use parse to find out why.
+//The text must hold exactly one value, optionally surrounded by whitespace, and follow RFC 8259:
+//string escapes are decoded, while trailing text, bare words, malformed numbers and nesting deeper
+//than ParseOptions::default().max_depth are all Invalid. It never panics, whatever the input.
+pub fn from_str(value: &str) -> Value {
+	return parse(value).unwrap_or(Value::Invalid);
+}
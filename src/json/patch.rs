@@ -0,0 +1,353 @@
+//JSON Patch (RFC 6902): apply a list of operations to a Value, or generate the operations that
+//turn one Value into another.
+//
+//Patches are read from and written to JSON with the FromJson/ToJson traits.
+
+use crate::json::Value;
+use crate::json::pointer;
+use crate::json::pointer::ArrayIndex;
+use crate::json::traits::{FromJson, FromJsonError, ToJson};
+use crate::json::traits::__private;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+//One patch operation. Paths and from are JSON Pointers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+	Add { path: String, value: Value },
+	Remove { path: String },
+	Replace { path: String, value: Value },
+	Move { from: String, path: String },
+	Copy { from: String, path: String },
+	Test { path: String, value: Value }
+}
+
+//Error returned when a patch can't be applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchError {
+	//Index of the failing operation in the patch
+	pub operation: usize,
+	pub message: String
+}
+
+impl fmt::Display for PatchError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "patch operation {}: {}", self.operation, self.message)
+	}
+}
+
+impl Error for PatchError {}
+
+impl Operation {
+	//Name of the operation as written in the "op" member
+	pub fn name(&self) -> &'static str {
+		match self {
+			Operation::Add { .. } => "add",
+			Operation::Remove { .. } => "remove",
+			Operation::Replace { .. } => "replace",
+			Operation::Move { .. } => "move",
+			Operation::Copy { .. } => "copy",
+			Operation::Test { .. } => "test"
+		}
+	}
+
+	pub fn path(&self) -> &str {
+		match self {
+			Operation::Add { path, .. } => path,
+			Operation::Remove { path } => path,
+			Operation::Replace { path, .. } => path,
+			Operation::Move { path, .. } => path,
+			Operation::Copy { path, .. } => path,
+			Operation::Test { path, .. } => path
+		}
+	}
+}
+
+impl ToJson for Operation {
+	fn to_json(&self) -> Value {
+		let mut object = HashMap::new();
+		object.insert(String::from("op"), Value::from(self.name()));
+		object.insert(String::from("path"), Value::from(self.path()));
+
+		match self {
+			Operation::Add { value, .. } | Operation::Replace { value, .. } | Operation::Test { value, .. } => {
+				object.insert(String::from("value"), value.clone());
+			}
+			Operation::Move { from, .. } | Operation::Copy { from, .. } => {
+				object.insert(String::from("from"), Value::from(from.as_str()));
+			}
+			Operation::Remove { .. } => {}
+		}
+
+		return Value::Object(object);
+	}
+}
+
+impl FromJson for Operation {
+	fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+		let object = __private::object(value)?;
+		let op = __private::tag(object, "op")?;
+		let path: String = __private::field(object, "path")?;
+
+		//value may legitimately be null, so it has to be present rather than non-null
+		let value = || match object.get("value") {
+			Some(x) => Ok(x.clone()),
+			None => Err(FromJsonError::missing_field("value"))
+		};
+
+		match op {
+			"add" => Ok(Operation::Add { path, value: value()? }),
+			"remove" => Ok(Operation::Remove { path }),
+			"replace" => Ok(Operation::Replace { path, value: value()? }),
+			"move" => Ok(Operation::Move { from: __private::field(object, "from")?, path }),
+			"copy" => Ok(Operation::Copy { from: __private::field(object, "from")?, path }),
+			"test" => Ok(Operation::Test { path, value: value()? }),
+			_ => Err(FromJsonError::new(&format!("unknown operation \"{}\"", op)).with_key("op"))
+		}
+	}
+}
+
+//Split a pointer into its parent pointer and its last unescaped token
+fn split(path: &str) -> Result<(&str, String), String> {
+	let mut tokens = pointer::parse(path).map_err(|e| e.to_string())?;
+
+	match tokens.pop() {
+		Some(last) => Ok((&path[..path.rfind('/').unwrap()], last)),
+		None => Err(String::from("the root has no parent"))
+	}
+}
+
+fn add(target: &mut Value, path: &str, value: Value) -> Result<(), String> {
+	if path.is_empty() {
+		*target = value;
+		return Ok(());
+	}
+
+	let (parent, last) = split(path)?;
+	match target.pointer_mut(parent).map_err(|e| e.to_string())? {
+		Value::Object(x) => {
+			x.insert(last, value);
+		}
+		Value::Array(x) => {
+			//Unlike pointer_set, adding to an array inserts rather than replaces
+			let index = match pointer::array_index(&last) {
+				Some(ArrayIndex::At(i)) if i <= x.len() => i,
+				Some(ArrayIndex::End) => x.len(),
+				_ => return Err(format!("invalid array index \"{}\" in \"{}\"", last, path))
+			};
+			x.insert(index, value);
+		}
+		x => return Err(format!("can't add to {} at \"{}\"", x.type_name(), path))
+	}
+
+	return Ok(());
+}
+
+fn remove(target: &mut Value, path: &str) -> Result<Value, String> {
+	return target.pointer_remove(path).map_err(|e| e.to_string());
+}
+
+fn apply_one(target: &mut Value, operation: &Operation) -> Result<(), String> {
+	match operation {
+		Operation::Add { path, value } => add(target, path, value.clone()),
+		Operation::Remove { path } => remove(target, path).map(|_| ()),
+		Operation::Replace { path, value } => {
+			let current = target.pointer_mut(path).map_err(|e| e.to_string())?;
+			*current = value.clone();
+			Ok(())
+		}
+		Operation::Move { from, path } => {
+			if from == path {
+				return target.pointer(from).map(|_| ()).map_err(|e| e.to_string());
+			}
+			if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+				return Err(format!("can't move \"{}\" into its own child \"{}\"", from, path));
+			}
+
+			let value = remove(target, from)?;
+			add(target, path, value)
+		}
+		Operation::Copy { from, path } => {
+			let value = target.pointer(from).map_err(|e| e.to_string())?.clone();
+			add(target, path, value)
+		}
+		Operation::Test { path, value } => {
+			let current = target.pointer(path).map_err(|e| e.to_string())?;
+			if current != value {
+				return Err(format!("test failed at \"{}\"", path));
+			}
+			Ok(())
+		}
+	}
+}
+
+//Apply a patch. Either every operation succeeds or target is left unchanged.
+pub fn apply(target: &mut Value, patch: &[Operation]) -> Result<(), PatchError> {
+	let mut working = target.clone();
+
+	for (i, operation) in patch.iter().enumerate() {
+		apply_one(&mut working, operation).map_err(|message| PatchError { operation: i, message })?;
+	}
+
+	*target = working;
+	return Ok(());
+}
+
+//Generate a patch that turns from into to. Arrays are compared element by element so that
+//insertions and deletions become single add/remove operations.
+pub fn generate(from: &Value, to: &Value) -> Vec<Operation> {
+	let mut to_return: Vec<Operation> = Vec::new();
+	generate_into(&mut to_return, String::new(), from, to);
+	return to_return;
+}
+
+fn generate_into(patch: &mut Vec<Operation>, path: String, from: &Value, to: &Value) {
+	if from == to {
+		return;
+	}
+
+	match (from, to) {
+		(Value::Object(a), Value::Object(b)) => {
+			//Sort keys so the output doesn't depend on hash order
+			let mut removed: Vec<&String> = a.keys().filter(|k| !b.contains_key(*k)).collect();
+			removed.sort();
+			for key in removed {
+				patch.push(Operation::Remove { path: format!("{}/{}", path, pointer::escape(key)) });
+			}
+
+			let mut keys: Vec<&String> = b.keys().collect();
+			keys.sort();
+			for key in keys {
+				let child = format!("{}/{}", path, pointer::escape(key));
+				match a.get(key) {
+					Some(x) => generate_into(patch, child, x, &b[key]),
+					None => patch.push(Operation::Add { path: child, value: b[key].clone() })
+				}
+			}
+		}
+		(Value::Array(a), Value::Array(b)) => generate_array(patch, &path, a, b),
+		_ => patch.push(Operation::Replace { path, value: to.clone() })
+	}
+}
+
+//Steps of an edit script between two arrays
+enum Edit {
+	Keep,
+	Delete(usize),
+	Insert(usize)
+}
+
+//Largest LCS table edit_script builds, in cells. Bigger middles are compared index by index instead.
+const MAX_TABLE: usize = 1 << 20;
+
+//Longest common subsequence edit script. Common prefixes and suffixes are trimmed first,
+//which keeps the table small for the usual case of a few changes in a long array.
+fn edit_script(a: &[Value], b: &[Value]) -> Vec<Edit> {
+	let mut prefix = 0;
+	while prefix < a.len() && prefix < b.len() && a[prefix] == b[prefix] {
+		prefix += 1;
+	}
+
+	let mut suffix = 0;
+	while suffix < a.len() - prefix && suffix < b.len() - prefix && a[a.len() - 1 - suffix] == b[b.len() - 1 - suffix] {
+		suffix += 1;
+	}
+
+	let a_mid = &a[prefix..a.len() - suffix];
+	let b_mid = &b[prefix..b.len() - suffix];
+	let n = a_mid.len();
+	let m = b_mid.len();
+
+	let mut to_return: Vec<Edit> = Vec::new();
+	for _ in 0..prefix {
+		to_return.push(Edit::Keep);
+	}
+
+	//Too big to diff: delete and reinsert the whole middle, which generate_array turns into
+	//index-wise changes followed by removes or adds for the difference in length
+	let too_big = match (n + 1).checked_mul(m + 1) {
+		Some(x) => x > MAX_TABLE,
+		None => true
+	};
+	if too_big {
+		to_return.extend((0..n).map(|i| Edit::Delete(prefix + i)));
+		to_return.extend((0..m).map(|j| Edit::Insert(prefix + j)));
+		to_return.extend((0..suffix).map(|_| Edit::Keep));
+		return to_return;
+	}
+
+	//table[i][j] is the LCS length of a_mid[i..] and b_mid[j..]
+	let mut table = vec![vec![0usize; m + 1]; n + 1];
+	for i in (0..n).rev() {
+		for j in (0..m).rev() {
+			table[i][j] = if a_mid[i] == b_mid[j] { table[i + 1][j + 1] + 1 } else { table[i + 1][j].max(table[i][j + 1]) };
+		}
+	}
+
+	let (mut i, mut j) = (0, 0);
+	while i < n || j < m {
+		if i < n && j < m && a_mid[i] == b_mid[j] {
+			to_return.push(Edit::Keep);
+			i += 1;
+			j += 1;
+		} else if j < m && (i == n || table[i][j + 1] >= table[i + 1][j]) {
+			to_return.push(Edit::Insert(prefix + j));
+			j += 1;
+		} else {
+			to_return.push(Edit::Delete(prefix + i));
+			i += 1;
+		}
+	}
+
+	for _ in 0..suffix {
+		to_return.push(Edit::Keep);
+	}
+
+	return to_return;
+}
+
+fn generate_array(patch: &mut Vec<Operation>, path: &str, a: &[Value], b: &[Value]) {
+	let script = edit_script(a, b);
+
+	//Position in the array as it is being patched
+	let mut position = 0;
+	let mut i = 0;
+
+	while i < script.len() {
+		if let Edit::Keep = script[i] {
+			position += 1;
+			i += 1;
+			continue;
+		}
+
+		//Gather a run of changes between two kept elements
+		let mut deleted: Vec<usize> = Vec::new();
+		let mut inserted: Vec<usize> = Vec::new();
+		while i < script.len() {
+			match script[i] {
+				Edit::Delete(x) => deleted.push(x),
+				Edit::Insert(x) => inserted.push(x),
+				Edit::Keep => break
+			}
+			i += 1;
+		}
+
+		//A deletion and an insertion at the same place is a change to that element
+		let paired = deleted.len().min(inserted.len());
+		for k in 0..paired {
+			generate_into(patch, format!("{}/{}", path, position), &a[deleted[k]], &b[inserted[k]]);
+			position += 1;
+		}
+
+		for _ in paired..deleted.len() {
+			patch.push(Operation::Remove { path: format!("{}/{}", path, position) });
+		}
+
+		for k in paired..inserted.len() {
+			patch.push(Operation::Add { path: format!("{}/{}", path, position), value: b[inserted[k]].clone() });
+			position += 1;
+		}
+	}
+}
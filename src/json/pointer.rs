@@ -0,0 +1,236 @@
+use crate::json::Value;
+
+use std::error::Error;
+use std::fmt;
+
+//Error returned when a JSON Pointer (RFC 6901) can't be parsed or followed
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerError {
+	//The whole pointer
+	pub pointer: String,
+	//Position of the failing reference token (0 is the first token after the root)
+	pub position: usize,
+	//The failing reference token, unescaped. A token with an invalid escape can't be unescaped and
+	//is given as written.
+	pub token: String,
+	pub message: String
+}
+
+impl fmt::Display for PointerError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "pointer \"{}\": token {} (\"{}\"): {}", self.pointer, self.position, self.token, self.message)
+	}
+}
+
+impl Error for PointerError {}
+
+//Split a pointer into its unescaped reference tokens
+pub fn parse(pointer: &str) -> Result<Vec<String>, PointerError> {
+	if pointer.is_empty() {
+		return Ok(Vec::new());
+	}
+
+	if !pointer.starts_with('/') {
+		return Err(PointerError {
+			pointer: pointer.to_string(),
+			position: 0,
+			token: String::new(),
+			message: String::from("pointer must be empty or start with /")
+		});
+	}
+
+	let mut to_return: Vec<String> = Vec::new();
+	for (position, raw) in pointer[1..].split('/').enumerate() {
+		let mut token = String::new();
+		let mut chars = raw.chars();
+
+		while let Some(c) = chars.next() {
+			if c != '~' {
+				token.push(c);
+				continue;
+			}
+
+			//~0 is ~ and ~1 is /. Anything else is invalid.
+			match chars.next() {
+				Some('0') => token.push('~'),
+				Some('1') => token.push('/'),
+				_ => return Err(PointerError {
+					pointer: pointer.to_string(),
+					position,
+					token: raw.to_string(),
+					message: String::from("~ must be followed by 0 or 1")
+				})
+			}
+		}
+
+		to_return.push(token);
+	}
+
+	return Ok(to_return);
+}
+
+//Escape a key so it can be used as a reference token
+pub fn escape(key: &str) -> String {
+	return key.replace('~', "~0").replace('/', "~1");
+}
+
+//Where a reference token points inside an array
+pub(crate) enum ArrayIndex {
+	At(usize),
+	End
+}
+
+//Array indexes are "-" or digits without leading zeros
+pub(crate) fn array_index(token: &str) -> Option<ArrayIndex> {
+	if token == "-" {
+		return Some(ArrayIndex::End);
+	}
+
+	if token.is_empty() || !token.chars().all(|c| c.is_ascii_digit()) || (token.len() > 1 && token.starts_with('0')) {
+		return None;
+	}
+
+	return token.parse::<usize>().ok().map(ArrayIndex::At);
+}
+
+//Builds errors for one pointer
+struct Context<'a> {
+	pointer: &'a str,
+	tokens: &'a [String]
+}
+
+impl<'a> Context<'a> {
+	fn error(&self, position: usize, message: &str) -> PointerError {
+		return PointerError {
+			pointer: self.pointer.to_string(),
+			position,
+			token: self.tokens[position].clone(),
+			message: message.to_string()
+		};
+	}
+
+	fn step<'v>(&self, current: &'v Value, position: usize) -> Result<&'v Value, PointerError> {
+		let token = &self.tokens[position];
+
+		match current {
+			Value::Object(x) => x.get(token).ok_or_else(|| self.error(position, "key not found")),
+			Value::Array(x) => match array_index(token) {
+				Some(ArrayIndex::At(i)) if i < x.len() => Ok(&x[i]),
+				Some(_) => Err(self.error(position, "index out of bounds")),
+				None => Err(self.error(position, "invalid array index"))
+			},
+			_ => Err(self.error(position, &format!("can't index into {}", current.type_name())))
+		}
+	}
+
+	fn step_mut<'v>(&self, current: &'v mut Value, position: usize) -> Result<&'v mut Value, PointerError> {
+		let token = &self.tokens[position];
+
+		match current {
+			Value::Object(x) => x.get_mut(token).ok_or_else(|| self.error(position, "key not found")),
+			Value::Array(x) => match array_index(token) {
+				Some(ArrayIndex::At(i)) if i < x.len() => Ok(&mut x[i]),
+				Some(_) => Err(self.error(position, "index out of bounds")),
+				None => Err(self.error(position, "invalid array index"))
+			},
+			_ => Err(self.error(position, &format!("can't index into {}", current.type_name())))
+		}
+	}
+
+	//Follow every token but the last, returning the parent of the target
+	fn parent_mut<'v>(&self, root: &'v mut Value) -> Result<&'v mut Value, PointerError> {
+		let mut current = root;
+		for position in 0..self.tokens.len() - 1 {
+			current = self.step_mut(current, position)?;
+		}
+
+		return Ok(current);
+	}
+}
+
+impl Value {
+	//Look up a value with a JSON Pointer such as "/users/0/name"
+	pub fn pointer(&self, pointer: &str) -> Result<&Value, PointerError> {
+		let tokens = parse(pointer)?;
+		let context = Context { pointer, tokens: &tokens };
+
+		let mut current = self;
+		for position in 0..tokens.len() {
+			current = context.step(current, position)?;
+		}
+
+		return Ok(current);
+	}
+
+	//Mutable version of pointer
+	pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut Value, PointerError> {
+		let tokens = parse(pointer)?;
+		let context = Context { pointer, tokens: &tokens };
+
+		let mut current = self;
+		for position in 0..tokens.len() {
+			current = context.step_mut(current, position)?;
+		}
+
+		return Ok(current);
+	}
+
+	//Set the value a pointer refers to, returning the value it replaced.
+	//The parent must already exist. Object keys are created, and array indexes may be an existing
+	//element, the length of the array or "-" (both of which append).
+	pub fn pointer_set(&mut self, pointer: &str, value: Value) -> Result<Option<Value>, PointerError> {
+		let tokens = parse(pointer)?;
+		if tokens.is_empty() {
+			return Ok(Some(std::mem::replace(self, value)));
+		}
+
+		let context = Context { pointer, tokens: &tokens };
+		let last = tokens.len() - 1;
+		let parent = context.parent_mut(self)?;
+
+		match parent {
+			Value::Object(x) => Ok(x.insert(tokens[last].clone(), value)),
+			Value::Array(x) => match array_index(&tokens[last]) {
+				Some(ArrayIndex::At(i)) if i < x.len() => Ok(Some(std::mem::replace(&mut x[i], value))),
+				Some(ArrayIndex::At(i)) if i == x.len() => {
+					x.push(value);
+					Ok(None)
+				}
+				Some(ArrayIndex::End) => {
+					x.push(value);
+					Ok(None)
+				}
+				Some(_) => Err(context.error(last, "index out of bounds")),
+				None => Err(context.error(last, "invalid array index"))
+			},
+			_ => Err(context.error(last, &format!("can't index into {}", parent.type_name())))
+		}
+	}
+
+	//Remove the value a pointer refers to and return it. The root can't be removed.
+	pub fn pointer_remove(&mut self, pointer: &str) -> Result<Value, PointerError> {
+		let tokens = parse(pointer)?;
+		if tokens.is_empty() {
+			return Err(PointerError {
+				pointer: pointer.to_string(),
+				position: 0,
+				token: String::new(),
+				message: String::from("can't remove the root value")
+			});
+		}
+
+		let context = Context { pointer, tokens: &tokens };
+		let last = tokens.len() - 1;
+		let parent = context.parent_mut(self)?;
+
+		match parent {
+			Value::Object(x) => x.remove(&tokens[last]).ok_or_else(|| context.error(last, "key not found")),
+			Value::Array(x) => match array_index(&tokens[last]) {
+				Some(ArrayIndex::At(i)) if i < x.len() => Ok(x.remove(i)),
+				Some(_) => Err(context.error(last, "index out of bounds")),
+				None => Err(context.error(last, "invalid array index"))
+			},
+			_ => Err(context.error(last, &format!("can't index into {}", parent.type_name())))
+		}
+	}
+}
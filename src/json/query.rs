@@ -0,0 +1,1039 @@
+//JSONPath queries (RFC 9535), like $.orders[?(@.total > 100)].id
+//
+//A query is compiled once into a Query, which can then select nodes from any number of documents.
+//Selected nodes are references into the queried Value.
+
+use crate::json::Value;
+use crate::json::regex::Regex;
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+//Error returned when a query can't be compiled
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+	//Character offset into the query
+	pub position: usize,
+	pub message: String
+}
+
+impl fmt::Display for QueryError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invalid query at {}: {}", self.position, self.message)
+	}
+}
+
+impl Error for QueryError {}
+
+//Integers in queries have to fit in an IEEE double exactly
+const MAX_INT: i64 = (1 << 53) - 1;
+
+#[derive(Debug, Clone)]
+enum Segment {
+	Child(Vec<Selector>),
+	Descendant(Vec<Selector>)
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+	Name(String),
+	Wildcard,
+	Index(i64),
+	Slice(Option<i64>, Option<i64>, i64),
+	Filter(Box<Expr>)
+}
+
+//A query inside a filter, starting at the root ($) or the current node (@)
+#[derive(Debug, Clone)]
+struct FilterQuery {
+	relative: bool,
+	segments: Vec<Segment>
+}
+
+impl FilterQuery {
+	//Singular queries select at most one node: only names and indexes, no descendants
+	fn is_singular(&self) -> bool {
+		return self.segments.iter().all(|segment| match segment {
+			Segment::Child(x) => x.len() == 1 && matches!(x[0], Selector::Name(_) | Selector::Index(_)),
+			Segment::Descendant(_) => false
+		});
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+	Equal,
+	NotEqual,
+	Less,
+	LessEqual,
+	Greater,
+	GreaterEqual
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+	Value,
+	Logical,
+	Nodes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FunctionName {
+	Length,
+	Count,
+	Match,
+	Search,
+	Value
+}
+
+impl FunctionName {
+	fn from_name(name: &str) -> Option<FunctionName> {
+		match name {
+			"length" => Some(FunctionName::Length),
+			"count" => Some(FunctionName::Count),
+			"match" => Some(FunctionName::Match),
+			"search" => Some(FunctionName::Search),
+			"value" => Some(FunctionName::Value),
+			_ => None
+		}
+	}
+
+	//Parameter types and result type
+	fn signature(&self) -> (&'static [Kind], Kind) {
+		match self {
+			FunctionName::Length => (&[Kind::Value], Kind::Value),
+			FunctionName::Count => (&[Kind::Nodes], Kind::Value),
+			FunctionName::Match => (&[Kind::Value, Kind::Value], Kind::Logical),
+			FunctionName::Search => (&[Kind::Value, Kind::Value], Kind::Logical),
+			FunctionName::Value => (&[Kind::Nodes], Kind::Value)
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+enum Argument {
+	Literal(Value),
+	Query(FilterQuery),
+	Function(Function)
+}
+
+#[derive(Debug, Clone)]
+struct Function {
+	name: FunctionName,
+	args: Vec<Argument>,
+	//match() and search() with a literal pattern compile it once up front
+	regex: Option<Regex>
+}
+
+//Something that can be compared: a literal, a singular query or a function returning a value
+#[derive(Debug, Clone)]
+enum Comparable {
+	Literal(Value),
+	Query(FilterQuery),
+	Function(Function)
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+	Or(Vec<Expr>),
+	And(Vec<Expr>),
+	Not(Box<Expr>),
+	Compare(Comparable, Op, Comparable),
+	Exists(FilterQuery),
+	Function(Function)
+}
+
+//A compiled JSONPath query
+#[derive(Debug, Clone)]
+pub struct Query {
+	segments: Vec<Segment>
+}
+
+impl Query {
+	pub fn compile(query: &str) -> Result<Query, QueryError> {
+		let chars: Vec<char> = query.chars().collect();
+		let mut parser = Parser { chars: &chars, pos: 0 };
+
+		if !parser.eat('$') {
+			return Err(parser.error("query must start with $"));
+		}
+
+		let segments = parser.parse_segments()?;
+		if parser.pos != chars.len() {
+			return Err(parser.error("unexpected character"));
+		}
+
+		return Ok(Query { segments });
+	}
+
+	//Select every node the query matches, in document order
+	pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+		return apply_segments(&self.segments, vec![root], root);
+	}
+}
+
+impl FromStr for Query {
+	type Err = QueryError;
+
+	fn from_str(query: &str) -> Result<Query, QueryError> {
+		return Query::compile(query);
+	}
+}
+
+impl Value {
+	//Compile and run a JSONPath query against this value
+	pub fn query(&self, query: &str) -> Result<Vec<&Value>, QueryError> {
+		return Ok(Query::compile(query)?.select(self));
+	}
+}
+
+struct Parser<'a> {
+	chars: &'a [char],
+	pos: usize
+}
+
+impl<'a> Parser<'a> {
+	fn error(&self, message: &str) -> QueryError {
+		return QueryError {
+			position: self.pos,
+			message: message.to_string()
+		};
+	}
+
+	fn peek(&self) -> Option<char> {
+		return self.chars.get(self.pos).cloned();
+	}
+
+	fn eat(&mut self, c: char) -> bool {
+		if self.peek() == Some(c) {
+			self.pos += 1;
+			return true;
+		}
+		return false;
+	}
+
+	fn eat_str(&mut self, text: &str) -> bool {
+		let len = text.chars().count();
+		if self.pos + len <= self.chars.len() && self.chars[self.pos..self.pos + len].iter().cloned().eq(text.chars()) {
+			self.pos += len;
+			return true;
+		}
+		return false;
+	}
+
+	fn expect(&mut self, c: char) -> Result<(), QueryError> {
+		if self.eat(c) {
+			return Ok(());
+		}
+		return Err(self.error(&format!("expected {}", c)));
+	}
+
+	//Blank space: space, tab, newline and carriage return
+	fn skip_blank(&mut self) {
+		while let Some(c) = self.peek() {
+			if c != ' ' && c != '\t' && c != '\n' && c != '\r' {
+				break;
+			}
+			self.pos += 1;
+		}
+	}
+
+	fn parse_segments(&mut self) -> Result<Vec<Segment>, QueryError> {
+		let mut segments: Vec<Segment> = Vec::new();
+
+		loop {
+			//Blank space is only allowed if another segment follows
+			let start = self.pos;
+			self.skip_blank();
+
+			match self.peek() {
+				Some('[') | Some('.') => segments.push(self.parse_segment()?),
+				_ => {
+					self.pos = start;
+					return Ok(segments);
+				}
+			}
+		}
+	}
+
+	fn parse_segment(&mut self) -> Result<Segment, QueryError> {
+		if self.eat_str("..") {
+			return match self.peek() {
+				Some('[') => Ok(Segment::Descendant(self.parse_bracketed()?)),
+				Some('*') => {
+					self.pos += 1;
+					Ok(Segment::Descendant(vec![Selector::Wildcard]))
+				}
+				_ => Ok(Segment::Descendant(vec![Selector::Name(self.parse_member_name()?)]))
+			};
+		}
+
+		if self.eat('.') {
+			if self.eat('*') {
+				return Ok(Segment::Child(vec![Selector::Wildcard]));
+			}
+			return Ok(Segment::Child(vec![Selector::Name(self.parse_member_name()?)]));
+		}
+
+		return Ok(Segment::Child(self.parse_bracketed()?));
+	}
+
+	//Shorthand names after a dot: a letter, _ or non-ASCII character, then digits too
+	fn parse_member_name(&mut self) -> Result<String, QueryError> {
+		let first = |c: char| c.is_ascii_alphabetic() || c == '_' || c as u32 >= 0x80;
+
+		match self.peek() {
+			Some(c) if first(c) => (),
+			_ => return Err(self.error("expected a member name"))
+		}
+
+		let start = self.pos;
+		while let Some(c) = self.peek() {
+			if !first(c) && !c.is_ascii_digit() {
+				break;
+			}
+			self.pos += 1;
+		}
+
+		return Ok(self.chars[start..self.pos].iter().collect());
+	}
+
+	fn parse_bracketed(&mut self) -> Result<Vec<Selector>, QueryError> {
+		self.expect('[')?;
+		let mut selectors: Vec<Selector> = Vec::new();
+
+		loop {
+			self.skip_blank();
+			selectors.push(self.parse_selector()?);
+			self.skip_blank();
+
+			if self.eat(']') {
+				return Ok(selectors);
+			}
+			self.expect(',')?;
+		}
+	}
+
+	fn parse_selector(&mut self) -> Result<Selector, QueryError> {
+		match self.peek() {
+			Some('\'') | Some('"') => Ok(Selector::Name(self.parse_string()?)),
+			Some('*') => {
+				self.pos += 1;
+				Ok(Selector::Wildcard)
+			}
+			Some('?') => {
+				self.pos += 1;
+				self.skip_blank();
+				Ok(Selector::Filter(Box::new(self.parse_or()?)))
+			}
+			Some(c) if c == '-' || c == ':' || c.is_ascii_digit() => self.parse_index_or_slice(),
+			_ => Err(self.error("expected a selector"))
+		}
+	}
+
+	fn parse_int(&mut self) -> Result<i64, QueryError> {
+		let start = self.pos;
+		let negative = self.eat('-');
+
+		let digits_start = self.pos;
+		while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+			self.pos += 1;
+		}
+
+		let digits: String = self.chars[digits_start..self.pos].iter().collect();
+		if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0')) || (negative && digits == "0") {
+			self.pos = start;
+			return Err(self.error("invalid integer"));
+		}
+
+		match digits.parse::<i64>() {
+			Ok(x) if x <= MAX_INT => Ok(if negative { -x } else { x }),
+			_ => {
+				self.pos = start;
+				Err(self.error("integer out of range"))
+			}
+		}
+	}
+
+	fn parse_index_or_slice(&mut self) -> Result<Selector, QueryError> {
+		let start = if self.peek() == Some(':') { None } else { Some(self.parse_int()?) };
+		self.skip_blank();
+
+		if !self.eat(':') {
+			return match start {
+				Some(x) => Ok(Selector::Index(x)),
+				None => Err(self.error("expected an index"))
+			};
+		}
+		self.skip_blank();
+
+		let end = match self.peek() {
+			Some(c) if c == '-' || c.is_ascii_digit() => Some(self.parse_int()?),
+			_ => None
+		};
+		self.skip_blank();
+
+		let mut step = 1;
+		if self.eat(':') {
+			self.skip_blank();
+			if let Some(c) = self.peek() {
+				if c == '-' || c.is_ascii_digit() {
+					step = self.parse_int()?;
+				}
+			}
+		}
+
+		return Ok(Selector::Slice(start, end, step));
+	}
+
+	//Single or double quoted string with JSON style escapes
+	fn parse_string(&mut self) -> Result<String, QueryError> {
+		let quote = match self.peek() {
+			Some(c) if c == '\'' || c == '"' => c,
+			_ => return Err(self.error("expected a string"))
+		};
+		self.pos += 1;
+
+		let mut to_return = String::new();
+		loop {
+			let c = match self.peek() {
+				Some(c) => c,
+				None => return Err(self.error("unterminated string"))
+			};
+			self.pos += 1;
+
+			if c == quote {
+				return Ok(to_return);
+			}
+
+			if (c as u32) < 0x20 {
+				return Err(self.error("control characters must be escaped"));
+			}
+
+			if c != '\\' {
+				to_return.push(c);
+				continue;
+			}
+
+			let escaped = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+			self.pos += 1;
+
+			match escaped {
+				'b' => to_return.push('\u{8}'),
+				'f' => to_return.push('\u{c}'),
+				'n' => to_return.push('\n'),
+				'r' => to_return.push('\r'),
+				't' => to_return.push('\t'),
+				'/' => to_return.push('/'),
+				'\\' => to_return.push('\\'),
+				'u' => to_return.push(self.parse_unicode_escape()?),
+				c if c == quote => to_return.push(c),
+				_ => return Err(self.error("invalid escape"))
+			}
+		}
+	}
+
+	fn parse_hex4(&mut self) -> Result<u32, QueryError> {
+		if self.pos + 4 > self.chars.len() {
+			return Err(self.error("invalid \\u escape"));
+		}
+
+		let hex: String = self.chars[self.pos..self.pos + 4].iter().collect();
+		let code = u32::from_str_radix(&hex, 16).map_err(|_| self.error("invalid \\u escape"))?;
+		self.pos += 4;
+		return Ok(code);
+	}
+
+	//\uXXXX, including surrogate pairs
+	fn parse_unicode_escape(&mut self) -> Result<char, QueryError> {
+		let high = self.parse_hex4()?;
+
+		if (0xD800..0xDC00).contains(&high) {
+			if !self.eat_str("\\u") {
+				return Err(self.error("unpaired surrogate"));
+			}
+			let low = self.parse_hex4()?;
+			if !(0xDC00..0xE000).contains(&low) {
+				return Err(self.error("unpaired surrogate"));
+			}
+
+			let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+			return std::char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"));
+		}
+
+		return std::char::from_u32(high).ok_or_else(|| self.error("unpaired surrogate"));
+	}
+
+	fn parse_or(&mut self) -> Result<Expr, QueryError> {
+		let mut items = vec![self.parse_and()?];
+
+		loop {
+			let start = self.pos;
+			self.skip_blank();
+			if !self.eat_str("||") {
+				self.pos = start;
+				break;
+			}
+			self.skip_blank();
+			items.push(self.parse_and()?);
+		}
+
+		if items.len() == 1 {
+			return Ok(items.pop().unwrap());
+		}
+		return Ok(Expr::Or(items));
+	}
+
+	fn parse_and(&mut self) -> Result<Expr, QueryError> {
+		let mut items = vec![self.parse_basic()?];
+
+		loop {
+			let start = self.pos;
+			self.skip_blank();
+			if !self.eat_str("&&") {
+				self.pos = start;
+				break;
+			}
+			self.skip_blank();
+			items.push(self.parse_basic()?);
+		}
+
+		if items.len() == 1 {
+			return Ok(items.pop().unwrap());
+		}
+		return Ok(Expr::And(items));
+	}
+
+	//Parenthesized expression, comparison or test
+	fn parse_basic(&mut self) -> Result<Expr, QueryError> {
+		if self.eat('!') {
+			self.skip_blank();
+
+			if self.peek() == Some('(') {
+				return Ok(Expr::Not(Box::new(self.parse_paren()?)));
+			}
+
+			let start = self.pos;
+			let operand = self.parse_comparable()?;
+			return Ok(Expr::Not(Box::new(self.test_expr(operand, start)?)));
+		}
+
+		if self.peek() == Some('(') {
+			return self.parse_paren();
+		}
+
+		let start = self.pos;
+		let left = self.parse_comparable()?;
+
+		let before_op = self.pos;
+		self.skip_blank();
+		match self.parse_op() {
+			Some(op) => {
+				self.skip_blank();
+				let right_start = self.pos;
+				let right = self.parse_comparable()?;
+
+				let left = self.check_comparable(left, start)?;
+				let right = self.check_comparable(right, right_start)?;
+				Ok(Expr::Compare(left, op, right))
+			}
+			None => {
+				self.pos = before_op;
+				self.test_expr(left, start)
+			}
+		}
+	}
+
+	fn parse_paren(&mut self) -> Result<Expr, QueryError> {
+		self.expect('(')?;
+		self.skip_blank();
+		let inner = self.parse_or()?;
+		self.skip_blank();
+		self.expect(')')?;
+		return Ok(inner);
+	}
+
+	fn parse_op(&mut self) -> Option<Op> {
+		let ops = [
+			("==", Op::Equal), ("!=", Op::NotEqual), ("<=", Op::LessEqual),
+			(">=", Op::GreaterEqual), ("<", Op::Less), (">", Op::Greater)
+		];
+
+		for (text, op) in ops.iter() {
+			if self.eat_str(text) {
+				return Some(*op);
+			}
+		}
+		return None;
+	}
+
+	//Turn an operand that isn't compared into a test expression
+	fn test_expr(&self, operand: Comparable, start: usize) -> Result<Expr, QueryError> {
+		match operand {
+			Comparable::Query(x) => Ok(Expr::Exists(x)),
+			Comparable::Function(x) => {
+				if x.name.signature().1 == Kind::Value {
+					return Err(QueryError { position: start, message: String::from("function result must be compared") });
+				}
+				Ok(Expr::Function(x))
+			}
+			Comparable::Literal(_) => Err(QueryError { position: start, message: String::from("literal must be compared") })
+		}
+	}
+
+	//Comparisons need singular queries and functions that return values
+	fn check_comparable(&self, operand: Comparable, start: usize) -> Result<Comparable, QueryError> {
+		match operand {
+			Comparable::Query(ref x) if !x.is_singular() => {
+				Err(QueryError { position: start, message: String::from("only singular queries can be compared") })
+			}
+			Comparable::Function(ref x) if x.name.signature().1 != Kind::Value => {
+				Err(QueryError { position: start, message: String::from("function result can't be compared") })
+			}
+			_ => Ok(operand)
+		}
+	}
+
+	//Literal, filter query or function call
+	fn parse_comparable(&mut self) -> Result<Comparable, QueryError> {
+		match self.peek() {
+			Some('@') | Some('$') => {
+				let relative = self.peek() == Some('@');
+				self.pos += 1;
+				let segments = self.parse_segments()?;
+				Ok(Comparable::Query(FilterQuery { relative, segments }))
+			}
+			Some('\'') | Some('"') => Ok(Comparable::Literal(Value::Text(self.parse_string()?))),
+			Some(c) if c == '-' || c.is_ascii_digit() => Ok(Comparable::Literal(self.parse_number()?)),
+			Some(c) if c.is_ascii_lowercase() => {
+				if self.eat_keyword("true") {
+					return Ok(Comparable::Literal(Value::Bool(true)));
+				}
+				if self.eat_keyword("false") {
+					return Ok(Comparable::Literal(Value::Bool(false)));
+				}
+				if self.eat_keyword("null") {
+					return Ok(Comparable::Literal(Value::Null));
+				}
+				Ok(Comparable::Function(self.parse_function()?))
+			}
+			_ => Err(self.error("expected an expression"))
+		}
+	}
+
+	//Keywords can't run into a longer name
+	fn eat_keyword(&mut self, keyword: &str) -> bool {
+		let start = self.pos;
+		if !self.eat_str(keyword) {
+			return false;
+		}
+
+		if self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+			self.pos = start;
+			return false;
+		}
+		return true;
+	}
+
+	fn parse_number(&mut self) -> Result<Value, QueryError> {
+		let start = self.pos;
+		self.eat('-');
+
+		let int_start = self.pos;
+		while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+			self.pos += 1;
+		}
+		let int_len = self.pos - int_start;
+		if int_len == 0 || (int_len > 1 && self.chars[int_start] == '0') {
+			self.pos = start;
+			return Err(self.error("invalid number"));
+		}
+
+		if self.peek() == Some('.') {
+			self.pos += 1;
+			let frac_start = self.pos;
+			while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+				self.pos += 1;
+			}
+			if self.pos == frac_start {
+				return Err(self.error("invalid number"));
+			}
+		}
+
+		if self.peek() == Some('e') || self.peek() == Some('E') {
+			self.pos += 1;
+			if !self.eat('+') {
+				self.eat('-');
+			}
+			let exp_start = self.pos;
+			while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+				self.pos += 1;
+			}
+			if self.pos == exp_start {
+				return Err(self.error("invalid number"));
+			}
+		}
+
+		let text: String = self.chars[start..self.pos].iter().collect();
+		return text.parse::<f64>().map(Value::Number).map_err(|_| self.error("invalid number"));
+	}
+
+	fn parse_function(&mut self) -> Result<Function, QueryError> {
+		let start = self.pos;
+		while self.peek().is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+			self.pos += 1;
+		}
+
+		let text: String = self.chars[start..self.pos].iter().collect();
+		let name = match FunctionName::from_name(&text) {
+			Some(x) => x,
+			None => {
+				self.pos = start;
+				return Err(self.error(&format!("unknown function {}", text)));
+			}
+		};
+
+		self.expect('(')?;
+		let (params, _) = name.signature();
+		let mut args: Vec<Argument> = Vec::new();
+
+		self.skip_blank();
+		if self.peek() != Some(')') {
+			loop {
+				let arg_start = self.pos;
+				let kind = *params.get(args.len()).ok_or_else(|| self.error("too many arguments"))?;
+				let arg = self.parse_argument()?;
+				args.push(self.check_argument(arg, kind, arg_start)?);
+
+				self.skip_blank();
+				if !self.eat(',') {
+					break;
+				}
+				self.skip_blank();
+			}
+		}
+		self.expect(')')?;
+
+		if args.len() != params.len() {
+			return Err(QueryError { position: start, message: format!("{} takes {} arguments", text, params.len()) });
+		}
+
+		//Precompile literal patterns
+		let mut regex = None;
+		if name == FunctionName::Match || name == FunctionName::Search {
+			if let Argument::Literal(Value::Text(ref pattern)) = args[1] {
+				regex = Regex::new(pattern).ok();
+			}
+		}
+
+		return Ok(Function { name, args, regex });
+	}
+
+	fn parse_argument(&mut self) -> Result<Argument, QueryError> {
+		//None of the standard functions take a logical expression
+		if self.peek() == Some('!') || self.peek() == Some('(') {
+			return Err(self.error("argument has the wrong type"));
+		}
+
+		//A lone operand, unless it turns out to be the start of a logical expression
+		let start = self.pos;
+		let operand = self.parse_comparable()?;
+
+		let after = self.pos;
+		self.skip_blank();
+		let is_logical = self.parse_op().is_some() || self.eat_str("&&") || self.eat_str("||");
+		if is_logical {
+			return Err(QueryError { position: start, message: String::from("argument has the wrong type") });
+		}
+		self.pos = after;
+
+		match operand {
+			Comparable::Literal(x) => Ok(Argument::Literal(x)),
+			Comparable::Query(x) => Ok(Argument::Query(x)),
+			Comparable::Function(x) => Ok(Argument::Function(x))
+		}
+	}
+
+	//Check an argument against the parameter type of a function
+	fn check_argument(&self, arg: Argument, kind: Kind, start: usize) -> Result<Argument, QueryError> {
+		let ok = match (&arg, kind) {
+			(Argument::Literal(_), Kind::Value) => true,
+			(Argument::Query(x), Kind::Value) => x.is_singular(),
+			(Argument::Query(_), Kind::Nodes) => true,
+			(Argument::Query(_), Kind::Logical) => true,
+			(Argument::Function(x), _) => {
+				let result = x.name.signature().1;
+				result == kind || (kind == Kind::Logical && result == Kind::Nodes)
+			}
+			_ => false
+		};
+
+		if !ok {
+			return Err(QueryError { position: start, message: String::from("argument has the wrong type") });
+		}
+		return Ok(arg);
+	}
+}
+
+//Result of evaluating a comparable or value argument: nothing, a node, or a computed value
+enum Operand<'a> {
+	Nothing,
+	Node(&'a Value),
+	Computed(Value)
+}
+
+impl<'a> Operand<'a> {
+	fn get(&self) -> Option<&Value> {
+		match self {
+			Operand::Nothing => None,
+			Operand::Node(x) => Some(x),
+			Operand::Computed(x) => Some(x)
+		}
+	}
+}
+
+fn apply_segments<'a>(segments: &[Segment], nodes: Vec<&'a Value>, root: &'a Value) -> Vec<&'a Value> {
+	let mut nodes = nodes;
+
+	for segment in segments {
+		let mut next: Vec<&'a Value> = Vec::new();
+
+		for node in nodes {
+			match segment {
+				Segment::Child(selectors) => {
+					for selector in selectors {
+						apply_selector(selector, node, root, &mut next);
+					}
+				}
+				Segment::Descendant(selectors) => {
+					let mut descendants: Vec<&'a Value> = Vec::new();
+					collect_descendants(node, &mut descendants);
+
+					for descendant in descendants {
+						for selector in selectors {
+							apply_selector(selector, descendant, root, &mut next);
+						}
+					}
+				}
+			}
+		}
+
+		nodes = next;
+	}
+
+	return nodes;
+}
+
+//A node followed by all of its descendants, parents before children
+fn collect_descendants<'a>(node: &'a Value, out: &mut Vec<&'a Value>) {
+	out.push(node);
+
+	match node {
+		Value::Array(x) => {
+			for item in x {
+				collect_descendants(item, out);
+			}
+		}
+		Value::Object(x) => {
+			for item in x.values() {
+				collect_descendants(item, out);
+			}
+		}
+		_ => ()
+	}
+}
+
+//Turn a possibly negative index into a position in an array of len elements
+fn normalize(index: i64, len: i64) -> i64 {
+	if index >= 0 { index } else { len + index }
+}
+
+fn apply_selector<'a>(selector: &Selector, node: &'a Value, root: &'a Value, out: &mut Vec<&'a Value>) {
+	match (selector, node) {
+		(Selector::Name(name), Value::Object(x)) => {
+			if let Some(x) = x.get(name) {
+				out.push(x);
+			}
+		}
+		(Selector::Wildcard, Value::Object(x)) => out.extend(x.values()),
+		(Selector::Wildcard, Value::Array(x)) => out.extend(x.iter()),
+		(Selector::Index(index), Value::Array(x)) => {
+			let i = normalize(*index, x.len() as i64);
+			if i >= 0 && i < x.len() as i64 {
+				out.push(&x[i as usize]);
+			}
+		}
+		(Selector::Slice(start, end, step), Value::Array(x)) => {
+			let len = x.len() as i64;
+			let step = *step;
+			if step == 0 {
+				return;
+			}
+
+			if step > 0 {
+				let lower = normalize(start.unwrap_or(0), len).clamp(0, len);
+				let upper = normalize(end.unwrap_or(len), len).clamp(0, len);
+
+				let mut i = lower;
+				while i < upper {
+					out.push(&x[i as usize]);
+					i += step;
+				}
+			} else {
+				let upper = normalize(start.unwrap_or(len - 1), len).clamp(-1, len - 1);
+				let lower = match end {
+					Some(e) => normalize(*e, len).clamp(-1, len - 1),
+					None => -1
+				};
+
+				let mut i = upper;
+				while lower < i {
+					out.push(&x[i as usize]);
+					i += step;
+				}
+			}
+		}
+		(Selector::Filter(expr), Value::Array(x)) => {
+			for item in x {
+				if eval_expr(expr, item, root) {
+					out.push(item);
+				}
+			}
+		}
+		(Selector::Filter(expr), Value::Object(x)) => {
+			for item in x.values() {
+				if eval_expr(expr, item, root) {
+					out.push(item);
+				}
+			}
+		}
+		_ => ()
+	}
+}
+
+fn eval_query<'a>(query: &FilterQuery, current: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+	let start = if query.relative { current } else { root };
+	return apply_segments(&query.segments, vec![start], root);
+}
+
+fn eval_expr(expr: &Expr, current: &Value, root: &Value) -> bool {
+	match expr {
+		Expr::Or(x) => x.iter().any(|e| eval_expr(e, current, root)),
+		Expr::And(x) => x.iter().all(|e| eval_expr(e, current, root)),
+		Expr::Not(x) => !eval_expr(x, current, root),
+		Expr::Exists(x) => !eval_query(x, current, root).is_empty(),
+		Expr::Function(x) => eval_logical_function(x, current, root),
+		Expr::Compare(left, op, right) => {
+			let left = eval_comparable(left, current, root);
+			let right = eval_comparable(right, current, root);
+			compare(left.get(), *op, right.get())
+		}
+	}
+}
+
+fn eval_comparable<'a>(operand: &Comparable, current: &'a Value, root: &'a Value) -> Operand<'a> {
+	match operand {
+		Comparable::Literal(x) => Operand::Computed(x.clone()),
+		Comparable::Query(x) => match eval_query(x, current, root).first() {
+			Some(x) => Operand::Node(x),
+			None => Operand::Nothing
+		},
+		Comparable::Function(x) => eval_value_function(x, current, root)
+	}
+}
+
+fn equal(left: Option<&Value>, right: Option<&Value>) -> bool {
+	return left == right;
+}
+
+fn less(left: Option<&Value>, right: Option<&Value>) -> bool {
+	match (left, right) {
+		(Some(Value::Number(a)), Some(Value::Number(b))) => a < b,
+		(Some(Value::Text(a)), Some(Value::Text(b))) => a < b,
+		_ => false
+	}
+}
+
+fn compare(left: Option<&Value>, op: Op, right: Option<&Value>) -> bool {
+	match op {
+		Op::Equal => equal(left, right),
+		Op::NotEqual => !equal(left, right),
+		Op::Less => less(left, right),
+		Op::LessEqual => less(left, right) || equal(left, right),
+		Op::Greater => less(right, left),
+		Op::GreaterEqual => less(right, left) || equal(left, right)
+	}
+}
+
+fn eval_value_argument<'a>(arg: &Argument, current: &'a Value, root: &'a Value) -> Operand<'a> {
+	match arg {
+		Argument::Literal(x) => Operand::Computed(x.clone()),
+		Argument::Query(x) => match eval_query(x, current, root).first() {
+			Some(x) => Operand::Node(x),
+			None => Operand::Nothing
+		},
+		Argument::Function(x) => eval_value_function(x, current, root)
+	}
+}
+
+fn eval_nodes_argument<'a>(arg: &Argument, current: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+	match arg {
+		Argument::Query(x) => eval_query(x, current, root),
+		_ => Vec::new()
+	}
+}
+
+fn eval_value_function<'a>(function: &Function, current: &'a Value, root: &'a Value) -> Operand<'a> {
+	match function.name {
+		FunctionName::Length => {
+			let arg = eval_value_argument(&function.args[0], current, root);
+			match arg.get() {
+				Some(Value::Text(x)) => Operand::Computed(Value::from(x.chars().count())),
+				Some(Value::Array(x)) => Operand::Computed(Value::from(x.len())),
+				Some(Value::Object(x)) => Operand::Computed(Value::from(x.len())),
+				_ => Operand::Nothing
+			}
+		}
+		FunctionName::Count => {
+			let nodes = eval_nodes_argument(&function.args[0], current, root);
+			Operand::Computed(Value::from(nodes.len()))
+		}
+		FunctionName::Value => {
+			let nodes = eval_nodes_argument(&function.args[0], current, root);
+			if nodes.len() == 1 { Operand::Node(nodes[0]) } else { Operand::Nothing }
+		}
+		FunctionName::Match | FunctionName::Search => Operand::Nothing
+	}
+}
+
+fn eval_logical_function(function: &Function, current: &Value, root: &Value) -> bool {
+	match function.name {
+		FunctionName::Match | FunctionName::Search => {
+			let text = eval_value_argument(&function.args[0], current, root);
+			let pattern = eval_value_argument(&function.args[1], current, root);
+
+			let text = match text.get() {
+				Some(Value::Text(x)) => x,
+				_ => return false
+			};
+
+			//Patterns that aren't literals are compiled for each node
+			let compiled;
+			let regex = match (&function.regex, pattern.get()) {
+				(Some(x), _) => x,
+				(None, Some(Value::Text(x))) => match Regex::new(x) {
+					Ok(x) => {
+						compiled = x;
+						&compiled
+					}
+					Err(_) => return false
+				},
+				_ => return false
+			};
+
+			if function.name == FunctionName::Match { regex.is_match(text) } else { regex.search(text) }
+		}
+		_ => false
+	}
+}
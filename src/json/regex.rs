@@ -0,0 +1,508 @@
+//A small regular expression engine for JSONPath match()/search() and JSON Schema "pattern".
+//It covers I-Regexp (RFC 9485) plus the common ECMA-262 extras: ^ and $ anchors, \d \w \s classes
+//and (?:...) groups. Patterns are compiled to a program and run on a Pike VM, so matching time is
+//linear in the length of the text.
+
+use std::error::Error;
+use std::fmt;
+
+//Error returned when a pattern can't be compiled
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegexError {
+	pub position: usize,
+	pub message: String
+}
+
+impl fmt::Display for RegexError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invalid regex at {}: {}", self.position, self.message)
+	}
+}
+
+impl Error for RegexError {}
+
+//Largest count allowed in {n,m}, to keep compiled programs small
+const MAX_REPEAT: u32 = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+enum ClassItem {
+	Range(char, char),
+	Digit(bool),
+	Word(bool),
+	Space(bool),
+	Property(String, bool)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Class {
+	negated: bool,
+	items: Vec<ClassItem>
+}
+
+//Check a character against a \p{...} unicode category. Categories are approximated with std's char tests.
+fn has_property(name: &str, c: char) -> bool {
+	match name {
+		"L" => c.is_alphabetic(),
+		"Lu" => c.is_uppercase(),
+		"Ll" => c.is_lowercase(),
+		"N" | "Nd" => c.is_numeric(),
+		"P" => c.is_ascii_punctuation(),
+		"Z" | "Zs" => c.is_whitespace() && !c.is_control(),
+		"Cc" => c.is_control(),
+		_ => false
+	}
+}
+
+const PROPERTIES: [&str; 9] = ["L", "Lu", "Ll", "N", "Nd", "P", "Z", "Zs", "Cc"];
+
+impl ClassItem {
+	fn matches(&self, c: char) -> bool {
+		match self {
+			ClassItem::Range(a, b) => *a <= c && c <= *b,
+			ClassItem::Digit(negated) => c.is_ascii_digit() != *negated,
+			ClassItem::Word(negated) => (c.is_ascii_alphanumeric() || c == '_') != *negated,
+			ClassItem::Space(negated) => c.is_whitespace() != *negated,
+			ClassItem::Property(name, negated) => has_property(name, c) != *negated
+		}
+	}
+}
+
+impl Class {
+	fn matches(&self, c: char) -> bool {
+		return self.items.iter().any(|x| x.matches(c)) != self.negated;
+	}
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+	Empty,
+	Char(char),
+	Any,
+	Class(Class),
+	Start,
+	End,
+	Concat(Vec<Node>),
+	Alternate(Vec<Node>),
+	Repeat(Box<Node>, u32, Option<u32>)
+}
+
+struct Parser<'a> {
+	chars: &'a [char],
+	pos: usize
+}
+
+impl<'a> Parser<'a> {
+	fn error(&self, message: &str) -> RegexError {
+		return RegexError {
+			position: self.pos,
+			message: message.to_string()
+		};
+	}
+
+	fn peek(&self) -> Option<char> {
+		return self.chars.get(self.pos).cloned();
+	}
+
+	fn next(&mut self) -> Option<char> {
+		let c = self.peek();
+		if c.is_some() {
+			self.pos += 1;
+		}
+		return c;
+	}
+
+	fn eat(&mut self, c: char) -> bool {
+		if self.peek() == Some(c) {
+			self.pos += 1;
+			return true;
+		}
+		return false;
+	}
+
+	fn parse_alternate(&mut self) -> Result<Node, RegexError> {
+		let mut branches = vec![self.parse_concat()?];
+		while self.eat('|') {
+			branches.push(self.parse_concat()?);
+		}
+
+		if branches.len() == 1 {
+			return Ok(branches.pop().unwrap());
+		}
+		return Ok(Node::Alternate(branches));
+	}
+
+	fn parse_concat(&mut self) -> Result<Node, RegexError> {
+		let mut items: Vec<Node> = Vec::new();
+
+		while let Some(c) = self.peek() {
+			if c == '|' || c == ')' {
+				break;
+			}
+
+			let atom = self.parse_atom()?;
+			items.push(self.parse_quantifiers(atom)?);
+		}
+
+		match items.len() {
+			0 => Ok(Node::Empty),
+			1 => Ok(items.pop().unwrap()),
+			_ => Ok(Node::Concat(items))
+		}
+	}
+
+	fn parse_number(&mut self) -> Option<u32> {
+		let start = self.pos;
+		while let Some(c) = self.peek() {
+			if !c.is_ascii_digit() {
+				break;
+			}
+			self.pos += 1;
+		}
+
+		let text: String = self.chars[start..self.pos].iter().collect();
+		return text.parse().ok();
+	}
+
+	fn parse_quantifiers(&mut self, atom: Node) -> Result<Node, RegexError> {
+		let mut node = atom;
+
+		loop {
+			let (min, max) = match self.peek() {
+				Some('*') => (0, None),
+				Some('+') => (1, None),
+				Some('?') => (0, Some(1)),
+				Some('{') => {
+					self.pos += 1;
+					let min = self.parse_number().ok_or_else(|| self.error("expected a number in {}"))?;
+					let max = if self.eat(',') {
+						if self.peek() == Some('}') { None } else { Some(self.parse_number().ok_or_else(|| self.error("expected a number in {}"))?) }
+					} else {
+						Some(min)
+					};
+
+					if self.peek() != Some('}') {
+						return Err(self.error("expected }"));
+					}
+					if min > MAX_REPEAT || max.is_some_and(|x| x > MAX_REPEAT || x < min) {
+						return Err(self.error("invalid repetition count"));
+					}
+					(min, max)
+				}
+				_ => return Ok(node)
+			};
+			self.pos += 1;
+
+			//Lazy quantifiers match the same strings, so the marker is ignored
+			self.eat('?');
+			node = Node::Repeat(Box::new(node), min, max);
+		}
+	}
+
+	fn parse_atom(&mut self) -> Result<Node, RegexError> {
+		let c = self.next().ok_or_else(|| self.error("unexpected end of pattern"))?;
+
+		match c {
+			'(' => {
+				if self.eat('?') && !self.eat(':') {
+					return Err(self.error("only (?:...) groups are supported"));
+				}
+
+				let inner = self.parse_alternate()?;
+				if !self.eat(')') {
+					return Err(self.error("expected )"));
+				}
+				Ok(inner)
+			}
+			'[' => self.parse_class(),
+			'.' => Ok(Node::Any),
+			'^' => Ok(Node::Start),
+			'$' => Ok(Node::End),
+			'\\' => self.parse_escape(false).map(|x| match x {
+				ClassItem::Range(a, b) if a == b => Node::Char(a),
+				x => Node::Class(Class { negated: false, items: vec![x] })
+			}),
+			'*' | '+' | '?' | '{' => Err(self.error("nothing to repeat")),
+			')' | ']' | '}' => Err(self.error("unbalanced bracket")),
+			_ => Ok(Node::Char(c))
+		}
+	}
+
+	//Parse what follows a backslash. Single characters come back as a one character range.
+	fn parse_escape(&mut self, in_class: bool) -> Result<ClassItem, RegexError> {
+		let c = self.next().ok_or_else(|| self.error("unexpected end of pattern"))?;
+
+		let item = match c {
+			'd' => ClassItem::Digit(false),
+			'D' => ClassItem::Digit(true),
+			'w' => ClassItem::Word(false),
+			'W' => ClassItem::Word(true),
+			's' => ClassItem::Space(false),
+			'S' => ClassItem::Space(true),
+			'n' => ClassItem::Range('\n', '\n'),
+			'r' => ClassItem::Range('\r', '\r'),
+			't' => ClassItem::Range('\t', '\t'),
+			'f' => ClassItem::Range('\u{c}', '\u{c}'),
+			'v' => ClassItem::Range('\u{b}', '\u{b}'),
+			'b' if in_class => ClassItem::Range('\u{8}', '\u{8}'),
+			'p' | 'P' => {
+				if !self.eat('{') {
+					return Err(self.error("expected { after \\p"));
+				}
+				let start = self.pos;
+				while self.peek().is_some_and(|x| x != '}') {
+					self.pos += 1;
+				}
+				let name: String = self.chars[start..self.pos].iter().collect();
+				if !self.eat('}') || !PROPERTIES.contains(&name.as_str()) {
+					return Err(self.error("unsupported unicode property"));
+				}
+				ClassItem::Property(name, c == 'P')
+			}
+			'u' => {
+				let start = self.pos;
+				self.pos = (self.pos + 4).min(self.chars.len());
+				let hex: String = self.chars[start..self.pos].iter().collect();
+				let code = u32::from_str_radix(&hex, 16).ok().and_then(std::char::from_u32);
+				let code = code.ok_or_else(|| self.error("invalid \\u escape"))?;
+				ClassItem::Range(code, code)
+			}
+			c if c.is_ascii_alphanumeric() => return Err(self.error("unsupported escape")),
+			c => ClassItem::Range(c, c)
+		};
+
+		return Ok(item);
+	}
+
+	fn parse_class(&mut self) -> Result<Node, RegexError> {
+		let negated = self.eat('^');
+		let mut items: Vec<ClassItem> = Vec::new();
+
+		//A ] right at the start is a literal
+		if self.eat(']') {
+			items.push(ClassItem::Range(']', ']'));
+		}
+
+		loop {
+			let c = self.next().ok_or_else(|| self.error("unterminated character class"))?;
+			if c == ']' {
+				break;
+			}
+
+			let item = if c == '\\' { self.parse_escape(true)? } else { ClassItem::Range(c, c) };
+
+			//Ranges like a-z
+			if let ClassItem::Range(start, _) = item {
+				if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|x| *x != ']') {
+					self.pos += 1;
+					let end = match self.next() {
+						Some('\\') => self.parse_escape(true)?,
+						Some(x) => ClassItem::Range(x, x),
+						None => return Err(self.error("unterminated character class"))
+					};
+
+					match end {
+						ClassItem::Range(end, _) if end >= start => items.push(ClassItem::Range(start, end)),
+						_ => return Err(self.error("invalid character range"))
+					}
+					continue;
+				}
+			}
+
+			items.push(item);
+		}
+
+		return Ok(Node::Class(Class { negated, items }));
+	}
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+	Char(char),
+	Any,
+	Class(Class),
+	Start,
+	End,
+	Split(usize, usize),
+	Jump(usize),
+	Match
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) {
+	match node {
+		Node::Empty => (),
+		Node::Char(c) => program.push(Inst::Char(*c)),
+		Node::Any => program.push(Inst::Any),
+		Node::Class(x) => program.push(Inst::Class(x.clone())),
+		Node::Start => program.push(Inst::Start),
+		Node::End => program.push(Inst::End),
+		Node::Concat(x) => {
+			for item in x {
+				compile(item, program);
+			}
+		}
+		Node::Alternate(x) => {
+			//split L1, next; L1: branch; jump end; next: split ...
+			let mut jumps: Vec<usize> = Vec::new();
+			for (i, branch) in x.iter().enumerate() {
+				if i + 1 < x.len() {
+					let split = program.len();
+					program.push(Inst::Split(split + 1, 0));
+					compile(branch, program);
+					jumps.push(program.len());
+					program.push(Inst::Jump(0));
+					let next = program.len();
+					program[split] = Inst::Split(split + 1, next);
+				} else {
+					compile(branch, program);
+				}
+			}
+
+			let end = program.len();
+			for jump in jumps {
+				program[jump] = Inst::Jump(end);
+			}
+		}
+		Node::Repeat(inner, min, max) => {
+			for _ in 0..*min {
+				compile(inner, program);
+			}
+
+			match max {
+				//loop: split body, end; body; jump loop
+				None => {
+					let split = program.len();
+					program.push(Inst::Split(split + 1, 0));
+					compile(inner, program);
+					program.push(Inst::Jump(split));
+					let end = program.len();
+					program[split] = Inst::Split(split + 1, end);
+				}
+				//Each optional copy can skip straight to the end
+				Some(max) => {
+					let mut splits: Vec<usize> = Vec::new();
+					for _ in *min..*max {
+						splits.push(program.len());
+						program.push(Inst::Split(program.len() + 1, 0));
+						compile(inner, program);
+					}
+
+					let end = program.len();
+					for split in splits {
+						program[split] = Inst::Split(split + 1, end);
+					}
+				}
+			}
+		}
+	}
+}
+
+//A compiled regular expression
+#[derive(Debug, Clone)]
+pub struct Regex {
+	program: Vec<Inst>
+}
+
+impl Regex {
+	pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+		let chars: Vec<char> = pattern.chars().collect();
+		let mut parser = Parser { chars: &chars, pos: 0 };
+
+		let node = parser.parse_alternate()?;
+		if parser.pos != chars.len() {
+			return Err(parser.error("unbalanced )"));
+		}
+
+		let mut program: Vec<Inst> = Vec::new();
+		compile(&node, &mut program);
+		program.push(Inst::Match);
+
+		return Ok(Regex { program });
+	}
+
+	//Check if the whole text matches
+	pub fn is_match(&self, text: &str) -> bool {
+		return self.run(text, true);
+	}
+
+	//Check if any part of the text matches
+	pub fn search(&self, text: &str) -> bool {
+		return self.run(text, false);
+	}
+
+	//Add a thread and follow its jumps, splits and assertions
+	fn add_thread(&self, list: &mut Vec<usize>, seen: &mut [usize], generation: usize, pc: usize, pos: usize, len: usize) {
+		if seen[pc] == generation {
+			return;
+		}
+		seen[pc] = generation;
+
+		match self.program[pc] {
+			Inst::Jump(x) => self.add_thread(list, seen, generation, x, pos, len),
+			Inst::Split(a, b) => {
+				self.add_thread(list, seen, generation, a, pos, len);
+				self.add_thread(list, seen, generation, b, pos, len);
+			}
+			Inst::Start => {
+				if pos == 0 {
+					self.add_thread(list, seen, generation, pc + 1, pos, len);
+				}
+			}
+			Inst::End => {
+				if pos == len {
+					self.add_thread(list, seen, generation, pc + 1, pos, len);
+				}
+			}
+			_ => list.push(pc)
+		}
+	}
+
+	fn run(&self, text: &str, full: bool) -> bool {
+		let chars: Vec<char> = text.chars().collect();
+		let len = chars.len();
+
+		let mut current: Vec<usize> = Vec::new();
+		let mut next: Vec<usize> = Vec::new();
+		let mut seen = vec![usize::MAX; self.program.len()];
+
+		self.add_thread(&mut current, &mut seen, 0, 0, 0, len);
+
+		for pos in 0..=len {
+			let generation = pos + 1;
+			//pos runs one past the last character, where there's nothing left to match
+			let current_char = chars.get(pos).copied();
+
+			for &pc in &current {
+				let advance = match self.program[pc] {
+					Inst::Match => {
+						if !full || pos == len {
+							return true;
+						}
+						false
+					}
+					Inst::Char(c) => current_char == Some(c),
+					Inst::Any => current_char.is_some_and(|x| x != '\n' && x != '\r'),
+					Inst::Class(ref x) => current_char.is_some_and(|c| x.matches(c)),
+					_ => false
+				};
+
+				if advance {
+					self.add_thread(&mut next, &mut seen, generation, pc + 1, pos + 1, len);
+				}
+			}
+
+			//Searching can start a new attempt at every position
+			if !full && pos < len {
+				self.add_thread(&mut next, &mut seen, generation, 0, pos + 1, len);
+			}
+
+			std::mem::swap(&mut current, &mut next);
+			next.clear();
+
+			if full && current.is_empty() {
+				return false;
+			}
+		}
+
+		return false;
+	}
+}
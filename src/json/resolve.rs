@@ -0,0 +1,163 @@
+//Inline {"$ref": "file.json#/pointer"} references in documents split across files.
+//
+//A reference is an object with a "$ref" member. It is replaced by the value it points to: the part
+//before # names a file relative to the file holding the reference (empty means the same file), and
+//the part after it is a JSON Pointer into that file (empty means the whole file). Other members next
+//to "$ref" are dropped.
+
+use crate::json::Value;
+use crate::json::parsing::{self, ParseError, ParseOptions};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+//Error returned when a reference can't be resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveError {
+	//References being followed when the error happened, outermost first, as "file#pointer"
+	pub chain: Vec<String>,
+	pub message: String,
+	//Where a referenced file stops being valid JSON, when that is the problem
+	pub parse: Option<ParseError>
+}
+
+impl fmt::Display for ResolveError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.chain.is_empty() {
+			return write!(f, "{}", self.message);
+		}
+		write!(f, "{} (while resolving {})", self.message, self.chain.join(" -> "))
+	}
+}
+
+impl Error for ResolveError {}
+
+//Loads and caches files while resolving references. Reuse one Resolver to share the cache.
+#[derive(Debug, Default)]
+pub struct Resolver {
+	cache: HashMap<PathBuf, Value>
+}
+
+impl Resolver {
+	pub fn new() -> Resolver {
+		return Resolver::default();
+	}
+
+	//Load a file and inline every reference in it
+	pub fn resolve_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Value, ResolveError> {
+		let path = canonical(path.as_ref(), &[])?;
+		let document = self.load(&path, &[])?;
+		return self.inline(&document, &path, None, &mut Vec::new());
+	}
+
+	//Inline every reference in a value, resolving relative file names against base (the file the
+	//value came from). References within the same document ("#/pointer") are read from value, not
+	//from base on disk.
+	pub fn resolve<P: AsRef<Path>>(&mut self, value: &Value, base: P) -> Result<Value, ResolveError> {
+		return self.inline(value, base.as_ref(), Some(value), &mut Vec::new());
+	}
+
+	//Parse a file, or return the copy loaded earlier
+	fn load(&mut self, path: &Path, chain: &[String]) -> Result<Value, ResolveError> {
+		if let Some(x) = self.cache.get(path) {
+			return Ok(x.clone());
+		}
+
+		let bytes = fs::read(path).map_err(|e| failure(chain, format!("can't read {}: {}", path.display(), e)))?;
+		let document = parsing::parse_bytes(&bytes, &ParseOptions::default()).map_err(|e| ResolveError {
+			chain: chain.to_vec(),
+			message: format!("{} isn't valid JSON: {}", path.display(), e),
+			parse: Some(e)
+		})?;
+
+		self.cache.insert(path.to_path_buf(), document.clone());
+		return Ok(document);
+	}
+
+	//root is the document in file when it didn't come from disk
+	fn inline(&mut self, value: &Value, file: &Path, root: Option<&Value>, chain: &mut Vec<String>) -> Result<Value, ResolveError> {
+		match value {
+			Value::Object(x) => {
+				if let Some(reference) = x.get("$ref") {
+					return self.follow(reference, file, root, chain);
+				}
+
+				let mut to_return = HashMap::with_capacity(x.len());
+				for (key, member) in x {
+					to_return.insert(key.clone(), self.inline(member, file, root, chain)?);
+				}
+				return Ok(Value::Object(to_return));
+			}
+			Value::Array(x) => {
+				let mut to_return = Vec::with_capacity(x.len());
+				for item in x {
+					to_return.push(self.inline(item, file, root, chain)?);
+				}
+				return Ok(Value::Array(to_return));
+			}
+			_ => return Ok(value.clone())
+		}
+	}
+
+	fn follow(&mut self, reference: &Value, file: &Path, root: Option<&Value>, chain: &mut Vec<String>) -> Result<Value, ResolveError> {
+		let reference = match reference {
+			Value::Text(x) => x,
+			_ => return Err(failure(chain, String::from("$ref must be text")))
+		};
+
+		let (name, pointer) = match reference.find('#') {
+			Some(i) => (&reference[..i], &reference[i + 1..]),
+			None => (reference.as_str(), "")
+		};
+
+		let target = if name.is_empty() {
+			file.to_path_buf()
+		} else {
+			canonical(&file.parent().unwrap_or_else(|| Path::new("")).join(name), chain)?
+		};
+
+		let key = format!("{}#{}", target.display(), pointer);
+		if chain.contains(&key) {
+			chain.push(key);
+			return Err(failure(chain, String::from("reference cycle")));
+		}
+		chain.push(key);
+
+		//Another file is always read from disk, even if it is the one root came from
+		let root = if name.is_empty() { root } else { None };
+		let loaded;
+		let document = match root {
+			Some(x) => x,
+			None => {
+				loaded = self.load(&target, chain)?;
+				&loaded
+			}
+		};
+		let found = document.pointer(pointer).map_err(|e| failure(chain, e.to_string()))?.clone();
+		let to_return = self.inline(&found, &target, root, chain)?;
+
+		chain.pop();
+		return Ok(to_return);
+	}
+}
+
+fn failure(chain: &[String], message: String) -> ResolveError {
+	return ResolveError {
+		chain: chain.to_vec(),
+		message,
+		parse: None
+	};
+}
+
+//Canonical paths make the same file reached by different relative paths share one cache entry
+fn canonical(path: &Path, chain: &[String]) -> Result<PathBuf, ResolveError> {
+	return fs::canonicalize(path).map_err(|e| failure(chain, format!("can't read {}: {}", path.display(), e)));
+}
+
+//Load a file and inline every reference in it
+pub fn resolve_file<P: AsRef<Path>>(path: P) -> Result<Value, ResolveError> {
+	return Resolver::new().resolve_file(path);
+}
@@ -0,0 +1,797 @@
+//JSON Schema (draft 2020-12) validation.
+//
+//A schema Value is compiled once into a Schema, which can then validate any number of instances.
+//Validation doesn't stop at the first problem: every violation is reported with the JSON Pointer
+//of the failing instance value and of the schema keyword that rejected it.
+//
+//Supported keywords: type, enum, const, minimum, maximum, exclusiveMinimum, exclusiveMaximum,
+//multipleOf, minLength, maxLength, pattern, minItems, maxItems, uniqueItems, prefixItems, items,
+//contains, minProperties, maxProperties, required, properties, patternProperties,
+//additionalProperties, propertyNames, allOf, anyOf, oneOf, not, if/then/else and $ref to a
+//pointer inside the same document (usually "#/$defs/name"). Other keywords are ignored. A $ref cycle
+//that comes back to a schema without descending into the instance is a compile error.
+//
+//infer goes the other way, building a schema from sample documents.
+
+use crate::json::Value;
+use crate::json::pointer;
+use crate::json::regex::Regex;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+//Error returned when a schema can't be compiled
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+	//Pointer to the offending part of the schema
+	pub pointer: String,
+	pub message: String
+}
+
+impl fmt::Display for SchemaError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invalid schema at \"{}\": {}", self.pointer, self.message)
+	}
+}
+
+impl Error for SchemaError {}
+
+//One reason an instance doesn't match a schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+	//Pointer to the failing value in the instance
+	pub instance: String,
+	//Pointer to the keyword in the schema that rejected it
+	pub schema: String,
+	pub message: String
+}
+
+impl fmt::Display for Violation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "\"{}\": {} (schema \"{}\")", self.instance, self.message, self.schema)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Type {
+	Null,
+	Boolean,
+	Object,
+	Array,
+	Number,
+	Integer,
+	String
+}
+
+impl Type {
+	fn from_name(name: &str) -> Option<Type> {
+		match name {
+			"null" => Some(Type::Null),
+			"boolean" => Some(Type::Boolean),
+			"object" => Some(Type::Object),
+			"array" => Some(Type::Array),
+			"number" => Some(Type::Number),
+			"integer" => Some(Type::Integer),
+			"string" => Some(Type::String),
+			_ => None
+		}
+	}
+
+	fn name(&self) -> &'static str {
+		match self {
+			Type::Null => "null",
+			Type::Boolean => "boolean",
+			Type::Object => "object",
+			Type::Array => "array",
+			Type::Number => "number",
+			Type::Integer => "integer",
+			Type::String => "string"
+		}
+	}
+
+	fn matches(&self, value: &Value) -> bool {
+		match (self, value) {
+			(Type::Null, Value::Null) => true,
+			(Type::Boolean, Value::Bool(_)) => true,
+			(Type::Object, Value::Object(_)) => true,
+			(Type::Array, Value::Array(_)) => true,
+			(Type::Number, Value::Number(_)) => true,
+			(Type::Integer, Value::Number(x)) => x.fract() == 0.0,
+			(Type::String, Value::Text(_)) => true,
+			_ => false
+		}
+	}
+}
+
+//Name of an instance's type as JSON Schema spells it
+fn type_name(value: &Value) -> &'static str {
+	match value {
+		Value::Text(_) => "string",
+		Value::Bool(_) => "boolean",
+		_ => value.type_name()
+	}
+}
+
+//Whether n is a multiple of x. Decimal divisors like 0.1 have no exact binary form, so the quotient
+//only has to be within rounding error of a whole number.
+fn is_multiple(n: f64, x: f64) -> bool {
+	let quotient = n / x;
+	return (quotient - quotient.round()).abs() <= 1e-9 * quotient.abs().max(1.0);
+}
+
+//Decode the %XX escapes of a $ref fragment (a URI fragment) into the pointer they spell
+fn percent_decode(fragment: &str) -> Option<String> {
+	let mut bytes: Vec<u8> = Vec::new();
+	let mut rest = fragment.bytes();
+
+	while let Some(c) = rest.next() {
+		if c != b'%' {
+			bytes.push(c);
+			continue;
+		}
+
+		let high = (rest.next()? as char).to_digit(16)?;
+		let low = (rest.next()? as char).to_digit(16)?;
+		bytes.push((high * 16 + low) as u8);
+	}
+
+	return String::from_utf8(bytes).ok();
+}
+
+//Compiled keywords. Subschemas are indexes into Schema::nodes.
+#[derive(Debug, Clone)]
+enum Keyword {
+	Type(Vec<Type>),
+	Enum(Vec<Value>),
+	Const(Value),
+	Minimum(f64),
+	Maximum(f64),
+	ExclusiveMinimum(f64),
+	ExclusiveMaximum(f64),
+	MultipleOf(f64),
+	MinLength(usize),
+	MaxLength(usize),
+	Pattern(String, Regex),
+	MinItems(usize),
+	MaxItems(usize),
+	UniqueItems,
+	PrefixItems(Vec<usize>),
+	//Schema for items after the first skip (the length of prefixItems)
+	Items(usize, usize),
+	Contains(usize),
+	MinProperties(usize),
+	MaxProperties(usize),
+	Required(Vec<String>),
+	Properties(Vec<(String, usize)>),
+	PatternProperties(Vec<(String, Regex, usize)>),
+	//Schema for members not covered by properties or patternProperties
+	AdditionalProperties(usize, Vec<String>, Vec<Regex>),
+	PropertyNames(usize),
+	AllOf(Vec<usize>),
+	AnyOf(Vec<usize>),
+	OneOf(Vec<usize>),
+	Not(usize),
+	If(usize, Option<usize>, Option<usize>),
+	Ref(usize)
+}
+
+impl Keyword {
+	//Name of the keyword in the schema, used to build schema pointers
+	fn name(&self) -> &'static str {
+		match self {
+			Keyword::Type(_) => "type",
+			Keyword::Enum(_) => "enum",
+			Keyword::Const(_) => "const",
+			Keyword::Minimum(_) => "minimum",
+			Keyword::Maximum(_) => "maximum",
+			Keyword::ExclusiveMinimum(_) => "exclusiveMinimum",
+			Keyword::ExclusiveMaximum(_) => "exclusiveMaximum",
+			Keyword::MultipleOf(_) => "multipleOf",
+			Keyword::MinLength(_) => "minLength",
+			Keyword::MaxLength(_) => "maxLength",
+			Keyword::Pattern(..) => "pattern",
+			Keyword::MinItems(_) => "minItems",
+			Keyword::MaxItems(_) => "maxItems",
+			Keyword::UniqueItems => "uniqueItems",
+			Keyword::PrefixItems(_) => "prefixItems",
+			Keyword::Items(..) => "items",
+			Keyword::Contains(_) => "contains",
+			Keyword::MinProperties(_) => "minProperties",
+			Keyword::MaxProperties(_) => "maxProperties",
+			Keyword::Required(_) => "required",
+			Keyword::Properties(_) => "properties",
+			Keyword::PatternProperties(_) => "patternProperties",
+			Keyword::AdditionalProperties(..) => "additionalProperties",
+			Keyword::PropertyNames(_) => "propertyNames",
+			Keyword::AllOf(_) => "allOf",
+			Keyword::AnyOf(_) => "anyOf",
+			Keyword::OneOf(_) => "oneOf",
+			Keyword::Not(_) => "not",
+			Keyword::If(..) => "if",
+			Keyword::Ref(_) => "$ref"
+		}
+	}
+
+	//Subschemas applied to the same instance value rather than to a part of it
+	fn in_place(&self) -> Vec<usize> {
+		match self {
+			Keyword::AllOf(x) | Keyword::AnyOf(x) | Keyword::OneOf(x) => x.clone(),
+			Keyword::Not(x) | Keyword::Ref(x) => vec![*x],
+			Keyword::If(condition, then, otherwise) => {
+				let mut to_return = vec![*condition];
+				to_return.extend(then.iter().chain(otherwise.iter()));
+				to_return
+			}
+			_ => Vec::new()
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+	//true accepts everything, false rejects everything
+	Bool(bool),
+	Keywords(Vec<Keyword>)
+}
+
+//A compiled schema
+#[derive(Debug, Clone)]
+pub struct Schema {
+	nodes: Vec<Node>,
+	//Schema pointer of each node
+	pointers: Vec<String>
+}
+
+struct Compiler<'a> {
+	root: &'a Value,
+	nodes: Vec<Node>,
+	pointers: Vec<String>,
+	//Nodes already compiled, so that recursive $refs terminate
+	by_pointer: HashMap<String, usize>
+}
+
+impl<'a> Compiler<'a> {
+	fn error(pointer: &str, message: &str) -> SchemaError {
+		return SchemaError {
+			pointer: pointer.to_string(),
+			message: message.to_string()
+		};
+	}
+
+	fn compile(&mut self, pointer: &str) -> Result<usize, SchemaError> {
+		if let Some(x) = self.by_pointer.get(pointer) {
+			return Ok(*x);
+		}
+
+		let index = self.nodes.len();
+		self.nodes.push(Node::Bool(true));
+		self.pointers.push(pointer.to_string());
+		self.by_pointer.insert(pointer.to_string(), index);
+
+		let root = self.root;
+		let schema = root.pointer(pointer).map_err(|e| Compiler::error(pointer, &e.to_string()))?;
+		let node = match schema {
+			Value::Bool(x) => Node::Bool(*x),
+			Value::Object(x) => Node::Keywords(self.keywords(pointer, x)?),
+			_ => return Err(Compiler::error(pointer, "a schema must be an object or a bool"))
+		};
+
+		self.nodes[index] = node;
+		return Ok(index);
+	}
+
+	fn child(&mut self, pointer: &str, key: &str) -> Result<usize, SchemaError> {
+		return self.compile(&format!("{}/{}", pointer, pointer::escape(key)));
+	}
+
+	fn children(&mut self, pointer: &str, key: &str, value: &Value) -> Result<Vec<usize>, SchemaError> {
+		let here = format!("{}/{}", pointer, key);
+		match value {
+			Value::Array(x) if !x.is_empty() => (0..x.len()).map(|i| self.compile(&format!("{}/{}", here, i))).collect(),
+			_ => Err(Compiler::error(&here, "expected a non-empty array of schemas"))
+		}
+	}
+
+	fn keywords(&mut self, pointer: &str, schema: &HashMap<String, Value>) -> Result<Vec<Keyword>, SchemaError> {
+		let mut to_return: Vec<Keyword> = Vec::new();
+
+		//Sort keywords so violations come out in a stable order
+		let mut keys: Vec<&String> = schema.keys().collect();
+		keys.sort();
+
+		for key in keys {
+			let value = &schema[key];
+			let here = format!("{}/{}", pointer, pointer::escape(key));
+
+			let number = || match value {
+				Value::Number(x) => Ok(*x),
+				_ => Err(Compiler::error(&here, "expected a number"))
+			};
+			let count = || match value {
+				Value::Number(x) if *x >= 0.0 && x.fract() == 0.0 => Ok(*x as usize),
+				_ => Err(Compiler::error(&here, "expected a non-negative integer"))
+			};
+			let object = || match value {
+				Value::Object(x) => {
+					let mut keys: Vec<&String> = x.keys().collect();
+					keys.sort();
+					Ok(keys)
+				}
+				_ => Err(Compiler::error(&here, "expected an object"))
+			};
+			let regex = |pattern: &str, at: &str| Regex::new(pattern).map_err(|e| Compiler::error(at, &e.to_string()));
+
+			let keyword = match key.as_str() {
+				"type" => {
+					let names = match value {
+						Value::Text(x) => vec![x.as_str()],
+						Value::Array(x) => x.iter().map(|x| match x {
+							Value::Text(x) => Ok(x.as_str()),
+							_ => Err(Compiler::error(&here, "type names must be text"))
+						}).collect::<Result<Vec<&str>, SchemaError>>()?,
+						_ => return Err(Compiler::error(&here, "expected a type name or an array of them"))
+					};
+
+					let types = names.iter().map(|x| Type::from_name(x).ok_or_else(|| Compiler::error(&here, &format!("unknown type \"{}\"", x))));
+					Keyword::Type(types.collect::<Result<Vec<Type>, SchemaError>>()?)
+				}
+				"enum" => match value {
+					Value::Array(x) => Keyword::Enum(x.clone()),
+					_ => return Err(Compiler::error(&here, "expected an array"))
+				},
+				"const" => Keyword::Const(value.clone()),
+				"minimum" => Keyword::Minimum(number()?),
+				"maximum" => Keyword::Maximum(number()?),
+				"exclusiveMinimum" => Keyword::ExclusiveMinimum(number()?),
+				"exclusiveMaximum" => Keyword::ExclusiveMaximum(number()?),
+				"multipleOf" => match number()? {
+					x if x > 0.0 => Keyword::MultipleOf(x),
+					_ => return Err(Compiler::error(&here, "multipleOf must be greater than 0"))
+				},
+				"minLength" => Keyword::MinLength(count()?),
+				"maxLength" => Keyword::MaxLength(count()?),
+				"pattern" => match value {
+					Value::Text(x) => Keyword::Pattern(x.clone(), regex(x, &here)?),
+					_ => return Err(Compiler::error(&here, "expected text"))
+				},
+				"minItems" => Keyword::MinItems(count()?),
+				"maxItems" => Keyword::MaxItems(count()?),
+				"uniqueItems" => match value {
+					Value::Bool(true) => Keyword::UniqueItems,
+					Value::Bool(false) => continue,
+					_ => return Err(Compiler::error(&here, "expected a bool"))
+				},
+				"prefixItems" => Keyword::PrefixItems(self.children(pointer, key, value)?),
+				"items" => {
+					let skip = match schema.get("prefixItems") {
+						Some(Value::Array(x)) => x.len(),
+						_ => 0
+					};
+					Keyword::Items(self.child(pointer, key)?, skip)
+				}
+				"contains" => Keyword::Contains(self.child(pointer, key)?),
+				"minProperties" => Keyword::MinProperties(count()?),
+				"maxProperties" => Keyword::MaxProperties(count()?),
+				"required" => match value {
+					Value::Array(x) => Keyword::Required(x.iter().map(|x| match x {
+						Value::Text(x) => Ok(x.clone()),
+						_ => Err(Compiler::error(&here, "required names must be text"))
+					}).collect::<Result<Vec<String>, SchemaError>>()?),
+					_ => return Err(Compiler::error(&here, "expected an array"))
+				},
+				"properties" => {
+					let mut properties: Vec<(String, usize)> = Vec::new();
+					for name in object()? {
+						properties.push((name.clone(), self.child(&here, name)?));
+					}
+					Keyword::Properties(properties)
+				}
+				"patternProperties" => {
+					let mut properties: Vec<(String, Regex, usize)> = Vec::new();
+					for name in object()? {
+						let at = format!("{}/{}", here, pointer::escape(name));
+						properties.push((name.clone(), regex(name, &at)?, self.compile(&at)?));
+					}
+					Keyword::PatternProperties(properties)
+				}
+				"additionalProperties" => {
+					let known = match schema.get("properties") {
+						Some(Value::Object(x)) => x.keys().cloned().collect(),
+						_ => Vec::new()
+					};
+					let mut patterns: Vec<Regex> = Vec::new();
+					if let Some(Value::Object(x)) = schema.get("patternProperties") {
+						for name in x.keys() {
+							patterns.push(regex(name, &format!("{}/patternProperties/{}", pointer, pointer::escape(name)))?);
+						}
+					}
+					Keyword::AdditionalProperties(self.child(pointer, key)?, known, patterns)
+				}
+				"propertyNames" => Keyword::PropertyNames(self.child(pointer, key)?),
+				"allOf" => Keyword::AllOf(self.children(pointer, key, value)?),
+				"anyOf" => Keyword::AnyOf(self.children(pointer, key, value)?),
+				"oneOf" => Keyword::OneOf(self.children(pointer, key, value)?),
+				"not" => Keyword::Not(self.child(pointer, key)?),
+				"if" => {
+					let then = if schema.contains_key("then") { Some(self.child(pointer, "then")?) } else { None };
+					let otherwise = if schema.contains_key("else") { Some(self.child(pointer, "else")?) } else { None };
+					Keyword::If(self.child(pointer, key)?, then, otherwise)
+				}
+				"$ref" => {
+					let reference = match value {
+						Value::Text(x) => x,
+						_ => return Err(Compiler::error(&here, "expected text"))
+					};
+					if !reference.starts_with('#') {
+						return Err(Compiler::error(&here, "only references within the same document (\"#...\") are supported"));
+					}
+					let target = percent_decode(&reference[1..]).ok_or_else(|| Compiler::error(&here, "invalid percent-encoding"))?;
+					Keyword::Ref(self.compile(&target).map_err(|e| Compiler::error(&here, &e.message))?)
+				}
+				_ => continue
+			};
+
+			to_return.push(keyword);
+		}
+
+		return Ok(to_return);
+	}
+
+	//Reject $ref cycles that come back to a node without descending into the instance, which
+	//validation would follow forever. state is 1 while a node is being visited and 2 once done.
+	fn check_cycles(&self, node: usize, state: &mut Vec<u8>) -> Result<(), SchemaError> {
+		state[node] = 1;
+
+		if let Node::Keywords(keywords) = &self.nodes[node] {
+			for keyword in keywords {
+				for next in keyword.in_place() {
+					match state[next] {
+						0 => self.check_cycles(next, state)?,
+						1 => {
+							let at = format!("{}/{}", self.pointers[node], keyword.name());
+							return Err(Compiler::error(&at, "$ref cycle never descends into the instance"));
+						}
+						_ => {}
+					}
+				}
+			}
+		}
+
+		state[node] = 2;
+		return Ok(());
+	}
+}
+
+//State for one validate call
+struct Validator<'a> {
+	schema: &'a Schema,
+	violations: Vec<Violation>
+}
+
+impl<'a> Validator<'a> {
+	fn fail(&mut self, instance: &str, node: usize, keyword: &Keyword, message: String) {
+		self.violations.push(Violation {
+			instance: instance.to_string(),
+			schema: format!("{}/{}", self.schema.pointers[node], keyword.name()),
+			message
+		});
+	}
+
+	//Check a subschema on its own, without recording its violations
+	fn passes(&self, node: usize, value: &Value, instance: &str) -> bool {
+		let mut inner = Validator { schema: self.schema, violations: Vec::new() };
+		inner.validate(node, value, instance);
+		return inner.violations.is_empty();
+	}
+
+	fn validate(&mut self, node: usize, value: &Value, instance: &str) {
+		let keywords = match &self.schema.nodes[node] {
+			Node::Bool(true) => return,
+			Node::Bool(false) => {
+				self.violations.push(Violation {
+					instance: instance.to_string(),
+					schema: self.schema.pointers[node].clone(),
+					message: String::from("no value is allowed here")
+				});
+				return;
+			}
+			Node::Keywords(x) => x
+		};
+
+		for keyword in keywords {
+			self.keyword(node, keyword, value, instance);
+		}
+	}
+
+	fn keyword(&mut self, node: usize, keyword: &Keyword, value: &Value, instance: &str) {
+		let child = |key: &str| format!("{}/{}", instance, pointer::escape(key));
+
+		match (keyword, value) {
+			(Keyword::Type(types), _) if !types.iter().any(|x| x.matches(value)) => {
+				let names: Vec<&str> = types.iter().map(Type::name).collect();
+				self.fail(instance, node, keyword, format!("expected {}, found {}", names.join(" or "), type_name(value)));
+			}
+			(Keyword::Enum(options), _) if !options.contains(value) => {
+				self.fail(instance, node, keyword, format!("{} is not one of the allowed values", value));
+			}
+			(Keyword::Const(expected), _) if value != expected => {
+				self.fail(instance, node, keyword, format!("expected {}, found {}", expected, value));
+			}
+			(Keyword::Minimum(x), Value::Number(n)) if n < x => self.fail(instance, node, keyword, format!("{} is less than {}", n, x)),
+			(Keyword::Maximum(x), Value::Number(n)) if n > x => self.fail(instance, node, keyword, format!("{} is greater than {}", n, x)),
+			(Keyword::ExclusiveMinimum(x), Value::Number(n)) if n <= x => {
+				self.fail(instance, node, keyword, format!("{} is not greater than {}", n, x));
+			}
+			(Keyword::ExclusiveMaximum(x), Value::Number(n)) if n >= x => {
+				self.fail(instance, node, keyword, format!("{} is not less than {}", n, x));
+			}
+			(Keyword::MultipleOf(x), Value::Number(n)) if !is_multiple(*n, *x) => {
+				self.fail(instance, node, keyword, format!("{} is not a multiple of {}", n, x));
+			}
+			(Keyword::MinLength(x), Value::Text(s)) if s.chars().count() < *x => {
+				self.fail(instance, node, keyword, format!("text is shorter than {} characters", x));
+			}
+			(Keyword::MaxLength(x), Value::Text(s)) if s.chars().count() > *x => {
+				self.fail(instance, node, keyword, format!("text is longer than {} characters", x));
+			}
+			(Keyword::Pattern(pattern, regex), Value::Text(s)) if !regex.search(s) => {
+				self.fail(instance, node, keyword, format!("text doesn't match \"{}\"", pattern));
+			}
+			(Keyword::MinItems(x), Value::Array(items)) if items.len() < *x => {
+				self.fail(instance, node, keyword, format!("array has fewer than {} items", x));
+			}
+			(Keyword::MaxItems(x), Value::Array(items)) if items.len() > *x => {
+				self.fail(instance, node, keyword, format!("array has more than {} items", x));
+			}
+			(Keyword::UniqueItems, Value::Array(items)) => {
+				for i in 1..items.len() {
+					if items[..i].contains(&items[i]) {
+						self.fail(&format!("{}/{}", instance, i), node, keyword, String::from("array items must be unique"));
+					}
+				}
+			}
+			(Keyword::PrefixItems(schemas), Value::Array(items)) => {
+				for (i, (schema, item)) in schemas.iter().zip(items).enumerate() {
+					self.validate(*schema, item, &format!("{}/{}", instance, i));
+				}
+			}
+			(Keyword::Items(schema, skip), Value::Array(items)) => {
+				for (i, item) in items.iter().enumerate().skip(*skip) {
+					self.validate(*schema, item, &format!("{}/{}", instance, i));
+				}
+			}
+			(Keyword::Contains(schema), Value::Array(items)) => {
+				let found = items.iter().enumerate().any(|(i, x)| self.passes(*schema, x, &format!("{}/{}", instance, i)));
+				if !found {
+					self.fail(instance, node, keyword, String::from("no item matches the contains schema"));
+				}
+			}
+			(Keyword::MinProperties(x), Value::Object(members)) if members.len() < *x => {
+				self.fail(instance, node, keyword, format!("object has fewer than {} members", x));
+			}
+			(Keyword::MaxProperties(x), Value::Object(members)) if members.len() > *x => {
+				self.fail(instance, node, keyword, format!("object has more than {} members", x));
+			}
+			(Keyword::Required(names), Value::Object(members)) => {
+				for name in names {
+					if !members.contains_key(name) {
+						self.fail(instance, node, keyword, format!("missing required member \"{}\"", name));
+					}
+				}
+			}
+			(Keyword::Properties(properties), Value::Object(members)) => {
+				for (name, schema) in properties {
+					if let Some(x) = members.get(name) {
+						self.validate(*schema, x, &child(name));
+					}
+				}
+			}
+			(Keyword::PatternProperties(properties), Value::Object(members)) => {
+				let mut names: Vec<&String> = members.keys().collect();
+				names.sort();
+				for (_, regex, schema) in properties {
+					for name in names.iter().filter(|x| regex.search(x)) {
+						self.validate(*schema, &members[*name], &child(name));
+					}
+				}
+			}
+			(Keyword::AdditionalProperties(schema, known, patterns), Value::Object(members)) => {
+				let mut names: Vec<&String> = members.keys().collect();
+				names.sort();
+				for name in names {
+					if !known.contains(name) && !patterns.iter().any(|x| x.search(name)) {
+						self.validate(*schema, &members[name], &child(name));
+					}
+				}
+			}
+			(Keyword::PropertyNames(schema), Value::Object(members)) => {
+				let mut names: Vec<&String> = members.keys().collect();
+				names.sort();
+				for name in names {
+					self.validate(*schema, &Value::from(name.as_str()), &child(name));
+				}
+			}
+			(Keyword::AllOf(schemas), _) => {
+				for schema in schemas {
+					self.validate(*schema, value, instance);
+				}
+			}
+			(Keyword::AnyOf(schemas), _) if !schemas.iter().any(|x| self.passes(*x, value, instance)) => {
+				self.fail(instance, node, keyword, String::from("value doesn't match any of the anyOf schemas"));
+			}
+			(Keyword::OneOf(schemas), _) => {
+				let matched = schemas.iter().filter(|x| self.passes(**x, value, instance)).count();
+				if matched != 1 {
+					self.fail(instance, node, keyword, format!("value matches {} of the oneOf schemas instead of exactly one", matched));
+				}
+			}
+			(Keyword::Not(schema), _) if self.passes(*schema, value, instance) => {
+				self.fail(instance, node, keyword, String::from("value matches the not schema"));
+			}
+			(Keyword::If(condition, then, otherwise), _) => {
+				let branch = if self.passes(*condition, value, instance) { then } else { otherwise };
+				if let Some(x) = branch {
+					self.validate(*x, value, instance);
+				}
+			}
+			(Keyword::Ref(schema), _) => self.validate(*schema, value, instance),
+			_ => {}
+		}
+	}
+}
+
+impl Schema {
+	//Compile a schema document
+	pub fn compile(schema: &Value) -> Result<Schema, SchemaError> {
+		let mut compiler = Compiler {
+			root: schema,
+			nodes: Vec::new(),
+			pointers: Vec::new(),
+			by_pointer: HashMap::new()
+		};
+		compiler.compile("")?;
+
+		let mut state = vec![0; compiler.nodes.len()];
+		for node in 0..compiler.nodes.len() {
+			if state[node] == 0 {
+				compiler.check_cycles(node, &mut state)?;
+			}
+		}
+
+		return Ok(Schema {
+			nodes: compiler.nodes,
+			pointers: compiler.pointers
+		});
+	}
+
+	//Validate an instance, returning every violation if it doesn't match
+	pub fn validate(&self, instance: &Value) -> Result<(), Vec<Violation>> {
+		let mut validator = Validator { schema: self, violations: Vec::new() };
+		validator.validate(0, instance, "");
+
+		if validator.violations.is_empty() {
+			return Ok(());
+		}
+		return Err(validator.violations);
+	}
+
+	pub fn is_valid(&self, instance: &Value) -> bool {
+		return self.validate(instance).is_ok();
+	}
+}
+
+//Everything seen at one position across the samples, used by infer and by codegen
+#[derive(Default)]
+pub(crate) struct Shape {
+	pub(crate) null: bool,
+	pub(crate) boolean: bool,
+	pub(crate) integer: bool,
+	pub(crate) number: bool,
+	pub(crate) string: bool,
+	//Number of objects seen here, and the members seen in them
+	pub(crate) objects: usize,
+	pub(crate) properties: HashMap<String, (usize, Shape)>,
+	//Items of every array seen here
+	pub(crate) array: bool,
+	pub(crate) items: Option<Box<Shape>>
+}
+
+impl Shape {
+	pub(crate) fn add(&mut self, value: &Value) {
+		match value {
+			Value::Null | Value::Invalid => self.null = true,
+			Value::Bool(_) => self.boolean = true,
+			Value::Number(x) if x.fract() == 0.0 => self.integer = true,
+			Value::Number(_) => self.number = true,
+			Value::Text(_) => self.string = true,
+			Value::Object(x) => {
+				self.objects += 1;
+				for (key, member) in x {
+					let property = self.properties.entry(key.clone()).or_default();
+					property.0 += 1;
+					property.1.add(member);
+				}
+			}
+			Value::Array(x) => {
+				self.array = true;
+				for item in x {
+					self.items.get_or_insert_with(Default::default).add(item);
+				}
+			}
+		}
+	}
+
+	fn to_schema(&self) -> Value {
+		let mut to_return: HashMap<String, Value> = HashMap::new();
+
+		//Integers are numbers, so a mix of both is just "number"
+		let mut types: Vec<Value> = Vec::new();
+		let seen = [
+			(self.null, "null"),
+			(self.boolean, "boolean"),
+			(self.integer && !self.number, "integer"),
+			(self.number, "number"),
+			(self.string, "string"),
+			(self.objects > 0, "object"),
+			(self.array, "array")
+		];
+		for (present, name) in seen.iter() {
+			if *present {
+				types.push(Value::from(*name));
+			}
+		}
+
+		match types.len() {
+			0 => {}
+			1 => {
+				to_return.insert(String::from("type"), types.pop().unwrap());
+			}
+			_ => {
+				to_return.insert(String::from("type"), Value::Array(types));
+			}
+		}
+
+		if self.objects > 0 {
+			let mut properties: HashMap<String, Value> = HashMap::new();
+			let mut required: Vec<&String> = Vec::new();
+
+			for (key, (count, shape)) in &self.properties {
+				properties.insert(key.clone(), shape.to_schema());
+				//Members missing from some samples are optional
+				if *count == self.objects {
+					required.push(key);
+				}
+			}
+			required.sort();
+
+			to_return.insert(String::from("properties"), Value::Object(properties));
+			if !required.is_empty() {
+				to_return.insert(String::from("required"), Value::from(required.iter().map(|x| x.as_str()).collect::<Vec<&str>>()));
+			}
+		}
+
+		if let Some(x) = &self.items {
+			to_return.insert(String::from("items"), x.to_schema());
+		}
+
+		return Value::Object(to_return);
+	}
+}
+
+//Infer a schema that every sample matches. Types seen at the same position are merged, object
+//members missing from some samples are left out of required, and the items of all arrays at a
+//position share one schema.
+pub fn infer(samples: &[Value]) -> Value {
+	let mut shape = Shape::default();
+	for sample in samples {
+		shape.add(sample);
+	}
+
+	let mut to_return = shape.to_schema();
+	if let Value::Object(x) = &mut to_return {
+		x.insert(String::from("$schema"), Value::from("https://json-schema.org/draft/2020-12/schema"));
+	}
+
+	return to_return;
+}
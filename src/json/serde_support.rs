@@ -0,0 +1,658 @@
+use crate::json::Value;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize};
+use serde::{Deserialize, Deserializer, Serializer};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::vec;
+
+//Error produced while converting between serde types and json::Value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+	pub message: String
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.message)
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+	fn custom<T: fmt::Display>(msg: T) -> Self {
+		return Error { message: msg.to_string() };
+	}
+}
+
+impl de::Error for Error {
+	fn custom<T: fmt::Display>(msg: T) -> Self {
+		return Error { message: msg.to_string() };
+	}
+}
+
+//Convert any serde type to a json::Value
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, Error> {
+	return value.serialize(ValueSerializer);
+}
+
+//Convert a json::Value to any serde type
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+	return T::deserialize(value);
+}
+
+impl Serialize for Value {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self {
+			Value::Invalid => Err(ser::Error::custom("cannot serialize an invalid value")),
+			Value::Null => serializer.serialize_unit(),
+			Value::Number(x) => serializer.serialize_f64(*x),
+			Value::Text(x) => serializer.serialize_str(x),
+			Value::Bool(x) => serializer.serialize_bool(*x),
+			Value::Object(x) => serializer.collect_map(x),
+			Value::Array(x) => serializer.collect_seq(x)
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for Value {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+		return deserializer.deserialize_any(ValueVisitor);
+	}
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+	type Value = Value;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("any json value")
+	}
+
+	fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+		return Ok(Value::Bool(v));
+	}
+
+	fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+		return Ok(Value::Number(v as f64));
+	}
+
+	fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+		return Ok(Value::Number(v as f64));
+	}
+
+	fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+		return Ok(Value::Number(v));
+	}
+
+	fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+		return Ok(Value::Text(v.to_string()));
+	}
+
+	fn visit_string<E>(self, v: String) -> Result<Value, E> {
+		return Ok(Value::Text(v));
+	}
+
+	fn visit_unit<E>(self) -> Result<Value, E> {
+		return Ok(Value::Null);
+	}
+
+	fn visit_none<E>(self) -> Result<Value, E> {
+		return Ok(Value::Null);
+	}
+
+	fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+		return Value::deserialize(deserializer);
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+		let mut to_return = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+		while let Some(x) = seq.next_element()? {
+			to_return.push(x);
+		}
+
+		return Ok(Value::Array(to_return));
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+		let mut to_return = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+		while let Some((k, v)) = map.next_entry()? {
+			to_return.insert(k, v);
+		}
+
+		return Ok(Value::Object(to_return));
+	}
+}
+
+//Serializer that builds a json::Value. Enums use the externally tagged form: {"Variant": content}.
+struct ValueSerializer;
+
+//Wrap the content of an enum variant in an object keyed by the variant name
+fn tagged(variant: &str, content: Value) -> Value {
+	let mut object = HashMap::new();
+	object.insert(variant.to_string(), content);
+	return Value::Object(object);
+}
+
+impl Serializer for ValueSerializer {
+	type Ok = Value;
+	type Error = Error;
+
+	type SerializeSeq = SerializeArray;
+	type SerializeTuple = SerializeArray;
+	type SerializeTupleStruct = SerializeArray;
+	type SerializeTupleVariant = SerializeArray;
+	type SerializeMap = SerializeObject;
+	type SerializeStruct = SerializeObject;
+	type SerializeStructVariant = SerializeObject;
+
+	fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+		return Ok(Value::Bool(v));
+	}
+
+	fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_char(self, v: char) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_str(self, v: &str) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	//Bytes are stored as an array of numbers
+	fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+		return Ok(Value::from(v));
+	}
+
+	fn serialize_none(self) -> Result<Value, Error> {
+		return Ok(Value::Null);
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+		return value.serialize(self);
+	}
+
+	fn serialize_unit(self) -> Result<Value, Error> {
+		return Ok(Value::Null);
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+		return Ok(Value::Null);
+	}
+
+	fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Value, Error> {
+		return Ok(Value::from(variant));
+	}
+
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Value, Error> {
+		return value.serialize(self);
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, variant: &'static str, value: &T) -> Result<Value, Error> {
+		return Ok(tagged(variant, value.serialize(self)?));
+	}
+
+	fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Error> {
+		return Ok(SerializeArray {
+			items: Vec::with_capacity(len.unwrap_or(0)),
+			variant: None
+		});
+	}
+
+	fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+		return self.serialize_seq(Some(len));
+	}
+
+	fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeArray, Error> {
+		return self.serialize_seq(Some(len));
+	}
+
+	fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize) -> Result<SerializeArray, Error> {
+		return Ok(SerializeArray {
+			items: Vec::with_capacity(len),
+			variant: Some(variant)
+		});
+	}
+
+	fn serialize_map(self, len: Option<usize>) -> Result<SerializeObject, Error> {
+		return Ok(SerializeObject {
+			members: HashMap::with_capacity(len.unwrap_or(0)),
+			next_key: None,
+			variant: None
+		});
+	}
+
+	fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject, Error> {
+		return self.serialize_map(Some(len));
+	}
+
+	fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize) -> Result<SerializeObject, Error> {
+		return Ok(SerializeObject {
+			members: HashMap::with_capacity(len),
+			next_key: None,
+			variant: Some(variant)
+		});
+	}
+}
+
+struct SerializeArray {
+	items: Vec<Value>,
+	variant: Option<&'static str>
+}
+
+impl SerializeArray {
+	fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		self.items.push(to_value(value)?);
+		return Ok(());
+	}
+
+	fn finish(self) -> Result<Value, Error> {
+		let array = Value::Array(self.items);
+
+		match self.variant {
+			Some(x) => Ok(tagged(x, array)),
+			None => Ok(array)
+		}
+	}
+}
+
+impl ser::SerializeSeq for SerializeArray {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		return self.push(value);
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		return self.finish();
+	}
+}
+
+impl ser::SerializeTuple for SerializeArray {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		return self.push(value);
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		return self.finish();
+	}
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		return self.push(value);
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		return self.finish();
+	}
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		return self.push(value);
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		return self.finish();
+	}
+}
+
+struct SerializeObject {
+	members: HashMap<String, Value>,
+	next_key: Option<String>,
+	variant: Option<&'static str>
+}
+
+impl SerializeObject {
+	fn finish(self) -> Result<Value, Error> {
+		let object = Value::Object(self.members);
+
+		match self.variant {
+			Some(x) => Ok(tagged(x, object)),
+			None => Ok(object)
+		}
+	}
+}
+
+impl ser::SerializeMap for SerializeObject {
+	type Ok = Value;
+	type Error = Error;
+
+	//Json keys have to be text, but numbers and bools are written out as their text form
+	fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+		let key = match to_value(key)? {
+			Value::Text(x) => x,
+			Value::Number(x) => x.to_string(),
+			Value::Bool(x) => x.to_string(),
+			x => return Err(ser::Error::custom(format!("object keys must be text, found {}", x.type_name())))
+		};
+
+		self.next_key = Some(key);
+		return Ok(());
+	}
+
+	fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+		let key = match self.next_key.take() {
+			Some(x) => x,
+			None => return Err(ser::Error::custom("serialize_value called before serialize_key"))
+		};
+
+		self.members.insert(key, to_value(value)?);
+		return Ok(());
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		return self.finish();
+	}
+}
+
+impl ser::SerializeStruct for SerializeObject {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+		self.members.insert(key.to_string(), to_value(value)?);
+		return Ok(());
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		return self.finish();
+	}
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+	type Ok = Value;
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+		self.members.insert(key.to_string(), to_value(value)?);
+		return Ok(());
+	}
+
+	fn end(self) -> Result<Value, Error> {
+		return self.finish();
+	}
+}
+
+//A json::Value can be read directly as a serde Deserializer
+impl<'de> Deserializer<'de> for Value {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self {
+			Value::Invalid => Err(de::Error::custom("cannot deserialize an invalid value")),
+			Value::Null => visitor.visit_unit(),
+			Value::Number(x) => {
+				//Hand whole numbers to the visitor as integers so integer fields accept them
+				if x.fract() == 0.0 && x >= 0.0 && x < u64::MAX as f64 {
+					return visitor.visit_u64(x as u64);
+				}
+				if x.fract() == 0.0 && x >= i64::MIN as f64 && x < 0.0 {
+					return visitor.visit_i64(x as i64);
+				}
+
+				visitor.visit_f64(x)
+			}
+			Value::Text(x) => visitor.visit_string(x),
+			Value::Bool(x) => visitor.visit_bool(x),
+			Value::Object(x) => visitor.visit_map(ObjectAccess::new(x)),
+			Value::Array(x) => visitor.visit_seq(ArrayAccess { items: x.into_iter() })
+		}
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self {
+			Value::Null => visitor.visit_none(),
+			_ => visitor.visit_some(self)
+		}
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+		return visitor.visit_newtype_struct(self);
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+		match self {
+			//Unit variant
+			Value::Text(x) => visitor.visit_enum(x.into_deserializer()),
+
+			//{"Variant": content}
+			Value::Object(x) => {
+				if x.len() != 1 {
+					return Err(de::Error::custom("expected an object with a single key for an enum variant"));
+				}
+
+				let (variant, content) = x.into_iter().next().unwrap();
+				visitor.visit_enum(EnumAccess { variant, content })
+			}
+
+			_ => Err(de::Error::custom(format!("expected text or object for an enum, found {}", self.type_name())))
+		}
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+	}
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+	type Deserializer = Value;
+
+	fn into_deserializer(self) -> Value {
+		return self;
+	}
+}
+
+struct ArrayAccess {
+	items: vec::IntoIter<Value>
+}
+
+impl<'de> SeqAccess<'de> for ArrayAccess {
+	type Error = Error;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+		match self.items.next() {
+			Some(x) => seed.deserialize(x).map(Some),
+			None => Ok(None)
+		}
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		return Some(self.items.len());
+	}
+}
+
+struct ObjectAccess {
+	members: std::collections::hash_map::IntoIter<String, Value>,
+	next_value: Option<Value>
+}
+
+impl ObjectAccess {
+	fn new(members: HashMap<String, Value>) -> ObjectAccess {
+		return ObjectAccess {
+			members: members.into_iter(),
+			next_value: None
+		};
+	}
+}
+
+impl<'de> MapAccess<'de> for ObjectAccess {
+	type Error = Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+		match self.members.next() {
+			Some((k, v)) => {
+				self.next_value = Some(v);
+				seed.deserialize(KeyDeserializer { key: k }).map(Some)
+			}
+			None => Ok(None)
+		}
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+		match self.next_value.take() {
+			Some(x) => seed.deserialize(x),
+			None => Err(de::Error::custom("next_value called before next_key"))
+		}
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		return Some(self.members.len());
+	}
+}
+
+//Object keys are always text, so numeric and bool keys written by serialize_key are parsed back
+//when the map's key type asks for them
+struct KeyDeserializer {
+	key: String
+}
+
+macro_rules! deserialize_parsed_key {
+	($($method:ident => $visit:ident: $t:ty),*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+				match self.key.parse::<$t>() {
+					Ok(x) => visitor.$visit(x),
+					Err(_) => Err(de::Error::custom(format!("expected a {} object key, found \"{}\"", stringify!($t), self.key)))
+				}
+			}
+		)*
+	};
+}
+
+impl<'de> Deserializer<'de> for KeyDeserializer {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		return visitor.visit_string(self.key);
+	}
+
+	deserialize_parsed_key! {
+		deserialize_bool => visit_bool: bool,
+		deserialize_i8 => visit_i8: i8,
+		deserialize_i16 => visit_i16: i16,
+		deserialize_i32 => visit_i32: i32,
+		deserialize_i64 => visit_i64: i64,
+		deserialize_i128 => visit_i128: i128,
+		deserialize_u8 => visit_u8: u8,
+		deserialize_u16 => visit_u16: u16,
+		deserialize_u32 => visit_u32: u32,
+		deserialize_u64 => visit_u64: u64,
+		deserialize_u128 => visit_u128: u128,
+		deserialize_f32 => visit_f32: f32,
+		deserialize_f64 => visit_f64: f64
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		return visitor.visit_some(self);
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+		return visitor.visit_newtype_struct(self);
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+		return visitor.visit_enum(self.key.into_deserializer());
+	}
+
+	serde::forward_to_deserialize_any! {
+		char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+	}
+}
+
+struct EnumAccess {
+	variant: String,
+	content: Value
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+	type Error = Error;
+	type Variant = VariantAccess;
+
+	fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, VariantAccess), Error> {
+		let variant = seed.deserialize(Value::Text(self.variant))?;
+		return Ok((variant, VariantAccess { content: self.content }));
+	}
+}
+
+struct VariantAccess {
+	content: Value
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess {
+	type Error = Error;
+
+	fn unit_variant(self) -> Result<(), Error> {
+		match self.content {
+			Value::Null => Ok(()),
+			x => Err(de::Error::custom(format!("expected null for a unit variant, found {}", x.type_name())))
+		}
+	}
+
+	fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+		return seed.deserialize(self.content);
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+		return self.content.deserialize_any(visitor);
+	}
+
+	fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+		return self.content.deserialize_any(visitor);
+	}
+}
@@ -0,0 +1,388 @@
+use crate::json::Value;
+pub use crate::json::PathSegment;
+use crate::json::conversion::ConversionError;
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::{TryFrom, TryInto};
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+//Types that can be written out as a json::Value
+pub trait ToJson {
+	fn to_json(&self) -> Value;
+}
+
+//Types that can be read back from a json::Value
+pub trait FromJson: Sized {
+	fn from_json(value: &Value) -> Result<Self, FromJsonError>;
+
+	//Value to use when an object field is missing entirely. Only Option fills this in.
+	fn from_missing() -> Option<Self> {
+		return None;
+	}
+}
+
+//Error returned by FromJson, with the path of the value that failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromJsonError {
+	pub path: Vec<PathSegment>,
+	pub message: String
+}
+
+impl FromJsonError {
+	pub fn new(message: &str) -> FromJsonError {
+		return FromJsonError {
+			path: Vec::new(),
+			message: message.to_string()
+		};
+	}
+
+	pub fn expected(expected: &str, found: &Value) -> FromJsonError {
+		return FromJsonError::new(&format!("expected {}, found {}", expected, found.type_name()));
+	}
+
+	pub fn missing_field(key: &str) -> FromJsonError {
+		return FromJsonError::new(&format!("missing field \"{}\"", key));
+	}
+
+	//Record that the error happened under an object key
+	pub fn with_key(mut self, key: &str) -> FromJsonError {
+		self.path.insert(0, PathSegment::Key(key.to_string()));
+		return self;
+	}
+
+	//Record that the error happened under an array index
+	pub fn with_index(mut self, index: usize) -> FromJsonError {
+		self.path.insert(0, PathSegment::Index(index));
+		return self;
+	}
+
+	//Path of the failing value, like $.users[0].name
+	pub fn path_string(&self) -> String {
+		let mut to_return = String::from("$");
+
+		for segment in &self.path {
+			match segment {
+				PathSegment::Key(x) => {
+					to_return.push('.');
+					to_return.push_str(x);
+				}
+				PathSegment::Index(x) => to_return += &format!("[{}]", x)
+			}
+		}
+
+		return to_return;
+	}
+}
+
+impl fmt::Display for FromJsonError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}: {}", self.path_string(), self.message)
+	}
+}
+
+impl Error for FromJsonError {}
+
+impl From<ConversionError> for FromJsonError {
+	fn from(input: ConversionError) -> Self {
+		return FromJsonError::new(&input.to_string());
+	}
+}
+
+//Scalars go through the From/TryFrom conversions
+macro_rules! scalar_impls {
+	($($t:ty),*) => {
+		$(
+			impl ToJson for $t {
+				fn to_json(&self) -> Value {
+					return Value::from(self.clone());
+				}
+			}
+
+			impl FromJson for $t {
+				fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+					match value {
+						Value::Number(_) | Value::Text(_) | Value::Bool(_) | Value::Null => {
+							return <$t>::try_from(value.clone()).map_err(FromJsonError::from);
+						}
+						_ => return Err(FromJsonError::expected(stringify!($t), value))
+					}
+				}
+			}
+		)*
+	};
+}
+scalar_impls!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64, bool, char, String, ());
+
+impl ToJson for str {
+	fn to_json(&self) -> Value {
+		return Value::from(self);
+	}
+}
+
+impl ToJson for Value {
+	fn to_json(&self) -> Value {
+		return self.clone();
+	}
+}
+
+impl FromJson for Value {
+	fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+		return Ok(value.clone());
+	}
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+	fn to_json(&self) -> Value {
+		return (**self).to_json();
+	}
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+	fn to_json(&self) -> Value {
+		return (**self).to_json();
+	}
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+	fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+		return T::from_json(value).map(Box::new);
+	}
+}
+
+//Option is null when empty, and a missing field reads as None
+impl<T: ToJson> ToJson for Option<T> {
+	fn to_json(&self) -> Value {
+		match self {
+			Some(x) => x.to_json(),
+			None => Value::Null
+		}
+	}
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+	fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+		match value {
+			Value::Null => Ok(None),
+			_ => T::from_json(value).map(Some)
+		}
+	}
+
+	fn from_missing() -> Option<Self> {
+		return Some(None);
+	}
+}
+
+//Arrays
+impl<T: ToJson> ToJson for [T] {
+	fn to_json(&self) -> Value {
+		return Value::Array(self.iter().map(ToJson::to_json).collect());
+	}
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+	fn to_json(&self) -> Value {
+		return self.as_slice().to_json();
+	}
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+	fn to_json(&self) -> Value {
+		return self[..].to_json();
+	}
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+	fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+		match value {
+			Value::Array(x) => {
+				let mut to_return = Vec::with_capacity(x.len());
+				for (i, item) in x.iter().enumerate() {
+					to_return.push(T::from_json(item).map_err(|e| e.with_index(i))?);
+				}
+
+				return Ok(to_return);
+			}
+			_ => Err(FromJsonError::expected("array", value))
+		}
+	}
+}
+
+impl<T: FromJson, const N: usize> FromJson for [T; N] {
+	fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+		let items = Vec::<T>::from_json(value)?;
+		let len = items.len();
+
+		return items.try_into().map_err(|_| {
+			FromJsonError::new(&format!("expected array of length {}, found array of length {}", N, len))
+		});
+	}
+}
+
+//Tuples are fixed length arrays
+macro_rules! tuple_impls {
+	($len:expr => $($name:ident $index:tt)+) => {
+		impl<$($name: ToJson),+> ToJson for ($($name,)+) {
+			fn to_json(&self) -> Value {
+				return Value::Array(vec![$(self.$index.to_json()),+]);
+			}
+		}
+
+		impl<$($name: FromJson),+> FromJson for ($($name,)+) {
+			fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+				let items = __private::array(value, $len)?;
+				return Ok(($(__private::element::<$name>(items, $index)?,)+));
+			}
+		}
+	};
+}
+tuple_impls!(1 => A 0);
+tuple_impls!(2 => A 0 B 1);
+tuple_impls!(3 => A 0 B 1 C 2);
+tuple_impls!(4 => A 0 B 1 C 2 D 3);
+tuple_impls!(5 => A 0 B 1 C 2 D 3 E 4);
+tuple_impls!(6 => A 0 B 1 C 2 D 3 E 4 F 5);
+
+//Objects
+impl<K: AsRef<str>, V: ToJson, S> ToJson for HashMap<K, V, S> {
+	fn to_json(&self) -> Value {
+		return Value::Object(self.iter().map(|(k, v)| (k.as_ref().to_string(), v.to_json())).collect());
+	}
+}
+
+impl<K: AsRef<str>, V: ToJson> ToJson for BTreeMap<K, V> {
+	fn to_json(&self) -> Value {
+		return Value::Object(self.iter().map(|(k, v)| (k.as_ref().to_string(), v.to_json())).collect());
+	}
+}
+
+impl<K, V, S> FromJson for HashMap<K, V, S>
+where
+	K: From<String> + Eq + Hash,
+	V: FromJson,
+	S: BuildHasher + Default
+{
+	fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+		let object = __private::object(value)?;
+		let mut to_return = HashMap::with_capacity_and_hasher(object.len(), S::default());
+
+		for (k, v) in object {
+			to_return.insert(K::from(k.clone()), V::from_json(v).map_err(|e| e.with_key(k))?);
+		}
+
+		return Ok(to_return);
+	}
+}
+
+impl<K, V> FromJson for BTreeMap<K, V>
+where
+	K: From<String> + Ord,
+	V: FromJson
+{
+	fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+		let object = __private::object(value)?;
+		let mut to_return = BTreeMap::new();
+
+		for (k, v) in object {
+			to_return.insert(K::from(k.clone()), V::from_json(v).map_err(|e| e.with_key(k))?);
+		}
+
+		return Ok(to_return);
+	}
+}
+
+//Helpers called from the code generated by #[derive(ToJson, FromJson)]. Not part of the public interface.
+#[doc(hidden)]
+pub mod __private {
+	use super::{FromJson, FromJsonError, ToJson};
+	use crate::json::Value;
+
+	use std::collections::{BTreeMap, HashMap};
+
+	//Types that are always written as a json object. The derive implements it for structs with
+	//named fields, and requires it of flattened fields and internally tagged newtype variants, so
+	//content with nowhere to put its members is a compile error.
+	pub trait ToJsonObject: ToJson {
+		fn to_json_object(&self) -> HashMap<String, Value>;
+	}
+
+	impl<T: ToJsonObject + ?Sized> ToJsonObject for Box<T> {
+		fn to_json_object(&self) -> HashMap<String, Value> {
+			return (**self).to_json_object();
+		}
+	}
+
+	impl<K: AsRef<str>, V: ToJson, S> ToJsonObject for HashMap<K, V, S> {
+		fn to_json_object(&self) -> HashMap<String, Value> {
+			return self.iter().map(|(k, v)| (k.as_ref().to_string(), v.to_json())).collect();
+		}
+	}
+
+	impl<K: AsRef<str>, V: ToJson> ToJsonObject for BTreeMap<K, V> {
+		fn to_json_object(&self) -> HashMap<String, Value> {
+			return self.iter().map(|(k, v)| (k.as_ref().to_string(), v.to_json())).collect();
+		}
+	}
+
+	pub fn object(value: &Value) -> Result<&HashMap<String, Value>, FromJsonError> {
+		match value {
+			Value::Object(x) => Ok(x),
+			_ => Err(FromJsonError::expected("object", value))
+		}
+	}
+
+	pub fn array(value: &Value, len: usize) -> Result<&Vec<Value>, FromJsonError> {
+		match value {
+			Value::Array(x) if x.len() == len => Ok(x),
+			Value::Array(x) => Err(FromJsonError::new(&format!("expected array of length {}, found array of length {}", len, x.len()))),
+			_ => Err(FromJsonError::expected("array", value))
+		}
+	}
+
+	pub fn null(value: &Value) -> Result<(), FromJsonError> {
+		match value {
+			Value::Null => Ok(()),
+			_ => Err(FromJsonError::expected("null", value))
+		}
+	}
+
+	pub fn element<T: FromJson>(items: &[Value], index: usize) -> Result<T, FromJsonError> {
+		return T::from_json(&items[index]).map_err(|e| e.with_index(index));
+	}
+
+	//Read a field, falling back to FromJson::from_missing when it is absent
+	pub fn field<T: FromJson>(object: &HashMap<String, Value>, key: &str) -> Result<T, FromJsonError> {
+		match object.get(key) {
+			Some(x) => T::from_json(x).map_err(|e| e.with_key(key)),
+			None => T::from_missing().ok_or_else(|| FromJsonError::missing_field(key))
+		}
+	}
+
+	//Read a field, calling default when it is absent
+	pub fn field_or<T: FromJson, F: FnOnce() -> T>(object: &HashMap<String, Value>, key: &str, default: F) -> Result<T, FromJsonError> {
+		match object.get(key) {
+			Some(x) => T::from_json(x).map_err(|e| e.with_key(key)),
+			None => Ok(default())
+		}
+	}
+
+	//Read the tag of an internally or adjacently tagged enum
+	pub fn tag<'a>(object: &'a HashMap<String, Value>, key: &str) -> Result<&'a str, FromJsonError> {
+		match object.get(key) {
+			Some(Value::Text(x)) => Ok(x),
+			Some(x) => Err(FromJsonError::expected("text", x).with_key(key)),
+			None => Err(FromJsonError::missing_field(key))
+		}
+	}
+
+	pub fn unknown_variant(name: &str) -> FromJsonError {
+		return FromJsonError::new(&format!("unknown variant \"{}\"", name));
+	}
+
+	//Add the tag of an internally or adjacently tagged enum to the variant's content
+	pub fn tagged(tag: &str, name: &str, mut object: HashMap<String, Value>) -> Value {
+		object.insert(tag.to_string(), Value::from(name));
+		return Value::Object(object);
+	}
+}
@@ -1,2 +1,9 @@
+//The codebase favours explicit returns
+#![allow(clippy::needless_return)]
+
+extern crate jsafe_derive;
+#[cfg(feature = "serde")]
+extern crate serde;
+
 pub mod json;
-pub mod c_bindings;
\ No newline at end of file
+pub mod c_bindings;
@@ -1,10 +1,67 @@
-mod json;
-use json::Value;
+#![allow(clippy::needless_return)]
 
-fn main() {
+extern crate jsafe;
+
+use jsafe::json::Value;
+use jsafe::json::codegen;
+use jsafe::json::parsing;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+const USAGE: &str = "usage: jsafe codegen <file or -> [RootName]";
+
+//Print Rust types modelling a sample document read from a file or stdin
+fn codegen(args: &[String]) -> Result<(), String> {
+	let path = args.first().ok_or_else(|| USAGE.to_string())?;
+	let root_name = args.get(1).map(|x| x.as_str()).unwrap_or("Root");
+
+	let mut text = String::new();
+	if path == "-" {
+		io::stdin().read_to_string(&mut text).map_err(|e| format!("can't read stdin: {}", e))?;
+	} else {
+		text = fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))?;
+	}
+
+	let text = text.trim();
+	if text.is_empty() {
+		return Err(String::from("the sample is empty"));
+	}
+
+	let sample = parsing::from_str(text);
+	if sample == Value::Invalid {
+		return Err(String::from("the sample isn't valid JSON"));
+	}
+
+	print!("{}", codegen::generate_rust(&sample, root_name));
+	return Ok(());
+}
+
+//Without a subcommand, time building a large object
+fn benchmark() {
 	let mut root = Value::obj();
 	root.pre_alloc(1000000);
 	for i in 0..1000000 {
 		root[i.to_string().as_str()] = i.into();
 	}
 }
+
+fn main() {
+	let args: Vec<String> = env::args().skip(1).collect();
+
+	let result = match args.first().map(|x| x.as_str()) {
+		Some("codegen") => codegen(&args[1..]),
+		Some(_) => Err(USAGE.to_string()),
+		None => {
+			benchmark();
+			Ok(())
+		}
+	};
+
+	if let Err(message) = result {
+		eprintln!("jsafe: {}", message);
+		process::exit(1);
+	}
+}
@@ -0,0 +1,85 @@
+//Compiled as both C and C++ by tests/c_header.rs
+#include "jsafe.h"
+
+#include <stdio.h>
+#include <string.h>
+
+#define CHECK(x) do { if (!(x)) { fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #x); return 1; } } while (0)
+
+static bool remove_first(const char* key, size_t index, jsafe_object* member, void* user_data) {
+	(void)key;
+	(void)index;
+	(void)member;
+	return jsafe_a_remove((jsafe_object*)user_data, 0) == JSAFE_OK;
+}
+
+static bool count_members(const char* key, size_t index, jsafe_object* member, void* user_data) {
+	(void)key;
+	(void)index;
+	(void)member;
+	*(size_t*)user_data += 1;
+	return true;
+}
+
+int main(void) {
+	jsafe_object* doc = jsafe_from_str("{\"name\": \"jsafe\", \"tags\": [\"json\", \"c\"], \"ok\": true}");
+	jsafe_value* list;
+	jsafe_iter* iter;
+	jsafe_string* text;
+	size_t len = 0;
+	size_t count = 0;
+
+	CHECK(doc != NULL);
+	CHECK(jsafe_is_obj(doc));
+	CHECK(jsafe_get_len(doc) == 3);
+	CHECK(jsafe_has_key(doc, "tags"));
+
+	iter = jsafe_iter_begin(doc);
+	CHECK(iter != NULL && jsafe_iter_next(iter));
+	text = jsafe_iter_key(iter);
+	CHECK(text != NULL && strcmp(text, "name") == 0);
+	CHECK(jsafe_free_string(text) == JSAFE_OK);
+	CHECK(jsafe_iter_free(iter) == JSAFE_OK);
+	CHECK(jsafe_foreach(doc, count_members, &count) == JSAFE_OK && count == 3);
+
+	CHECK(jsafe_pointer_get(doc, "/tags/1") == JSAFE_OK);
+	text = jsafe_get_text(doc);
+	CHECK(text != NULL && strcmp(text, "c") == 0);
+	CHECK(jsafe_free_string(text) == JSAFE_OK);
+
+	CHECK(jsafe_pointer_get(doc, "/ok") == JSAFE_OK);
+	CHECK(jsafe_get_bool(doc) == true);
+
+	CHECK(jsafe_reset(doc) == JSAFE_OK);
+	list = jsafe_new_arr();
+	CHECK(jsafe_free_detached(jsafe_new_num(1.0)) == JSAFE_OK);
+	CHECK(jsafe_o_add(doc, "list", list) == JSAFE_OK);
+	CHECK(jsafe_o_get(doc, "list") == JSAFE_OK);
+	CHECK(jsafe_a_add(doc, jsafe_new_bool(false)) == JSAFE_OK);
+	CHECK(jsafe_a_add(doc, NULL) == JSAFE_OK);
+	CHECK(jsafe_a_get(doc, 2) == JSAFE_ERR_NOT_FOUND);
+
+	text = jsafe_to_string_len(doc, &len);
+	CHECK(len == strlen("[false,null]") && strcmp(text, "[false,null]") == 0);
+	CHECK(jsafe_free_string(text) == JSAFE_OK);
+
+	//Removing an element ends the walk instead of skipping the element moved into its place
+	CHECK(jsafe_a_add(doc, NULL) == JSAFE_OK);
+	CHECK(jsafe_foreach(doc, remove_first, doc) == JSAFE_OK);
+	CHECK(jsafe_get_len(doc) == 2);
+
+	{
+		jsafe_parse_options options = {1};
+		jsafe_parse_error error;
+		CHECK(jsafe_from_buffer("[[1]]", 5, &options, &error) == NULL);
+		CHECK(error.line == 1 && error.column == 2 && strcmp(error.message, "nesting is too deep") == 0);
+	}
+
+	CHECK(jsafe_from_str("[1,") == NULL);
+	CHECK(jsafe_last_error() == JSAFE_ERR_PARSE);
+	CHECK(strcmp(jsafe_last_error_message(), "unexpected end of input at line 1, column 4") == 0);
+
+	CHECK(jsafe_free_value(doc) == JSAFE_OK);
+	CHECK(jsafe_free_value(doc) == JSAFE_ERR_INVALID_HANDLE);
+	return 0;
+}
@@ -0,0 +1,147 @@
+extern crate jsafe;
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_double, c_void};
+use std::thread;
+
+type Handle = *mut c_void;
+
+extern "C" {
+	fn jsafe_from_str(text: *const c_char) -> Handle;
+	fn jsafe_free_value(this: Handle) -> i32;
+	fn jsafe_free_string(this: *mut c_char) -> i32;
+	fn jsafe_o_get(this: Handle, key: *const c_char) -> i32;
+	fn jsafe_pointer_get(this: Handle, pointer: *const c_char) -> i32;
+	fn jsafe_reset(this: Handle) -> i32;
+	fn jsafe_get_len(this: Handle) -> usize;
+	fn jsafe_set_num(this: Handle, val: c_double) -> i32;
+	fn jsafe_to_string(this: Handle) -> *mut c_char;
+	fn jsafe_clone(this: Handle) -> Handle;
+	fn jsafe_cursor_clone(this: Handle) -> Handle;
+	fn jsafe_o_add_copy(this: Handle, key: *const c_char, source: Handle) -> i32;
+	fn jsafe_a_add_copy(this: Handle, source: Handle) -> i32;
+}
+
+const JSAFE_OK: i32 = 0;
+const JSAFE_ERR_INVALID_HANDLE: i32 = 2;
+const JSAFE_ERR_TYPE: i32 = 6;
+
+fn c(text: &[u8]) -> *const c_char {
+	text.as_ptr() as *const c_char
+}
+
+unsafe fn take(text: *mut c_char) -> String {
+	assert!(!text.is_null());
+	let to_return = CStr::from_ptr(text).to_str().unwrap().to_string();
+	assert_eq!(jsafe_free_string(text), JSAFE_OK);
+	to_return
+}
+
+#[test]
+fn clone_is_independent() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"{\"a\": {\"b\": [1, 2]}}\0"));
+		assert_eq!(jsafe_pointer_get(doc, c(b"/a/b\0")), JSAFE_OK);
+
+		let copy = jsafe_clone(doc);
+		assert_eq!(take(jsafe_to_string(copy)), "[1,2]");
+
+		//The copy starts at its own root and changes don't leak either way
+		assert_eq!(jsafe_pointer_get(copy, c(b"/0\0")), JSAFE_OK);
+		assert_eq!(jsafe_set_num(copy, 9.0), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "[1,2]");
+
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+		assert_eq!(jsafe_reset(copy), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(copy)), "[9,2]");
+		assert_eq!(jsafe_free_value(copy), JSAFE_OK);
+	}
+}
+
+#[test]
+fn copies_between_documents() {
+	unsafe {
+		let cache = jsafe_from_str(c(b"{\"user\": {\"name\": \"ann\"}, \"tags\": [\"x\"]}\0"));
+		let response = jsafe_from_str(c(b"{\"items\": []}\0"));
+
+		assert_eq!(jsafe_o_get(cache, c(b"user\0")), JSAFE_OK);
+		assert_eq!(jsafe_o_add_copy(response, c(b"user\0"), cache), JSAFE_OK);
+
+		assert_eq!(jsafe_pointer_get(cache, c(b"/tags\0")), JSAFE_OK);
+		assert_eq!(jsafe_o_get(response, c(b"items\0")), JSAFE_OK);
+		assert_eq!(jsafe_a_add_copy(response, cache), JSAFE_OK);
+		assert_eq!(jsafe_a_add_copy(response, cache), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(response)), "[[\"x\"],[\"x\"]]");
+
+		//The source handle is still usable and unchanged
+		assert_eq!(jsafe_get_len(cache), 1);
+		assert_eq!(jsafe_free_value(cache), JSAFE_OK);
+
+		assert_eq!(jsafe_pointer_get(response, c(b"/user/name\0")), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(response)), "\"ann\"");
+		assert_eq!(jsafe_free_value(response), JSAFE_OK);
+	}
+}
+
+#[test]
+fn copies_within_a_document() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"[1]\0"));
+
+		//Copying a value into itself copies it as it was before the call
+		assert_eq!(jsafe_a_add_copy(doc, doc), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "[1,[1]]");
+		assert_eq!(jsafe_o_add_copy(doc, c(b"k\0"), doc), JSAFE_ERR_TYPE);
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn freed_sources_are_rejected() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"[]\0"));
+		let source = jsafe_from_str(c(b"1\0"));
+		assert_eq!(jsafe_free_value(source), JSAFE_OK);
+
+		assert_eq!(jsafe_a_add_copy(doc, source), JSAFE_ERR_INVALID_HANDLE);
+		assert!(jsafe_clone(source).is_null());
+		assert_eq!(jsafe_get_len(doc), 0);
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn copying_both_ways_at_once_does_not_deadlock() {
+	unsafe {
+		let a = jsafe_from_str(c(b"{\"v\": 1, \"list\": []}\0"));
+		let b = jsafe_from_str(c(b"{\"v\": 2, \"list\": []}\0"));
+
+		//Each thread copies one document's number into the other's list
+		let cursors: Vec<(usize, usize)> = [(a, b), (b, a)].iter().map(|&(to, from)| {
+			let to = jsafe_cursor_clone(to);
+			let from = jsafe_cursor_clone(from);
+			assert_eq!(jsafe_o_get(to, c(b"list\0")), JSAFE_OK);
+			assert_eq!(jsafe_o_get(from, c(b"v\0")), JSAFE_OK);
+			(to as usize, from as usize)
+		}).collect();
+
+		let threads: Vec<_> = cursors.iter().map(|&(to, from)| {
+			thread::spawn(move || {
+				for _ in 0..500 {
+					assert_eq!(jsafe_a_add_copy(to as Handle, from as Handle), JSAFE_OK);
+				}
+			})
+		}).collect();
+		for thread in threads {
+			thread.join().unwrap();
+		}
+
+		for (to, from) in cursors {
+			assert_eq!(jsafe_get_len(to as Handle), 500);
+			assert_eq!(jsafe_free_value(to as Handle), JSAFE_OK);
+			assert_eq!(jsafe_free_value(from as Handle), JSAFE_OK);
+		}
+		assert_eq!(jsafe_free_value(a), JSAFE_OK);
+		assert_eq!(jsafe_free_value(b), JSAFE_OK);
+	}
+}
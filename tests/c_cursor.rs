@@ -0,0 +1,151 @@
+extern crate jsafe;
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_double, c_void};
+
+type Handle = *mut c_void;
+
+extern "C" {
+	fn jsafe_from_str(text: *const c_char) -> Handle;
+	fn jsafe_free_value(this: Handle) -> i32;
+	fn jsafe_free_string(this: *mut c_char) -> i32;
+	fn jsafe_new_text(text: *const c_char) -> Handle;
+	fn jsafe_new_num(val: c_double) -> Handle;
+	fn jsafe_o_get(this: Handle, key: *const c_char) -> i32;
+	fn jsafe_o_add(this: Handle, key: *const c_char, val: Handle) -> i32;
+	fn jsafe_a_get(this: Handle, index: usize) -> i32;
+	fn jsafe_reset(this: Handle) -> i32;
+	fn jsafe_pointer_get(this: Handle, pointer: *const c_char) -> i32;
+	fn jsafe_get_num(this: Handle) -> c_double;
+	fn jsafe_is_text(this: Handle) -> bool;
+	fn jsafe_to_string(this: Handle) -> *mut c_char;
+	fn jsafe_parent(this: Handle) -> i32;
+	fn jsafe_current_path(this: Handle) -> *mut c_char;
+	fn jsafe_cursor_clone(this: Handle) -> Handle;
+	fn jsafe_last_error() -> i32;
+}
+
+const JSAFE_OK: i32 = 0;
+const JSAFE_ERR_NOT_FOUND: i32 = 7;
+
+fn c(text: &[u8]) -> *const c_char {
+	text.as_ptr() as *const c_char
+}
+
+unsafe fn take(text: *mut c_char) -> String {
+	assert!(!text.is_null());
+	let to_return = CStr::from_ptr(text).to_str().unwrap().to_string();
+	assert_eq!(jsafe_free_string(text), JSAFE_OK);
+	to_return
+}
+
+#[test]
+fn parent_walks_back_up() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"{\"a\": {\"x\": 1, \"y\": 2}, \"b\": [10, 20]}\0"));
+
+		assert_eq!(jsafe_o_get(doc, c(b"a\0")), JSAFE_OK);
+		assert_eq!(jsafe_o_get(doc, c(b"x\0")), JSAFE_OK);
+		assert_eq!(jsafe_get_num(doc), 1.0);
+
+		//Siblings without going back to the root
+		assert_eq!(jsafe_parent(doc), JSAFE_OK);
+		assert_eq!(jsafe_o_get(doc, c(b"y\0")), JSAFE_OK);
+		assert_eq!(jsafe_get_num(doc), 2.0);
+		assert_eq!(take(jsafe_current_path(doc)), "/a/y");
+
+		assert_eq!(jsafe_parent(doc), JSAFE_OK);
+		assert_eq!(jsafe_parent(doc), JSAFE_OK);
+		assert_eq!(take(jsafe_current_path(doc)), "");
+		assert_eq!(jsafe_parent(doc), JSAFE_ERR_NOT_FOUND);
+
+		assert_eq!(jsafe_o_get(doc, c(b"b\0")), JSAFE_OK);
+		assert_eq!(jsafe_a_get(doc, 1), JSAFE_OK);
+		assert_eq!(jsafe_get_num(doc), 20.0);
+		assert_eq!(take(jsafe_current_path(doc)), "/b/1");
+
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn pointers_become_paths() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"{\"m/n\": [{\"~\": 5}]}\0"));
+
+		assert_eq!(jsafe_pointer_get(doc, c(b"/m~1n/0/~0\0")), JSAFE_OK);
+		assert_eq!(jsafe_get_num(doc), 5.0);
+		assert_eq!(take(jsafe_current_path(doc)), "/m~1n/0/~0");
+
+		assert_eq!(jsafe_parent(doc), JSAFE_OK);
+		assert_eq!(jsafe_parent(doc), JSAFE_OK);
+		assert_eq!(take(jsafe_current_path(doc)), "/m~1n");
+
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn clones_move_independently_and_share_changes() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"{\"a\": {\"x\": 1}}\0"));
+		assert_eq!(jsafe_o_get(doc, c(b"a\0")), JSAFE_OK);
+
+		let other = jsafe_cursor_clone(doc);
+		assert_eq!(take(jsafe_current_path(other)), "/a");
+		assert_eq!(jsafe_o_get(other, c(b"x\0")), JSAFE_OK);
+		assert_eq!(take(jsafe_current_path(doc)), "/a");
+
+		//A change through one cursor is seen through the other
+		assert_eq!(jsafe_o_add(doc, c(b"x\0"), jsafe_new_text(c(b"new\0"))), JSAFE_OK);
+		assert!(jsafe_is_text(other));
+
+		//The document lives until its last cursor is freed
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+		assert_eq!(jsafe_reset(other), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(other)), "{\"a\":{\"x\":\"new\"}}");
+		assert_eq!(jsafe_free_value(other), JSAFE_OK);
+	}
+}
+
+#[test]
+fn removed_values_are_reported_not_dangling() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"{\"a\": {\"x\": [1, 2]}}\0"));
+		let deep = jsafe_cursor_clone(doc);
+		assert_eq!(jsafe_pointer_get(deep, c(b"/a/x/1\0")), JSAFE_OK);
+
+		//Replace the subtree the other cursor points into
+		assert_eq!(jsafe_o_add(doc, c(b"a\0"), jsafe_new_num(3.0)), JSAFE_OK);
+
+		assert_eq!(jsafe_get_num(deep), 0.0);
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_NOT_FOUND);
+		assert!(jsafe_to_string(deep).is_null());
+
+		//Moving back up to a value that still exists recovers the cursor
+		assert_eq!(jsafe_parent(deep), JSAFE_OK);
+		assert_eq!(jsafe_parent(deep), JSAFE_OK);
+		assert_eq!(jsafe_get_num(deep), 3.0);
+
+		assert_eq!(jsafe_free_value(deep), JSAFE_OK);
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn adding_a_cursor_copies_its_value() {
+	unsafe {
+		let source = jsafe_from_str(c(b"{\"a\": [1, 2]}\0"));
+		let target = jsafe_from_str(c(b"{}\0"));
+
+		let cursor = jsafe_cursor_clone(source);
+		assert_eq!(jsafe_o_get(cursor, c(b"a\0")), JSAFE_OK);
+		assert_eq!(jsafe_o_add(target, c(b"copy\0"), cursor), JSAFE_OK);
+
+		assert_eq!(take(jsafe_to_string(target)), "{\"copy\":[1,2]}");
+		assert_eq!(take(jsafe_to_string(source)), "{\"a\":[1,2]}");
+
+		assert_eq!(jsafe_free_value(source), JSAFE_OK);
+		assert_eq!(jsafe_free_value(target), JSAFE_OK);
+	}
+}
@@ -0,0 +1,181 @@
+extern crate jsafe;
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_double, c_void};
+use std::ptr::null_mut;
+
+type Handle = *mut c_void;
+
+extern "C" {
+	fn jsafe_from_str(text: *const c_char) -> Handle;
+	fn jsafe_free_value(this: Handle) -> i32;
+	fn jsafe_free_string(this: *mut c_char) -> i32;
+	fn jsafe_new_num(val: c_double) -> Handle;
+	fn jsafe_new_text(text: *const c_char) -> Handle;
+	fn jsafe_o_get(this: Handle, key: *const c_char) -> i32;
+	fn jsafe_a_get(this: Handle, index: usize) -> i32;
+	fn jsafe_reset(this: Handle) -> i32;
+	fn jsafe_pointer_get(this: Handle, pointer: *const c_char) -> i32;
+	fn jsafe_get_num(this: Handle) -> c_double;
+	fn jsafe_get_len(this: Handle) -> usize;
+	fn jsafe_has_key(this: Handle, key: *const c_char) -> bool;
+	fn jsafe_to_string(this: Handle) -> *mut c_char;
+	fn jsafe_current_path(this: Handle) -> *mut c_char;
+	fn jsafe_cursor_clone(this: Handle) -> Handle;
+	fn jsafe_set_num(this: Handle, val: c_double) -> i32;
+	fn jsafe_set_text(this: Handle, text: *const c_char) -> i32;
+	fn jsafe_set_bool(this: Handle, val: bool) -> i32;
+	fn jsafe_set_null(this: Handle) -> i32;
+	fn jsafe_o_remove(this: Handle, key: *const c_char) -> i32;
+	fn jsafe_a_remove(this: Handle, index: usize) -> i32;
+	fn jsafe_a_insert(this: Handle, index: usize, val: Handle) -> i32;
+	fn jsafe_clear(this: Handle) -> i32;
+	fn jsafe_detach(this: Handle, detached: *mut Handle) -> i32;
+}
+
+const JSAFE_OK: i32 = 0;
+const JSAFE_ERR_NULL: i32 = 1;
+const JSAFE_ERR_INVALID_HANDLE: i32 = 2;
+const JSAFE_ERR_UTF8: i32 = 3;
+const JSAFE_ERR_TYPE: i32 = 6;
+const JSAFE_ERR_NOT_FOUND: i32 = 7;
+
+fn c(text: &[u8]) -> *const c_char {
+	text.as_ptr() as *const c_char
+}
+
+unsafe fn take(text: *mut c_char) -> String {
+	assert!(!text.is_null());
+	let to_return = CStr::from_ptr(text).to_str().unwrap().to_string();
+	assert_eq!(jsafe_free_string(text), JSAFE_OK);
+	to_return
+}
+
+#[test]
+fn setters_replace_the_value_under_the_cursor() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"{\"a\": {\"deep\": true}}\0"));
+		assert_eq!(jsafe_o_get(doc, c(b"a\0")), JSAFE_OK);
+
+		assert_eq!(jsafe_set_num(doc, 4.5), JSAFE_OK);
+		assert_eq!(jsafe_get_num(doc), 4.5);
+		assert_eq!(jsafe_set_text(doc, c(b"hi\0")), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "\"hi\"");
+		assert_eq!(jsafe_set_bool(doc, true), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "true");
+		assert_eq!(jsafe_set_null(doc), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "null");
+
+		//A failed call leaves the value alone
+		assert_eq!(jsafe_set_text(doc, std::ptr::null()), JSAFE_ERR_NULL);
+		assert_eq!(jsafe_set_text(doc, c(b"\xff\0")), JSAFE_ERR_UTF8);
+		assert_eq!(take(jsafe_to_string(doc)), "null");
+
+		assert_eq!(jsafe_reset(doc), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "{\"a\":null}");
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn removes_object_members() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"{\"a\": 1, \"b\": 2}\0"));
+
+		assert_eq!(jsafe_o_remove(doc, c(b"a\0")), JSAFE_OK);
+		assert!(!jsafe_has_key(doc, c(b"a\0")));
+		assert_eq!(jsafe_o_remove(doc, c(b"a\0")), JSAFE_ERR_NOT_FOUND);
+		assert_eq!(jsafe_get_len(doc), 1);
+
+		assert_eq!(jsafe_o_get(doc, c(b"b\0")), JSAFE_OK);
+		assert_eq!(jsafe_o_remove(doc, c(b"b\0")), JSAFE_ERR_TYPE);
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn removes_and_inserts_array_items() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"[1, 2, 3]\0"));
+
+		assert_eq!(jsafe_a_remove(doc, 1), JSAFE_OK);
+		assert_eq!(jsafe_a_remove(doc, 2), JSAFE_ERR_NOT_FOUND);
+		assert_eq!(take(jsafe_to_string(doc)), "[1,3]");
+
+		assert_eq!(jsafe_a_insert(doc, 0, jsafe_new_num(0.0)), JSAFE_OK);
+		assert_eq!(jsafe_a_insert(doc, 3, jsafe_new_text(c(b"end\0"))), JSAFE_OK);
+		assert_eq!(jsafe_a_insert(doc, 1, null_mut()), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "[0,null,1,3,\"end\"]");
+
+		//Out of range inserts still consume the value
+		let val = jsafe_new_num(9.0);
+		assert_eq!(jsafe_a_insert(doc, 6, val), JSAFE_ERR_NOT_FOUND);
+		assert_eq!(jsafe_free_value(val), JSAFE_ERR_INVALID_HANDLE);
+		assert_eq!(jsafe_get_len(doc), 5);
+
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn clears_containers() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"{\"a\": [1, 2], \"b\": {\"c\": 1}, \"d\": 5}\0"));
+
+		assert_eq!(jsafe_pointer_get(doc, c(b"/a\0")), JSAFE_OK);
+		assert_eq!(jsafe_clear(doc), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "[]");
+
+		assert_eq!(jsafe_pointer_get(doc, c(b"/b\0")), JSAFE_OK);
+		assert_eq!(jsafe_clear(doc), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "{}");
+
+		assert_eq!(jsafe_pointer_get(doc, c(b"/d\0")), JSAFE_OK);
+		assert_eq!(jsafe_clear(doc), JSAFE_ERR_TYPE);
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn detach_moves_a_subtree_into_a_new_document() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"{\"keep\": 1, \"move\": {\"list\": [1, 2]}}\0"));
+		let other = jsafe_cursor_clone(doc);
+		assert_eq!(jsafe_pointer_get(other, c(b"/move/list\0")), JSAFE_OK);
+
+		assert_eq!(jsafe_o_get(doc, c(b"move\0")), JSAFE_OK);
+		let mut detached: Handle = null_mut();
+		assert_eq!(jsafe_detach(doc, &mut detached), JSAFE_OK);
+
+		//The cursor moved up, and the subtree is an independent document
+		assert_eq!(take(jsafe_current_path(doc)), "");
+		assert_eq!(take(jsafe_to_string(doc)), "{\"keep\":1}");
+		assert_eq!(take(jsafe_to_string(detached)), "{\"list\":[1,2]}");
+		assert_eq!(jsafe_to_string(other), null_mut());
+		assert_eq!(jsafe_free_value(other), JSAFE_OK);
+
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+		assert_eq!(jsafe_a_get(detached, 0), JSAFE_ERR_TYPE);
+		assert_eq!(jsafe_free_value(detached), JSAFE_OK);
+	}
+}
+
+#[test]
+fn detach_needs_a_parent() {
+	unsafe {
+		let doc = jsafe_from_str(c(b"[[1]]\0"));
+		let mut detached: Handle = doc;
+
+		assert_eq!(jsafe_detach(doc, &mut detached), JSAFE_ERR_NOT_FOUND);
+		assert!(detached.is_null());
+		assert_eq!(jsafe_detach(doc, null_mut()), JSAFE_ERR_NULL);
+
+		assert_eq!(jsafe_a_get(doc, 0), JSAFE_OK);
+		assert_eq!(jsafe_detach(doc, &mut detached), JSAFE_OK);
+		assert_eq!(take(jsafe_to_string(doc)), "[]");
+		assert_eq!(take(jsafe_to_string(detached)), "[1]");
+
+		assert_eq!(jsafe_free_value(detached), JSAFE_OK);
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
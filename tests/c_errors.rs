@@ -0,0 +1,123 @@
+extern crate jsafe;
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_double, c_void};
+
+type Handle = *mut c_void;
+
+extern "C" {
+	fn jsafe_new_root() -> Handle;
+	fn jsafe_from_str(text: *const c_char) -> Handle;
+	fn jsafe_free_value(this: Handle) -> i32;
+	fn jsafe_new_text(text: *const c_char) -> Handle;
+	fn jsafe_new_num(val: c_double) -> Handle;
+	fn jsafe_o_get(this: Handle, key: *const c_char) -> i32;
+	fn jsafe_o_add(this: Handle, key: *const c_char, val: Handle) -> i32;
+	fn jsafe_a_get(this: Handle, index: usize) -> i32;
+	fn jsafe_a_add(this: Handle, val: Handle) -> i32;
+	fn jsafe_pointer_get(this: Handle, pointer: *const c_char) -> i32;
+	fn jsafe_has_key(this: Handle, key: *const c_char) -> bool;
+	fn jsafe_get_text(this: Handle) -> *mut c_char;
+	fn jsafe_get_num(this: Handle) -> c_double;
+	fn jsafe_get_len(this: Handle) -> usize;
+	fn jsafe_last_error() -> i32;
+	fn jsafe_last_error_message() -> *const c_char;
+}
+
+const JSAFE_OK: i32 = 0;
+const JSAFE_ERR_NULL: i32 = 1;
+const JSAFE_ERR_INVALID_HANDLE: i32 = 2;
+const JSAFE_ERR_UTF8: i32 = 3;
+const JSAFE_ERR_EMBEDDED_NUL: i32 = 4;
+const JSAFE_ERR_PARSE: i32 = 5;
+const JSAFE_ERR_TYPE: i32 = 6;
+const JSAFE_ERR_NOT_FOUND: i32 = 7;
+
+const BAD_UTF8: &[u8] = b"\xff\xfe\0";
+
+fn c(text: &[u8]) -> *const c_char {
+	text.as_ptr() as *const c_char
+}
+
+unsafe fn last_message() -> String {
+	CStr::from_ptr(jsafe_last_error_message()).to_str().unwrap().to_string()
+}
+
+#[test]
+fn invalid_utf8_is_reported_instead_of_panicking() {
+	unsafe {
+		let root = jsafe_new_root();
+
+		assert_eq!(jsafe_o_get(root, c(BAD_UTF8)), JSAFE_ERR_UTF8);
+		assert!(last_message().starts_with("key isn't valid UTF-8"));
+
+		assert_eq!(jsafe_o_add(root, c(BAD_UTF8), jsafe_new_num(1.0)), JSAFE_ERR_UTF8);
+		assert!(!jsafe_has_key(root, c(BAD_UTF8)));
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_UTF8);
+
+		assert!(jsafe_new_text(c(BAD_UTF8)).is_null());
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_UTF8);
+
+		assert_eq!(jsafe_from_str(c(BAD_UTF8)), std::ptr::null_mut());
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_UTF8);
+
+		assert_eq!(jsafe_o_get(root, std::ptr::null()), JSAFE_ERR_NULL);
+		assert_eq!(last_message(), "key is NULL");
+
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+	}
+}
+
+#[test]
+fn text_with_a_nul_is_reported() {
+	unsafe {
+		let root = jsafe_from_str(c(b"{\"a\":\"x\\u0000y\"}\0"));
+		assert_eq!(jsafe_o_get(root, c(b"a\0")), JSAFE_OK);
+
+		assert!(jsafe_get_text(root).is_null());
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_EMBEDDED_NUL);
+		assert_eq!(last_message(), "text contains a NUL byte at offset 1");
+
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+	}
+}
+
+#[test]
+fn parse_errors_have_positions() {
+	unsafe {
+		assert!(jsafe_from_str(c(b"{\"a\": [1, 2,]}\0")).is_null());
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_PARSE);
+		assert_eq!(last_message(), "expected a value at line 1, column 13");
+
+		let root = jsafe_from_str(c(b"[1, 2]\0"));
+		assert_eq!(jsafe_last_error(), JSAFE_OK);
+		assert_eq!(last_message(), "");
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+	}
+}
+
+#[test]
+fn wrong_types_and_missing_values() {
+	unsafe {
+		let root = jsafe_from_str(c(b"{\"list\": [\"a\"]}\0"));
+
+		assert_eq!(jsafe_a_add(root, jsafe_new_num(1.0)), JSAFE_ERR_TYPE);
+		assert_eq!(last_message(), "expected an array, found object");
+		assert_eq!(jsafe_a_get(root, 0), JSAFE_ERR_TYPE);
+
+		assert_eq!(jsafe_pointer_get(root, c(b"/list/3\0")), JSAFE_ERR_NOT_FOUND);
+		assert_eq!(jsafe_pointer_get(root, c(b"/list\0")), JSAFE_OK);
+		assert_eq!(jsafe_a_get(root, 5), JSAFE_ERR_NOT_FOUND);
+		assert_eq!(last_message(), "index 5 is out of range for an array of 1 items");
+
+		assert_eq!(jsafe_a_get(root, 0), JSAFE_OK);
+		assert_eq!(jsafe_get_num(root), 0.0);
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_TYPE);
+		assert_eq!(jsafe_get_len(root), 1);
+		assert_eq!(jsafe_last_error(), JSAFE_OK);
+
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+		assert_eq!(jsafe_get_len(root), 0);
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_INVALID_HANDLE);
+	}
+}
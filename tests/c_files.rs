@@ -0,0 +1,206 @@
+extern crate jsafe;
+
+use std::env;
+use std::ffi::CStr;
+use std::fs;
+use std::os::raw::{c_char, c_void};
+use std::path::PathBuf;
+use std::ptr::{null, null_mut};
+
+type Handle = *mut c_void;
+
+#[repr(C)]
+struct ParseOptions {
+	max_depth: usize
+}
+
+#[repr(C)]
+struct ParseError {
+	offset: usize,
+	line: usize,
+	column: usize,
+	message: [c_char; 128]
+}
+
+#[repr(C)]
+struct WriteOptions {
+	pretty: bool,
+	spaces: usize
+}
+
+extern "C" {
+	fn jsafe_free_value(this: Handle) -> i32;
+	fn jsafe_free_string(this: *mut c_char) -> i32;
+	fn jsafe_to_string(this: Handle) -> *mut c_char;
+	fn jsafe_o_get(this: Handle, key: *const c_char) -> i32;
+	fn jsafe_from_buffer(text: *const c_char, len: usize, options: *const ParseOptions, error: *mut ParseError) -> Handle;
+	fn jsafe_from_file(path: *const c_char, options: *const ParseOptions, error: *mut ParseError) -> Handle;
+	fn jsafe_to_file(this: Handle, path: *const c_char, options: *const WriteOptions) -> i32;
+	fn jsafe_last_error() -> i32;
+}
+
+const JSAFE_OK: i32 = 0;
+const JSAFE_ERR_NULL: i32 = 1;
+const JSAFE_ERR_PARSE: i32 = 5;
+const JSAFE_ERR_IO: i32 = 9;
+
+fn c(text: &[u8]) -> *const c_char {
+	text.as_ptr() as *const c_char
+}
+
+unsafe fn take(text: *mut c_char) -> String {
+	assert!(!text.is_null());
+	let to_return = CStr::from_ptr(text).to_str().unwrap().to_string();
+	assert_eq!(jsafe_free_string(text), JSAFE_OK);
+	to_return
+}
+
+fn new_error() -> ParseError {
+	ParseError { offset: 99, line: 99, column: 99, message: [1; 128] }
+}
+
+fn message(error: &ParseError) -> String {
+	unsafe { CStr::from_ptr(error.message.as_ptr()).to_str().unwrap().to_string() }
+}
+
+//A path in the temporary directory, as a NUL terminated string
+fn temp_path(name: &str) -> (PathBuf, Vec<u8>) {
+	let path = env::temp_dir().join(format!("jsafe-c-files-{}-{}.json", name, std::process::id()));
+	let mut text = path.to_str().unwrap().as_bytes().to_vec();
+	text.push(0);
+	(path, text)
+}
+
+#[test]
+fn parses_a_buffer_without_a_nul() {
+	unsafe {
+		//Only the JSON at the start of the buffer is passed
+		let text = b"[1, 2]  garbage";
+		let mut error = new_error();
+
+		let doc = jsafe_from_buffer(c(text), 8, null(), &mut error);
+		assert!(!doc.is_null());
+		assert_eq!((error.offset, error.line, error.column, message(&error)), (0, 0, 0, String::new()));
+		assert_eq!(take(jsafe_to_string(doc)), "[1,2]");
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+
+		assert!(jsafe_from_buffer(c(text), text.len(), null(), &mut error).is_null());
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_PARSE);
+		assert_eq!((error.offset, error.line, error.column), (8, 1, 9));
+		assert_eq!(message(&error), "unexpected text after the value");
+
+		assert!(jsafe_from_buffer(null(), 0, null(), null_mut()).is_null());
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_NULL);
+	}
+}
+
+#[test]
+fn reports_where_a_buffer_is_invalid() {
+	unsafe {
+		let text = b"{\n  \"a\": \"\xff\"\n}";
+		let mut error = new_error();
+
+		assert!(jsafe_from_buffer(c(text), text.len(), null(), &mut error).is_null());
+		assert_eq!((error.offset, error.line, error.column), (10, 2, 9));
+		assert_eq!(message(&error), "invalid UTF-8");
+	}
+}
+
+#[test]
+fn depth_limit_comes_from_options() {
+	unsafe {
+		let text = b"[[[]]]";
+		let mut error = new_error();
+
+		let shallow = ParseOptions { max_depth: 2 };
+		assert!(jsafe_from_buffer(c(text), text.len(), &shallow, &mut error).is_null());
+		assert_eq!(message(&error), "nesting is too deep");
+
+		//0 keeps the default
+		let default = ParseOptions { max_depth: 0 };
+		let doc = jsafe_from_buffer(c(text), text.len(), &default, &mut error);
+		assert!(!doc.is_null());
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
+
+#[test]
+fn round_trips_through_a_file() {
+	unsafe {
+		let (path, c_path) = temp_path("round-trip");
+		let text = b"{\"a\": [1, true]}";
+		let doc = jsafe_from_buffer(c(text), text.len(), null(), null_mut());
+
+		assert_eq!(jsafe_to_file(doc, c(&c_path), null()), JSAFE_OK);
+		assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\":[1,true]}\n");
+
+		let pretty = WriteOptions { pretty: true, spaces: 2 };
+		assert_eq!(jsafe_o_get(doc, c(b"a\0")), JSAFE_OK);
+		assert_eq!(jsafe_to_file(doc, c(&c_path), &pretty), JSAFE_OK);
+		assert!(fs::read_to_string(&path).unwrap().starts_with("[\n  1,"));
+
+		let mut error = new_error();
+		let read = jsafe_from_file(c(&c_path), null(), &mut error);
+		assert!(!read.is_null());
+		assert_eq!(message(&error), "");
+		assert_eq!(take(jsafe_to_string(read)), "[1,true]");
+
+		assert_eq!(jsafe_free_value(read), JSAFE_OK);
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+		fs::remove_file(path).unwrap();
+	}
+}
+
+#[test]
+fn strings_are_escaped_when_written() {
+	unsafe {
+		let (path, c_path) = temp_path("escaped");
+		let text = br#"{"a \"key\"": ["quote \" back \\ line\n tab\t", "{not} [an, object]"]}"#;
+		let doc = jsafe_from_buffer(c(text), text.len(), null(), null_mut());
+		assert!(!doc.is_null());
+
+		let compact = r#"{"a \"key\"":["quote \" back \\ line\n tab\t","{not} [an, object]"]}"#;
+		assert_eq!(take(jsafe_to_string(doc)), compact);
+
+		assert_eq!(jsafe_to_file(doc, c(&c_path), null()), JSAFE_OK);
+		assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}\n", compact));
+
+		let pretty = WriteOptions { pretty: true, spaces: 2 };
+		assert_eq!(jsafe_to_file(doc, c(&c_path), &pretty), JSAFE_OK);
+		assert_eq!(fs::read_to_string(&path).unwrap(), concat!(
+			"{\n",
+			"  \"a \\\"key\\\"\":[\n",
+			"    \"quote \\\" back \\\\ line\\n tab\\t\",\n",
+			"    \"{not} [an, object]\"\n",
+			"  ]\n",
+			"}\n"
+		));
+
+		//Both forms read back to the same document
+		let read = jsafe_from_file(c(&c_path), null(), null_mut());
+		assert!(!read.is_null());
+		assert_eq!(take(jsafe_to_string(read)), compact);
+
+		assert_eq!(jsafe_free_value(read), JSAFE_OK);
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+		fs::remove_file(path).unwrap();
+	}
+}
+
+#[test]
+fn missing_files_are_io_errors() {
+	unsafe {
+		let (_, c_path) = temp_path("missing");
+		let mut error = new_error();
+
+		assert!(jsafe_from_file(c(&c_path), null(), &mut error).is_null());
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_IO);
+		assert_eq!((error.offset, error.line, error.column), (0, 0, 0));
+		assert!(message(&error).starts_with("can't read "));
+
+		let doc = jsafe_from_buffer(c(b"1"), 1, null(), null_mut());
+		let (_, c_dir) = temp_path("no-such-dir/file");
+		assert_eq!(jsafe_to_file(doc, c(&c_dir), null()), JSAFE_ERR_IO);
+		assert_eq!(jsafe_free_value(doc), JSAFE_OK);
+	}
+}
@@ -0,0 +1,105 @@
+extern crate jsafe;
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_void};
+use std::thread;
+
+//Handles are opaque to C, so the tests treat them the same way
+type Handle = *mut c_void;
+
+extern "C" {
+	fn jsafe_new_root() -> Handle;
+	fn jsafe_from_str(text: *const c_char) -> Handle;
+	fn jsafe_free_value(this: Handle) -> i32;
+	fn jsafe_free_string(this: *mut c_char) -> i32;
+	fn jsafe_get_len(this: Handle) -> usize;
+	fn jsafe_is_obj(this: Handle) -> bool;
+	fn jsafe_to_string(this: Handle) -> *mut c_char;
+	#[cfg(target_pointer_width = "32")]
+	fn jsafe_last_error() -> i32;
+}
+
+const JSAFE_OK: i32 = 0;
+const JSAFE_ERR_NULL: i32 = 1;
+const JSAFE_ERR_INVALID_HANDLE: i32 = 2;
+#[cfg(target_pointer_width = "32")]
+const JSAFE_ERR_TOO_MANY_HANDLES: i32 = 10;
+
+#[test]
+fn double_free_and_use_after_free_are_detected() {
+	unsafe {
+		let root = jsafe_from_str(b"[1,2,3]\0".as_ptr() as *const c_char);
+		assert_eq!(jsafe_get_len(root), 3);
+
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+		assert_eq!(jsafe_free_value(root), JSAFE_ERR_INVALID_HANDLE);
+		assert_eq!(jsafe_get_len(root), 0);
+		assert!(!jsafe_is_obj(root));
+
+		//The slot is reused, but the old handle still doesn't match it
+		let other = jsafe_new_root();
+		assert!(jsafe_is_obj(other));
+		assert!(!jsafe_is_obj(root));
+		assert_eq!(jsafe_free_value(other), JSAFE_OK);
+
+		assert_eq!(jsafe_free_value(std::ptr::null_mut()), JSAFE_ERR_NULL);
+		assert_eq!(jsafe_free_value(0x12345 as Handle), JSAFE_ERR_INVALID_HANDLE);
+	}
+}
+
+#[test]
+fn strings() {
+	unsafe {
+		let root = jsafe_from_str(b"[true]\0".as_ptr() as *const c_char);
+		let text = jsafe_to_string(root);
+		assert_eq!(CStr::from_ptr(text).to_str(), Ok("[true]"));
+
+		assert_eq!(jsafe_free_string(text), JSAFE_OK);
+		assert_eq!(jsafe_free_string(text), JSAFE_ERR_INVALID_HANDLE);
+		assert_eq!(jsafe_free_string(std::ptr::null_mut()), JSAFE_ERR_NULL);
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+	}
+}
+
+#[test]
+fn many_handles_across_threads() {
+	let workers: Vec<_> = (0..8).map(|_| thread::spawn(|| unsafe {
+		let handles: Vec<usize> = (0..20000).map(|_| jsafe_new_root() as usize).collect();
+		for handle in &handles {
+			assert!(jsafe_is_obj(*handle as Handle));
+		}
+		for handle in handles {
+			assert_eq!(jsafe_free_value(handle as Handle), JSAFE_OK);
+		}
+	})).collect();
+
+	for worker in workers {
+		worker.join().unwrap();
+	}
+}
+
+//A shard only holds 2047 live handles on 32 bit targets (2^27 on 64 bit, too many to test)
+#[cfg(target_pointer_width = "32")]
+#[test]
+fn a_full_shard_is_an_error() {
+	thread::spawn(|| unsafe {
+		let mut handles = Vec::new();
+		loop {
+			let handle = jsafe_new_root();
+			if handle.is_null() {
+				break;
+			}
+			handles.push(handle);
+		}
+
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_TOO_MANY_HANDLES);
+		assert!(handles.len() <= 2047);
+
+		for handle in handles {
+			assert_eq!(jsafe_free_value(handle), JSAFE_OK);
+		}
+		let handle = jsafe_new_root();
+		assert!(!handle.is_null());
+		assert_eq!(jsafe_free_value(handle), JSAFE_OK);
+	}).join().unwrap();
+}
@@ -0,0 +1,65 @@
+//Compile a C program against the generated header, as C and as C++, link it to the cdylib and run it
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+//Directory holding the header generated by build.rs
+fn generated_dir() -> &'static Path {
+	Path::new(env!("OUT_DIR"))
+}
+
+//Directory holding libjsafe.so: cargo builds it next to the test binary in target/<profile>/deps
+fn library_dir() -> PathBuf {
+	let exe = env::current_exe().unwrap();
+	exe.parent().unwrap().to_path_buf()
+}
+
+fn compile_and_run(compiler: &str, flags: &[&str], name: &str) {
+	let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+	let libraries = library_dir();
+	let output = env::temp_dir().join(format!("jsafe_header_test_{}_{}", name, std::process::id()));
+
+	let status = match Command::new(compiler)
+		.args(flags)
+		.arg("-Wall").arg("-Wextra").arg("-Werror")
+		.arg("-I").arg(generated_dir())
+		.arg(root.join("tests/c/header_test.c"))
+		.arg("-o").arg(&output)
+		.arg("-L").arg(&libraries)
+		.arg("-ljsafe")
+		.arg(format!("-Wl,-rpath,{}", libraries.display()))
+		.status()
+	{
+		Ok(x) => x,
+		Err(e) => {
+			eprintln!("skipping {} header test, can't run {}: {}", name, compiler, e);
+			return;
+		}
+	};
+	assert!(status.success(), "{} failed to compile tests/c/header_test.c", compiler);
+
+	let status = Command::new(&output).status().unwrap();
+	let _ = fs::remove_file(&output);
+	assert!(status.success(), "header_test built as {} failed", name);
+}
+
+#[test]
+fn checked_in_header_is_current() {
+	let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+	let generated = fs::read_to_string(generated_dir().join("jsafe.h")).unwrap();
+	let checked_in = fs::read_to_string(root.join("headers/jsafe.h")).unwrap();
+
+	assert!(checked_in == generated, "headers/jsafe.h is out of date, copy {} over it", generated_dir().join("jsafe.h").display());
+}
+
+#[test]
+fn header_compiles_as_c() {
+	compile_and_run(&env::var("CC").unwrap_or_else(|_| String::from("cc")), &["-x", "c", "-std=c99", "-pedantic"], "c");
+}
+
+#[test]
+fn header_compiles_as_cpp() {
+	compile_and_run(&env::var("CXX").unwrap_or_else(|_| String::from("c++")), &["-x", "c++", "-std=c++11"], "cpp");
+}
@@ -0,0 +1,183 @@
+extern crate jsafe;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_double, c_void};
+use std::sync::Mutex;
+
+//Leak checker: counts the bytes each thread has allocated and not yet freed
+struct Counting;
+
+thread_local! {
+	static LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+fn track(delta: isize) {
+	let _ = LIVE.try_with(|x| x.set(x.get() + delta));
+}
+
+unsafe impl GlobalAlloc for Counting {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		track(layout.size() as isize);
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		track(-(layout.size() as isize));
+		System.dealloc(ptr, layout)
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		track(new_size as isize - layout.size() as isize);
+		System.realloc(ptr, layout, new_size)
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+//The handle table is shared, so tests that measure it run one at a time
+static SERIAL: Mutex<()> = Mutex::new(());
+
+//Run a scenario once so the handle table and error state reach their steady size, then check a
+//second run frees everything it allocates
+fn assert_no_leaks<F: Fn()>(scenario: F) {
+	let _lock = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+
+	scenario();
+	let before = LIVE.with(|x| x.get());
+	scenario();
+	let after = LIVE.with(|x| x.get());
+
+	assert_eq!(after - before, 0, "scenario leaked {} bytes", after - before);
+}
+
+type Handle = *mut c_void;
+
+extern "C" {
+	fn jsafe_new_root() -> Handle;
+	fn jsafe_from_str(text: *const c_char) -> Handle;
+	fn jsafe_new_obj() -> Handle;
+	fn jsafe_new_arr() -> Handle;
+	fn jsafe_new_text(text: *const c_char) -> Handle;
+	fn jsafe_new_null() -> Handle;
+	fn jsafe_new_bool(val: bool) -> Handle;
+	fn jsafe_new_num(val: c_double) -> Handle;
+	fn jsafe_free_value(this: Handle) -> i32;
+	fn jsafe_free_string(this: *mut c_char) -> i32;
+	fn jsafe_o_get(this: Handle, key: *const c_char) -> i32;
+	fn jsafe_o_add(this: Handle, key: *const c_char, val: Handle) -> i32;
+	fn jsafe_a_add(this: Handle, val: Handle) -> i32;
+	fn jsafe_reset(this: Handle) -> i32;
+	fn jsafe_to_string(this: Handle) -> *mut c_char;
+	fn jsafe_get_text(this: Handle) -> *mut c_char;
+	fn jsafe_is_null(this: Handle) -> bool;
+}
+
+const JSAFE_OK: i32 = 0;
+const JSAFE_ERR_UTF8: i32 = 3;
+const JSAFE_ERR_INVALID_HANDLE: i32 = 2;
+const JSAFE_ERR_TYPE: i32 = 6;
+
+fn c(text: &[u8]) -> *const c_char {
+	text.as_ptr() as *const c_char
+}
+
+unsafe fn constructors() -> Vec<Handle> {
+	vec![
+		jsafe_new_obj(),
+		jsafe_new_arr(),
+		jsafe_new_text(c(b"text\0")),
+		jsafe_new_text(std::ptr::null()),
+		jsafe_new_null(),
+		jsafe_new_bool(true),
+		jsafe_new_num(2.5),
+		jsafe_new_root(),
+		jsafe_from_str(c(b"{\"a\": [1, \"b\", {\"c\": null}]}\0"))
+	]
+}
+
+#[test]
+fn detached_values_can_be_freed() {
+	assert_no_leaks(|| unsafe {
+		for value in constructors() {
+			assert!(!value.is_null());
+			assert_eq!(jsafe_free_value(value), JSAFE_OK);
+			assert_eq!(jsafe_free_value(value), JSAFE_ERR_INVALID_HANDLE);
+		}
+	});
+}
+
+#[test]
+fn added_values_belong_to_the_document() {
+	assert_no_leaks(|| unsafe {
+		let root = jsafe_new_root();
+		assert_eq!(jsafe_o_add(root, c(b"list\0"), jsafe_new_arr()), JSAFE_OK);
+
+		for (i, value) in constructors().into_iter().enumerate() {
+			let key = format!("{}\0", i);
+			assert_eq!(jsafe_o_add(root, c(key.as_bytes()), value), JSAFE_OK);
+
+			//The value now lives in the document, so its handle is gone
+			assert_eq!(jsafe_free_value(value), JSAFE_ERR_INVALID_HANDLE);
+		}
+
+		assert_eq!(jsafe_o_get(root, c(b"list\0")), JSAFE_OK);
+		for value in constructors() {
+			assert_eq!(jsafe_a_add(root, value), JSAFE_OK);
+		}
+
+		let text = jsafe_to_string(root);
+		assert!(CStr::from_ptr(text).to_str().unwrap().starts_with("[{}"));
+		assert_eq!(jsafe_free_string(text), JSAFE_OK);
+
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+	});
+}
+
+#[test]
+fn null_adds_a_null_value() {
+	assert_no_leaks(|| unsafe {
+		let root = jsafe_from_str(c(b"{\"list\": []}\0"));
+
+		assert_eq!(jsafe_o_add(root, c(b"a\0"), std::ptr::null_mut()), JSAFE_OK);
+		assert_eq!(jsafe_o_get(root, c(b"a\0")), JSAFE_OK);
+		assert!(jsafe_is_null(root));
+
+		assert_eq!(jsafe_reset(root), JSAFE_OK);
+		assert_eq!(jsafe_o_get(root, c(b"list\0")), JSAFE_OK);
+		assert_eq!(jsafe_a_add(root, std::ptr::null_mut()), JSAFE_OK);
+
+		assert_eq!(jsafe_reset(root), JSAFE_OK);
+		let text = jsafe_to_string(root);
+		let text_str = CStr::from_ptr(text).to_str().unwrap();
+		assert!(text_str.contains("\"list\":[null]") && text_str.contains("\"a\":null"));
+		assert_eq!(jsafe_free_string(text), JSAFE_OK);
+
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+	});
+}
+
+#[test]
+fn failed_adds_still_consume_the_value() {
+	assert_no_leaks(|| unsafe {
+		let root = jsafe_new_root();
+
+		let value = jsafe_new_num(1.0);
+		assert_eq!(jsafe_a_add(root, value), JSAFE_ERR_TYPE);
+		assert_eq!(jsafe_free_value(value), JSAFE_ERR_INVALID_HANDLE);
+
+		let value = jsafe_new_text(c(b"x\0"));
+		assert_eq!(jsafe_o_add(root, c(b"\xff\0"), value), JSAFE_ERR_UTF8);
+		assert_eq!(jsafe_free_value(value), JSAFE_ERR_INVALID_HANDLE);
+
+		//Adding a document to itself is refused without consuming it
+		assert_eq!(jsafe_o_add(root, c(b"self\0"), root), JSAFE_ERR_INVALID_HANDLE);
+
+		let text = jsafe_get_text(root);
+		assert!(text.is_null());
+
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+	});
+}
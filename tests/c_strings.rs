@@ -0,0 +1,91 @@
+extern crate jsafe;
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_void};
+use std::slice;
+
+type Handle = *mut c_void;
+
+extern "C" {
+	fn jsafe_from_str(text: *const c_char) -> Handle;
+	fn jsafe_free_value(this: Handle) -> i32;
+	fn jsafe_free_string(this: *mut c_char) -> i32;
+	fn jsafe_o_get(this: Handle, key: *const c_char) -> i32;
+	fn jsafe_reset(this: Handle) -> i32;
+	fn jsafe_to_string(this: Handle) -> *mut c_char;
+	fn jsafe_to_string_len(this: Handle, len: *mut usize) -> *mut c_char;
+	fn jsafe_get_text(this: Handle) -> *mut c_char;
+	fn jsafe_get_text_len(this: Handle, len: *mut usize) -> *mut c_char;
+	fn jsafe_to_pretty(this: Handle, spaces: usize) -> *mut c_char;
+	fn jsafe_to_pretty_len(this: Handle, spaces: usize, len: *mut usize) -> *mut c_char;
+	fn jsafe_last_error() -> i32;
+}
+
+const JSAFE_OK: i32 = 0;
+const JSAFE_ERR_NULL: i32 = 1;
+const JSAFE_ERR_INVALID_HANDLE: i32 = 2;
+const JSAFE_ERR_EMBEDDED_NUL: i32 = 4;
+
+fn c(text: &[u8]) -> *const c_char {
+	text.as_ptr() as *const c_char
+}
+
+//Read a returned string and free it
+unsafe fn take(text: *mut c_char) -> String {
+	assert!(!text.is_null());
+	let to_return = CStr::from_ptr(text).to_str().unwrap().to_string();
+	assert_eq!(jsafe_free_string(text), JSAFE_OK);
+	to_return
+}
+
+#[test]
+fn strings_are_plain_char_pointers() {
+	unsafe {
+		let root = jsafe_from_str(c(b"{\"a\": \"hello\"}\0"));
+
+		assert_eq!(take(jsafe_to_string(root)), "{\"a\":\"hello\"}");
+		assert_eq!(take(jsafe_to_pretty(root, 2)), "{\n  \"a\":\"hello\"\n}");
+
+		assert_eq!(jsafe_o_get(root, c(b"a\0")), JSAFE_OK);
+		let text = jsafe_get_text(root);
+		assert_eq!(*text as u8, b'h');
+		assert_eq!(take(text), "hello");
+
+		assert_eq!(jsafe_free_string(text), JSAFE_ERR_INVALID_HANDLE);
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+	}
+}
+
+#[test]
+fn length_variants_keep_embedded_nuls() {
+	unsafe {
+		let root = jsafe_from_str(c(b"{\"a\": \"x\\u0000y\"}\0"));
+
+		//Serialized JSON escapes the NUL, so it never contains one
+		let mut len = 0;
+		let text = jsafe_to_string_len(root, &mut len);
+		assert_eq!(slice::from_raw_parts(text as *const u8, len + 1), b"{\"a\":\"x\\u0000y\"}\0");
+		assert_eq!(jsafe_free_string(text), JSAFE_OK);
+
+		let text = jsafe_to_pretty_len(root, 1, &mut len);
+		assert_eq!(slice::from_raw_parts(text as *const u8, len), b"{\n \"a\":\"x\\u0000y\"\n}");
+		assert_eq!(jsafe_free_string(text), JSAFE_OK);
+
+		assert_eq!(take(jsafe_to_string(root)), "{\"a\":\"x\\u0000y\"}");
+
+		assert_eq!(jsafe_o_get(root, c(b"a\0")), JSAFE_OK);
+		let text = jsafe_get_text_len(root, &mut len);
+		assert_eq!(slice::from_raw_parts(text as *const u8, len), b"x\0y");
+		assert_eq!(jsafe_free_string(text), JSAFE_OK);
+
+		assert!(jsafe_get_text(root).is_null());
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_EMBEDDED_NUL);
+
+		assert!(jsafe_get_text_len(root, std::ptr::null_mut()).is_null());
+		assert_eq!(jsafe_last_error(), JSAFE_ERR_NULL);
+
+		assert_eq!(jsafe_reset(root), JSAFE_OK);
+		assert!(jsafe_get_text_len(root, &mut len).is_null());
+		assert_eq!(jsafe_free_value(root), JSAFE_OK);
+	}
+}
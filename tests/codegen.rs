@@ -0,0 +1,169 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::codegen::generate_rust;
+use jsafe::json::{FromJson, ToJson, Value};
+
+const EXPECTED: &str = "use jsafe::json::{FromJson, ToJson, Value};
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct Order {
+	pub entries: Vec<Entry>,
+	#[json(rename = \"home-address\")]
+	pub home_address: HomeAddress,
+	#[json(rename = \"type\")]
+	pub type_: String,
+	#[json(rename = \"userId\")]
+	pub user_id: i64
+}
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct Entry {
+	pub extra: Option<bool>,
+	pub id: IdValue,
+	pub note: Option<Value>,
+	pub price: f64
+}
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+#[json(untagged)]
+pub enum IdValue {
+	Integer(i64),
+	Text(String)
+}
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct HomeAddress {
+	pub city: String
+}
+";
+
+//The types from EXPECTED, to check that generated code compiles and reads the sample
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct Order {
+	pub entries: Vec<Entry>,
+	#[json(rename = "home-address")]
+	pub home_address: HomeAddress,
+	#[json(rename = "type")]
+	pub type_: String,
+	#[json(rename = "userId")]
+	pub user_id: i64
+}
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct Entry {
+	pub extra: Option<bool>,
+	pub id: IdValue,
+	pub note: Option<Value>,
+	pub price: f64
+}
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+#[json(untagged)]
+pub enum IdValue {
+	Integer(i64),
+	Text(String)
+}
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct HomeAddress {
+	pub city: String
+}
+
+fn sample() -> Value {
+	json!({
+		"userId": 1.0,
+		"type": "online",
+		"entries": [{"id": 1.0, "note": null, "price": 2.0}, {"id": "2", "extra": true, "price": 2.5}],
+		"home-address": {"city": "x"}
+	})
+}
+
+#[test]
+fn generates_types() {
+	assert_eq!(generate_rust(&sample(), "order"), EXPECTED);
+
+	let order = Order::from_json(&sample()).unwrap();
+	assert_eq!(order.entries[1].id, IdValue::Text(String::from("2")));
+	assert_eq!(order.to_json(), json!({
+		"userId": 1.0,
+		"type": "online",
+		"entries": [{"id": 1.0, "note": null, "extra": null, "price": 2.0}, {"id": "2", "note": null, "extra": true, "price": 2.5}],
+		"home-address": {"city": "x"}
+	}));
+}
+
+#[test]
+fn non_object_roots() {
+	assert_eq!(
+		generate_rust(&json!([{"user_name": "a"}]), "Users"),
+		"use jsafe::json::{FromJson, ToJson};\n\npub type Users = Vec<User>;\n\n#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]\npub struct User {\n\tpub user_name: String\n}\n"
+	);
+	assert_eq!(generate_rust(&json!(null), "Root"), "use jsafe::json::{FromJson, ToJson, Value};\n\npub type Root = Option<Value>;\n");
+}
+
+const RENAMED: &str = "use jsafe::json::{FromJson, ToJson, Value};
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct Option2 {
+	pub string: String2,
+	pub value: Value2,
+	pub vec: Vec<VecItem>
+}
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct String2 {
+	#[json(rename = \"yield\")]
+	pub yield_: bool
+}
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct Value2 {
+	#[json(rename = \"try\")]
+	pub try_: i64
+}
+
+#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+pub struct VecItem {
+	#[json(rename = \"box\")]
+	pub box_: Option<Value>
+}
+";
+
+//The types from RENAMED, checking that they don't clash with the import or shadow std types
+mod renamed {
+	use jsafe::json::{FromJson, ToJson, Value};
+
+	#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+	pub struct Option2 {
+		pub string: String2,
+		pub value: Value2,
+		pub vec: Vec<VecItem>
+	}
+
+	#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+	pub struct String2 {
+		#[json(rename = "yield")]
+		pub yield_: bool
+	}
+
+	#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+	pub struct Value2 {
+		#[json(rename = "try")]
+		pub try_: i64
+	}
+
+	#[derive(Debug, Clone, PartialEq, ToJson, FromJson)]
+	pub struct VecItem {
+		#[json(rename = "box")]
+		pub box_: Option<Value>
+	}
+}
+
+#[test]
+fn names_avoid_keywords_and_imports() {
+	let sample = json!({"value": {"try": 1.0}, "string": {"yield": true}, "vec": [{"box": null}]});
+
+	assert_eq!(generate_rust(&sample, "option"), RENAMED);
+	assert_eq!(renamed::Option2::from_json(&sample).unwrap().to_json(), sample);
+}
@@ -0,0 +1,87 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::Value;
+use jsafe::json::conversion::ConversionError;
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+
+#[test]
+fn std_types_into_value() {
+	assert_eq!(Value::from(3i32).to_string(), "3");
+	assert_eq!(Value::from(3u64).to_string(), "3");
+	assert_eq!(Value::from(3usize).to_string(), "3");
+	assert_eq!(Value::from(0.5f32).to_string(), "0.5");
+	assert_eq!(Value::from('c').to_string(), "\"c\"");
+	assert_eq!(Value::from(None::<i32>).to_string(), "null");
+	assert_eq!(Value::from(Some(true)).to_string(), "true");
+	assert_eq!(Value::from(vec![1, 2]).to_string(), "[1,2]");
+	assert_eq!(Value::from(&["a", "b"][..]).to_string(), "[\"a\",\"b\"]");
+	assert_eq!(Value::from([1u8, 2u8]).to_string(), "[1,2]");
+	assert_eq!(Value::from((1, "two", false)).to_string(), "[1,\"two\",false]");
+
+	let mut tree = BTreeMap::new();
+	tree.insert("a", vec![Some(1)]);
+	assert_eq!(Value::from(tree).to_string(), "{\"a\":[1]}");
+
+	let mut map = HashMap::new();
+	map.insert(String::from("b"), 'x');
+	assert_eq!(Value::from(map).to_string(), "{\"b\":\"x\"}");
+
+	let collected: Value = (0..3).map(|x| x * 2).collect();
+	assert_eq!(collected.to_string(), "[0,2,4]");
+}
+
+#[test]
+fn value_into_std_types() {
+	assert_eq!(i32::try_from(json!(-4.0)), Ok(-4));
+	assert!(u8::try_from(json!(256.0)).is_err());
+	assert!(u64::try_from(json!(1.5)).is_err());
+	assert_eq!(char::try_from(json!("z")), Ok('z'));
+	assert_eq!(Option::<String>::try_from(json!(null)), Ok(None));
+	assert_eq!(Vec::<f64>::try_from(json!([1.5, 2.0])), Ok(vec![1.5, 2.0]));
+	assert_eq!(<[bool; 2]>::try_from(json!([true, false])), Ok([true, false]));
+	assert_eq!(<(i64, String)>::try_from(json!([1.0, "x"])), Ok((1, String::from("x"))));
+
+	let map = BTreeMap::<String, u32>::try_from(json!({"a": 1.0, "b": 2.0})).unwrap();
+	assert_eq!(map["a"], 1);
+	assert_eq!(map["b"], 2);
+
+	let map = HashMap::<String, Option<bool>>::try_from(json!({"a": null})).unwrap();
+	assert_eq!(map["a"], None);
+}
+
+#[test]
+fn containers_of_values() {
+	assert_eq!(Vec::<Value>::try_from(json!([1.0, "a"])), Ok(vec![json!(1.0), json!("a")]));
+	assert!(Vec::<Value>::try_from(json!({})).is_err());
+	assert_eq!(<(Value, bool)>::try_from(json!([[], true])), Ok((json!([]), true)));
+
+	let map = HashMap::<String, Value>::try_from(json!({"a": [1.0], "b": null})).unwrap();
+	assert_eq!(map["a"], json!([1.0]));
+	assert_eq!(map["b"], json!(null));
+
+	let map = BTreeMap::<String, Vec<Value>>::try_from(json!({"a": [true]})).unwrap();
+	assert_eq!(map["a"], vec![json!(true)]);
+}
+
+#[test]
+fn conversion_errors() {
+	assert_eq!(bool::try_from(json!("true")), Err(ConversionError {
+		expected: String::from("bool"),
+		found: String::from("text")
+	}));
+
+	assert_eq!(u8::try_from(json!(256.0)), Err(ConversionError {
+		expected: String::from("u8"),
+		found: String::from("number out of range")
+	}));
+	assert_eq!(i32::try_from(json!(1.5)).unwrap_err().to_string(), "expected i32, found fractional number");
+	assert_eq!(u64::try_from(Value::Number(f64::NAN)).unwrap_err().to_string(), "expected u64, found non-finite number");
+	assert_eq!(i8::try_from(Value::Number(f64::INFINITY)).unwrap_err().found, "non-finite number");
+	assert_eq!(i64::try_from(Value::Number(f64::NEG_INFINITY)).unwrap_err().found, "non-finite number");
+
+	let err = <(i32, i32)>::try_from(json!([1.0])).unwrap_err();
+	assert_eq!(err.to_string(), "expected array of length 2, found array of length 1");
+}
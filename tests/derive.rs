@@ -0,0 +1,218 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::{FromJson, ToJson, Value};
+
+use std::collections::BTreeMap;
+
+#[derive(ToJson, FromJson, Debug, Clone, PartialEq)]
+struct Address {
+	city: String,
+	#[json(rename = "zip")]
+	postal_code: String
+}
+
+fn default_port() -> u16 {
+	8080
+}
+
+#[derive(ToJson, FromJson, Debug, PartialEq)]
+struct Server {
+	name: String,
+	#[json(default = "default_port")]
+	port: u16,
+	#[json(default)]
+	tags: Vec<String>,
+	nickname: Option<String>,
+	#[json(skip)]
+	connections: u32,
+	#[json(flatten)]
+	address: Address
+}
+
+#[derive(ToJson, FromJson, Debug, PartialEq)]
+struct Wrapper<T>(T);
+
+#[derive(ToJson, FromJson, Debug, PartialEq)]
+struct Point(f64, f64);
+
+#[derive(ToJson, FromJson, Debug, PartialEq)]
+enum External {
+	Empty,
+	#[json(rename = "value")]
+	Single(i32),
+	Pair(i32, i32),
+	Named { x: i32 }
+}
+
+#[derive(ToJson, FromJson, Debug, PartialEq)]
+#[json(tag = "type")]
+enum Internal {
+	Ping,
+	Move { x: i32, y: i32 },
+	Wrapped(Address)
+}
+
+//Newtype variants of an internally tagged enum must hold something written as an object
+#[derive(ToJson, FromJson, Debug, PartialEq)]
+#[json(tag = "kind")]
+enum Boxed {
+	Address(Box<Address>),
+	Labels(BTreeMap<String, String>)
+}
+
+#[derive(ToJson, FromJson, Debug, PartialEq)]
+struct Labelled {
+	id: u32,
+	#[json(flatten)]
+	labels: BTreeMap<String, String>
+}
+
+#[derive(ToJson, FromJson, Debug, PartialEq)]
+#[json(tag = "t", content = "c")]
+enum Adjacent {
+	Stop,
+	Go(f64),
+	Turn { degrees: f64 }
+}
+
+#[derive(ToJson, FromJson, Debug, PartialEq)]
+#[json(untagged)]
+enum Untagged {
+	Number(f64),
+	Text(String),
+	Both { number: f64, text: String }
+}
+
+#[test]
+fn struct_round_trip() {
+	let server = Server {
+		name: String::from("api"),
+		port: 1,
+		tags: vec![String::from("a")],
+		nickname: None,
+		connections: 4,
+		address: Address {
+			city: String::from("Paris"),
+			postal_code: String::from("75001")
+		}
+	};
+
+	let value = server.to_json();
+	assert_eq!(value, json!({
+		"name": "api",
+		"port": 1.0,
+		"tags": ["a"],
+		"nickname": null,
+		"city": "Paris",
+		"zip": "75001"
+	}));
+
+	let back = Server::from_json(&value).unwrap();
+	assert_eq!(back.connections, 0);
+	assert_eq!(back.address, server.address);
+}
+
+#[test]
+fn defaults_and_optional_fields() {
+	let server = Server::from_json(&json!({"name": "api", "city": "Oslo", "zip": "0150"})).unwrap();
+
+	assert_eq!(server.port, 8080);
+	assert_eq!(server.tags, Vec::<String>::new());
+	assert_eq!(server.nickname, None);
+}
+
+#[test]
+fn tuple_structs() {
+	assert_eq!(Wrapper(true).to_json(), json!(true));
+	assert_eq!(Wrapper::<bool>::from_json(&json!(false)), Ok(Wrapper(false)));
+	assert_eq!(Point(1.0, 2.0).to_json(), json!([1.0, 2.0]));
+	assert_eq!(Point::from_json(&json!([3.0, 4.0])), Ok(Point(3.0, 4.0)));
+}
+
+#[test]
+fn externally_tagged_enum() {
+	let cases = vec![
+		(External::Empty, json!("Empty")),
+		(External::Single(1), json!({"value": 1.0})),
+		(External::Pair(1, 2), json!({"Pair": [1.0, 2.0]})),
+		(External::Named { x: 3 }, json!({"Named": {"x": 3.0}}))
+	];
+
+	for (variant, value) in cases {
+		assert_eq!(variant.to_json(), value);
+		assert_eq!(External::from_json(&value), Ok(variant));
+	}
+}
+
+#[test]
+fn internally_tagged_enum() {
+	let cases = vec![
+		(Internal::Ping, json!({"type": "Ping"})),
+		(Internal::Move { x: 1, y: 2 }, json!({"type": "Move", "x": 1.0, "y": 2.0})),
+		(Internal::Wrapped(Address { city: String::from("Rome"), postal_code: String::from("00100") }),
+			json!({"type": "Wrapped", "city": "Rome", "zip": "00100"}))
+	];
+
+	for (variant, value) in cases {
+		assert_eq!(variant.to_json(), value);
+		assert_eq!(Internal::from_json(&value), Ok(variant));
+	}
+}
+
+#[test]
+fn internally_tagged_newtypes_of_objects() {
+	let address = Address { city: String::from("Rome"), postal_code: String::from("00100") };
+	let value = json!({"kind": "Address", "city": "Rome", "zip": "00100"});
+	assert_eq!(Boxed::Address(Box::new(address.clone())).to_json(), value);
+	assert_eq!(Boxed::from_json(&value), Ok(Boxed::Address(Box::new(address))));
+
+	let labels: BTreeMap<String, String> = vec![(String::from("env"), String::from("prod"))].into_iter().collect();
+	assert_eq!(Boxed::Labels(labels.clone()).to_json(), json!({"kind": "Labels", "env": "prod"}));
+
+	//Flattened maps merge their entries into the parent
+	let labelled = Labelled { id: 1, labels };
+	assert_eq!(labelled.to_json(), json!({"id": 1.0, "env": "prod"}));
+}
+
+#[test]
+fn adjacently_tagged_enum() {
+	let cases = vec![
+		(Adjacent::Stop, json!({"t": "Stop"})),
+		(Adjacent::Go(1.5), json!({"t": "Go", "c": 1.5})),
+		(Adjacent::Turn { degrees: 90.0 }, json!({"t": "Turn", "c": {"degrees": 90.0}}))
+	];
+
+	for (variant, value) in cases {
+		assert_eq!(variant.to_json(), value);
+		assert_eq!(Adjacent::from_json(&value), Ok(variant));
+	}
+}
+
+#[test]
+fn untagged_enum() {
+	assert_eq!(Untagged::from_json(&json!(1.0)), Ok(Untagged::Number(1.0)));
+	assert_eq!(Untagged::from_json(&json!("a")), Ok(Untagged::Text(String::from("a"))));
+	assert_eq!(Untagged::Both { number: 1.0, text: String::from("b") }.to_json(), json!({"number": 1.0, "text": "b"}));
+	assert!(Untagged::from_json(&json!(null)).is_err());
+}
+
+#[test]
+fn errors_report_path() {
+	let value: Value = json!({"servers": [{"name": "a", "city": "x", "zip": "y"}, {"name": 5.0, "city": "x", "zip": "y"}]});
+
+	#[derive(FromJson, Debug)]
+	struct Config {
+		#[allow(dead_code)]
+		servers: Vec<Server>
+	}
+
+	let err = Config::from_json(&value).unwrap_err();
+	assert_eq!(err.to_string(), "$.servers[1].name: expected text, found number");
+
+	let err = Adjacent::from_json(&json!({"t": "Go", "c": "fast"})).unwrap_err();
+	assert_eq!(err.path_string(), "$.c");
+
+	let err = Server::from_json(&json!({"city": "x", "zip": "y"})).unwrap_err();
+	assert_eq!(err.to_string(), "$: missing field \"name\"");
+}
@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::diff;
+use jsafe::json::diff::{diff_with, render, Change, DiffOptions};
+
+#[test]
+fn changes_are_keyed_by_pointer() {
+	let a = json!({"name": "ana", "age": 30.0, "tags": ["a", "b"], "old": true, "a/b": 1.0});
+	let b = json!({"name": "bob", "age": "30", "tags": ["a"], "new": null, "a/b": 1.0});
+
+	assert_eq!(diff(&a, &b), vec![
+		Change::ChangedType { pointer: String::from("/age"), from: json!(30.0), to: json!("30") },
+		Change::ChangedValue { pointer: String::from("/name"), from: json!("ana"), to: json!("bob") },
+		Change::Added { pointer: String::from("/new"), value: json!(null) },
+		Change::Removed { pointer: String::from("/old"), value: json!(true) },
+		Change::Removed { pointer: String::from("/tags/1"), value: json!("b") }
+	]);
+	assert!(diff(&a, &a).is_empty());
+}
+
+#[test]
+fn options() {
+	let a = json!({"x": 0.1, "list": [1.0, 2.0, 3.0]});
+	let b = json!({"x": 0.10000001, "list": [3.0, 1.0, 2.0]});
+
+	assert_eq!(diff(&a, &b).len(), 4);
+
+	let options = DiffOptions { float_tolerance: 1e-6, ignore_array_order: true };
+	assert!(diff_with(&a, &b, &options).is_empty());
+
+	let c = json!({"x": 0.1, "list": [2.0, 4.0, 1.0]});
+	assert_eq!(diff_with(&a, &c, &options), vec![
+		Change::Removed { pointer: String::from("/list/2"), value: json!(3.0) },
+		Change::Added { pointer: String::from("/list/1"), value: json!(4.0) }
+	]);
+}
+
+#[test]
+fn rendering() {
+	let changes = diff(&json!({"a": 1.0, "b": [true]}), &json!({"a": 2.0}));
+
+	assert_eq!(
+		render(&changes, false),
+		"--- expected\n+++ actual\n@@ /a @@\n- 1\n+ 2\n@@ /b @@\n- [\n-   true\n- ]\n"
+	);
+
+	let colored = render(&changes, true);
+	assert!(colored.contains("\x1b[31m- 1\x1b[0m"));
+	assert!(colored.contains("\x1b[32m+ 2\x1b[0m"));
+	assert!(render(&[], true).is_empty());
+}
+
+#[test]
+fn rendering_escapes_strings() {
+	let changes = diff(&json!({"a": ["x, {y}"]}), &json!({"a": ["say \"[hi]\"\nbye"]}));
+
+	assert_eq!(
+		render(&changes, false),
+		"--- expected\n+++ actual\n@@ /a/0 @@\n- \"x, {y}\"\n+ \"say \\\"[hi]\\\"\\nbye\"\n"
+	);
+	assert_eq!(changes[0].to_string(), r#"changed "/a/0": "x, {y}" -> "say \"[hi]\"\nbye""#);
+}
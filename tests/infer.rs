@@ -0,0 +1,50 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::schema::{self, Schema};
+
+#[test]
+fn infer_merges_samples() {
+	let samples = vec![
+		json!({"id": 1.0, "price": 2.0, "name": "a", "tags": ["x"], "items": [{"sku": "a", "qty": 1.0}]}),
+		json!({"id": 2.0, "price": 2.5, "name": null, "items": [{"sku": "b"}]}),
+		json!({"id": 3.0, "price": 3.0, "name": "c", "tags": [], "items": []})
+	];
+
+	let inferred = schema::infer(&samples);
+	assert_eq!(inferred, json!({
+		"$schema": "https://json-schema.org/draft/2020-12/schema",
+		"type": "object",
+		"properties": {
+			"id": {"type": "integer"},
+			"price": {"type": "number"},
+			"name": {"type": ["null", "string"]},
+			"tags": {"type": "array", "items": {"type": "string"}},
+			"items": {
+				"type": "array",
+				"items": {
+					"type": "object",
+					"properties": {"sku": {"type": "string"}, "qty": {"type": "integer"}},
+					"required": ["sku"]
+				}
+			}
+		},
+		"required": ["id", "items", "name", "price"]
+	}));
+
+	//The inferred schema accepts its own samples
+	let compiled = Schema::compile(&inferred).unwrap();
+	for sample in &samples {
+		assert!(compiled.is_valid(sample));
+	}
+	assert!(!compiled.is_valid(&json!({"id": 1.5, "price": 1.0, "name": "a", "items": []})));
+}
+
+#[test]
+fn infer_edge_cases() {
+	assert_eq!(schema::infer(&[]), json!({"$schema": "https://json-schema.org/draft/2020-12/schema"}));
+	assert_eq!(
+		schema::infer(&[json!(1.0), json!("a"), json!([])]),
+		json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "type": ["integer", "string", "array"]})
+	);
+}
@@ -0,0 +1,79 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::interpolate::{InterpolateOptions, Source};
+
+use std::collections::HashMap;
+use std::env;
+
+fn variables() -> HashMap<String, String> {
+	let mut to_return = HashMap::new();
+	to_return.insert(String::from("HOST"), String::from("db.local"));
+	to_return.insert(String::from("PORT"), String::from("5432"));
+	to_return.insert(String::from("DEBUG"), String::from("true"));
+	to_return.insert(String::from("EMPTY"), String::new());
+	to_return
+}
+
+#[test]
+fn placeholders() {
+	let map = variables();
+	let options = InterpolateOptions { source: Source::Map(&map), coerce: false };
+
+	let mut value = json!({
+		"url": "postgres://${HOST}:${PORT}/app",
+		"user": "${USER_NAME:-admin}",
+		"empty": "${EMPTY:-fallback}",
+		"price": "$$5 and $ alone",
+		"list": ["${PORT}", 1.0],
+		"${HOST}": true
+	});
+	value.interpolate(&options).unwrap();
+
+	assert_eq!(value, json!({
+		"url": "postgres://db.local:5432/app",
+		"user": "admin",
+		"empty": "fallback",
+		"price": "$5 and $ alone",
+		"list": ["5432", 1.0],
+		"${HOST}": true
+	}));
+}
+
+#[test]
+fn coercion() {
+	let map = variables();
+	let options = InterpolateOptions { source: Source::Map(&map), coerce: true };
+
+	let mut value = json!(["${PORT}", "${DEBUG}", "${HOST}", "port ${PORT}", "${MISSING:-1.5}", "$${PORT}"]);
+	value.interpolate(&options).unwrap();
+	assert_eq!(value, json!([5432.0, true, "db.local", "port 5432", 1.5, "${PORT}"]));
+}
+
+#[test]
+fn errors() {
+	let map = variables();
+	let options = InterpolateOptions { source: Source::Map(&map), coerce: false };
+
+	let original = json!({"a": ["ok ${HOST}", "${MISSING}"]});
+	let mut value = original.clone();
+	let err = value.interpolate(&options).unwrap_err();
+	assert_eq!(err.pointer, "/a/1");
+	assert_eq!(err.variable, "MISSING");
+	assert_eq!(err.to_string(), "\"/a/1\": variable MISSING is not set");
+	assert_eq!(value, original);
+
+	assert_eq!(json!("${EMPTY:?must be set}").interpolate(&options).unwrap_err().message, "EMPTY: must be set");
+	assert!(json!("${HOST").interpolate(&options).is_err());
+	assert!(json!("${BAD NAME}").interpolate(&options).is_err());
+	assert!(json!("${HOST:+x}").interpolate(&options).is_err());
+}
+
+#[test]
+fn environment() {
+	env::set_var("JSAFE_INTERPOLATE_TEST", "from env");
+
+	let mut value = json!("${JSAFE_INTERPOLATE_TEST}");
+	value.interpolate(&InterpolateOptions::default()).unwrap();
+	assert_eq!(value, json!("from env"));
+}
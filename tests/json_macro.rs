@@ -0,0 +1,64 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::Value;
+
+#[test]
+fn scalars() {
+	assert_eq!(json!(null).to_string(), "null");
+	assert_eq!(json!(true).to_string(), "true");
+	assert_eq!(json!(1.5).to_string(), "1.5");
+	assert_eq!(json!("text").to_string(), "\"text\"");
+}
+
+#[test]
+fn interpolated_expressions() {
+	let id: i64 = 7;
+	let name = String::from("b");
+	let value = json!({"id": id, "tags": ["a", name], "ok": true, "x": null});
+
+	assert_eq!(value.len(), 4);
+	assert_eq!(value["id"].to_string(), "7");
+	assert_eq!(value["tags"].to_string(), "[\"a\",\"b\"]");
+	assert_eq!(value["ok"].to_string(), "true");
+	assert_eq!(value["x"].to_string(), "null");
+}
+
+#[test]
+fn nesting() {
+	let value = json!({
+		"outer": {
+			"inner": [[1.0, [2.0]], {"deep": {"deeper": null}}, {}, []]
+		}
+	});
+
+	let list = &value["outer"]["inner"];
+	assert_eq!(list.len(), 4);
+	assert_eq!(list[0].to_string(), "[1,[2]]");
+	assert_eq!(list[1]["deep"]["deeper"].to_string(), "null");
+	assert_eq!(list[2].to_string(), "{}");
+	assert_eq!(list[3].to_string(), "[]");
+}
+
+#[test]
+fn trailing_commas() {
+	let value = json!({
+		"a": [1.0, 2.0,],
+		"b": {"c": false,},
+		"d": null,
+	});
+
+	assert_eq!(value.len(), 3);
+	assert_eq!(value["a"].to_string(), "[1,2]");
+	assert_eq!(value["b"].to_string(), "{\"c\":false}");
+	assert_eq!(json!([null,]).to_string(), "[null]");
+}
+
+#[test]
+fn existing_values_are_moved_in() {
+	let inner = json!([true]);
+	let value: Value = json!({"inner": inner, "sum": 1.0 + 2.0});
+
+	assert_eq!(value["inner"].to_string(), "[true]");
+	assert_eq!(value["sum"].to_string(), "3");
+}
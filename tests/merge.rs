@@ -0,0 +1,67 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::merge::{ArrayStrategy, MergeOptions};
+
+#[test]
+fn merge_patch() {
+	//Example from RFC 7396
+	let mut value = json!({
+		"title": "Goodbye!",
+		"author": {"givenName": "John", "familyName": "Doe"},
+		"tags": ["example", "sample"],
+		"content": "This will be unchanged"
+	});
+	value.merge_patch(&json!({
+		"title": "Hello!",
+		"phoneNumber": "+01-123-456-7890",
+		"author": {"familyName": null},
+		"tags": ["example"]
+	}));
+
+	assert_eq!(value, json!({
+		"title": "Hello!",
+		"author": {"givenName": "John"},
+		"tags": ["example"],
+		"content": "This will be unchanged",
+		"phoneNumber": "+01-123-456-7890"
+	}));
+
+	let mut value = json!([1.0]);
+	value.merge_patch(&json!({"a": {"b": null, "c": 1.0}}));
+	assert_eq!(value, json!({"a": {"c": 1.0}}));
+
+	value.merge_patch(&json!("x"));
+	assert_eq!(value, json!("x"));
+}
+
+#[test]
+fn deep_merge_objects() {
+	let mut value = json!({"server": {"port": 80.0, "host": "a"}, "debug": false});
+	value.deep_merge(&json!({"server": {"port": 8080.0}, "debug": null}), &MergeOptions::default());
+	assert_eq!(value, json!({"server": {"port": 8080.0, "host": "a"}, "debug": null}));
+
+	let options = MergeOptions { null_deletes: true, ..MergeOptions::default() };
+	value.deep_merge(&json!({"debug": null}), &options);
+	assert_eq!(value, json!({"server": {"port": 8080.0, "host": "a"}}));
+}
+
+#[test]
+fn deep_merge_arrays() {
+	let base = json!({"list": [{"id": 1.0, "a": 1.0}, {"id": 2.0}]});
+	let layer = json!({"list": [{"id": 2.0, "b": 2.0}, {"id": 3.0}]});
+
+	let merge = |arrays: ArrayStrategy| {
+		let mut value = base.clone();
+		value.deep_merge(&layer, &MergeOptions { arrays, null_deletes: false });
+		value
+	};
+
+	assert_eq!(merge(ArrayStrategy::Replace), layer);
+	assert_eq!(merge(ArrayStrategy::Concat), json!({"list": [{"id": 1.0, "a": 1.0}, {"id": 2.0}, {"id": 2.0, "b": 2.0}, {"id": 3.0}]}));
+	assert_eq!(merge(ArrayStrategy::MergeByIndex), json!({"list": [{"id": 2.0, "a": 1.0, "b": 2.0}, {"id": 3.0}]}));
+	assert_eq!(
+		merge(ArrayStrategy::MergeByKey(String::from("id"))),
+		json!({"list": [{"id": 1.0, "a": 1.0}, {"id": 2.0, "b": 2.0}, {"id": 3.0}]})
+	);
+}
@@ -0,0 +1,77 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::Value;
+use jsafe::json::parsing;
+
+#[test]
+fn parses_every_kind_of_value() {
+	let value = parsing::parse(" {\"a\": [1, -2.5, 3e2, true, false, null], \"b\": {}, \"c\": [], \"d\": \"x\\\"y\\u00e9\\ud83d\\ude00\"} ").unwrap();
+
+	assert_eq!(value, json!({
+		"a": [1.0, -2.5, 300.0, true, false, null],
+		"b": {},
+		"c": [],
+		"d": "x\"y\u{e9}\u{1F600}"
+	}));
+}
+
+#[test]
+fn errors_give_the_position() {
+	let err = parsing::parse("{\n  \"a\": [1, 2,, 3]\n}").unwrap_err();
+	assert_eq!((err.offset, err.line, err.column), (15, 2, 14));
+	assert_eq!(err.message, "expected a value");
+	assert_eq!(err.to_string(), "expected a value at line 2, column 14");
+
+	//Columns count characters, not bytes
+	let err = parsing::parse("[\"é\" 1]").unwrap_err();
+	assert_eq!((err.line, err.column), (1, 6));
+	assert_eq!(err.message, "expected ',' or ']'");
+}
+
+#[test]
+fn rejects_invalid_json() {
+	for text in ["", "{", "[1,]", "{\"a\" 1}", "01", "1.", "-", "tru", "\"a", "\"\\x\"", "\"\\ud800\"", "1 2", "{a: 1}", "\"tab\there\""] {
+		assert!(parsing::parse(text).is_err(), "{:?} should be rejected", text);
+		assert_eq!(parsing::from_str(text), Value::Invalid);
+	}
+
+	let deep = "[".repeat(100000);
+	assert_eq!(parsing::parse(&deep).unwrap_err().message, "nesting is too deep");
+}
+
+#[test]
+fn depth_limit_is_configurable() {
+	let options = parsing::ParseOptions { max_depth: 2 };
+	assert!(parsing::parse_with("[[1]]", &options).is_ok());
+	assert_eq!(parsing::parse_with("[[[1]]]", &options).unwrap_err().offset, 2);
+	assert!(parsing::parse("[[[1]]]").is_ok());
+}
+
+#[test]
+fn bytes_must_be_utf8() {
+	let options = parsing::ParseOptions::default();
+	assert_eq!(parsing::parse_bytes(b"[\"\xc3\xa9\"]", &options).unwrap(), Value::Array(vec![Value::Text(String::from("é"))]));
+
+	let err = parsing::parse_bytes(b"[\n\"\xc3\xa9\xff\"]", &options).unwrap_err();
+	assert_eq!((err.offset, err.line, err.column), (5, 2, 3));
+	assert_eq!(err.message, "invalid UTF-8");
+}
+
+#[test]
+fn from_str_reads_exactly_one_value() {
+	assert_eq!(parsing::from_str(" \n[1, {\"a\": null}]\t"), json!([1.0, {"a": null}]));
+	assert_eq!(parsing::from_str("-1.5e1"), json!(-15.0));
+	assert_eq!(parsing::from_str("\"a\\nb\\u0041\""), json!("a\nbA"));
+	assert_eq!(parsing::from_str("{\"a,b\": \"}\"}"), json!({"a,b": "}"}));
+
+	//Each of these is Invalid rather than a panic or a partial value
+	for text in ["", " ", "12abc", "[1] x", "true false", "truex", "nul", "{\"a\": 1,}", "[\"a\" \"b\"]", "\"\u{7}\""] {
+		assert_eq!(parsing::from_str(text), Value::Invalid, "{:?} should be Invalid", text);
+	}
+
+	let deep = format!("{}{}", "[".repeat(513), "]".repeat(513));
+	assert_eq!(parsing::from_str(&deep), Value::Invalid);
+	let deep = format!("{}{}", "[".repeat(512), "]".repeat(512));
+	assert_ne!(parsing::from_str(&deep), Value::Invalid);
+}
@@ -0,0 +1,105 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::{FromJson, ToJson, Value};
+use jsafe::json::patch::{self, Operation};
+
+fn ops(value: Value) -> Vec<Operation> {
+	Vec::<Operation>::from_json(&value).unwrap()
+}
+
+#[test]
+fn apply_operations() {
+	let mut value = json!({"a": {"b": [1.0, 2.0]}, "c": "x"});
+
+	let patch = ops(json!([
+		{"op": "test", "path": "/c", "value": "x"},
+		{"op": "add", "path": "/a/b/1", "value": 1.5},
+		{"op": "add", "path": "/a/b/-", "value": 3.0},
+		{"op": "remove", "path": "/a/b/0"},
+		{"op": "replace", "path": "/c", "value": null},
+		{"op": "copy", "from": "/a/b", "path": "/d"},
+		{"op": "move", "from": "/a", "path": "/e"}
+	]));
+	patch::apply(&mut value, &patch).unwrap();
+
+	assert_eq!(value, json!({"c": null, "d": [1.5, 2.0, 3.0], "e": {"b": [1.5, 2.0, 3.0]}}));
+}
+
+#[test]
+fn failed_patches_change_nothing() {
+	let original = json!({"a": [1.0], "b": true});
+	let mut value = original.clone();
+
+	let patch = ops(json!([
+		{"op": "remove", "path": "/b"},
+		{"op": "test", "path": "/a/0", "value": 2.0}
+	]));
+	let err = patch::apply(&mut value, &patch).unwrap_err();
+	assert_eq!(err.operation, 1);
+	assert_eq!(value, original);
+
+	assert!(patch::apply(&mut value, &ops(json!([{"op": "add", "path": "/a/5", "value": 1.0}]))).is_err());
+	assert!(patch::apply(&mut value, &ops(json!([{"op": "add", "path": "/x/y", "value": 1.0}]))).is_err());
+	assert!(patch::apply(&mut value, &ops(json!([{"op": "replace", "path": "/x", "value": 1.0}]))).is_err());
+	assert!(patch::apply(&mut value, &ops(json!([{"op": "move", "from": "/a", "path": "/a/0"}]))).is_err());
+	assert_eq!(value, original);
+}
+
+#[test]
+fn parse_errors() {
+	assert!(Vec::<Operation>::from_json(&json!([{"op": "add", "path": "/a"}])).is_err());
+	assert!(Vec::<Operation>::from_json(&json!([{"op": "jump", "path": "/a"}])).is_err());
+	assert!(Operation::from_json(&json!({"op": "add", "path": "/a", "value": null})).is_ok());
+
+	let op = Operation::Move { from: String::from("/a"), path: String::from("/b") };
+	assert_eq!(op.to_json(), json!({"op": "move", "from": "/a", "path": "/b"}));
+}
+
+#[test]
+fn generate_round_trips() {
+	let from = json!({"name": "a", "list": [1.0, 2.0, 3.0, 4.0], "gone": 1.0, "nested": {"x": [{"k": 1.0}]}});
+	let to = json!({"name": "b", "list": [0.0, 1.0, 3.0, 4.0, 5.0], "new": [true], "nested": {"x": [{"k": 2.0}]}});
+
+	let patch = patch::generate(&from, &to);
+	let mut value = from.clone();
+	patch::apply(&mut value, &patch).unwrap();
+	assert_eq!(value, to);
+
+	assert!(patch::generate(&to, &to).is_empty());
+	assert_eq!(patch::generate(&json!(1.0), &json!("x")), ops(json!([{"op": "replace", "path": "", "value": "x"}])));
+}
+
+#[test]
+fn generate_detects_insertions_and_deletions() {
+	let from = json!([1.0, 2.0, 3.0, 4.0, 5.0]);
+
+	assert_eq!(
+		patch::generate(&from, &json!([0.0, 1.0, 2.0, 3.0, 4.0, 5.0])),
+		ops(json!([{"op": "add", "path": "/0", "value": 0.0}]))
+	);
+	assert_eq!(
+		patch::generate(&from, &json!([1.0, 2.0, 4.0, 5.0])),
+		ops(json!([{"op": "remove", "path": "/2"}]))
+	);
+	assert_eq!(
+		patch::generate(&json!([{"id": 1.0, "n": "a"}]), &json!([{"id": 1.0, "n": "b"}])),
+		ops(json!([{"op": "replace", "path": "/0/n", "value": "b"}]))
+	);
+}
+
+#[test]
+fn large_reordered_arrays_fall_back_to_index_wise_changes() {
+	//2000 x 2001 is past the LCS table limit
+	let from: Value = (0..2000).collect();
+	let to: Value = (1..2002).rev().collect();
+
+	let patch = patch::generate(&from, &to);
+	assert_eq!(patch.len(), 2001);
+	assert!(patch[..2000].iter().all(|x| x.name() == "replace"));
+	assert_eq!(patch[2000], ops(json!([{"op": "add", "path": "/2000", "value": 1.0}]))[0]);
+
+	let mut patched = from.clone();
+	patch::apply(&mut patched, &patch).unwrap();
+	assert_eq!(patched, to);
+}
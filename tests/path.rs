@@ -0,0 +1,41 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::{PathSegment, Value};
+
+#[test]
+fn get_path_reads_mixed_segments() {
+	let value = json!({"a": [{"b": 1.0}, {"b": 2.0}]});
+
+	assert_eq!(value.get_path(&path!["a", 1, "b"]), Some(&json!(2.0)));
+	assert_eq!(value.get_path(&path![]), Some(&value));
+	assert_eq!(value.get_path(&path!["a", 5, "b"]), None);
+	assert_eq!(value.get_path(&path!["a", "b"]), None);
+	assert_eq!(value.get_path(&[PathSegment::Key(String::from("a")), PathSegment::Index(0)]), Some(&json!({"b": 1.0})));
+}
+
+#[test]
+fn get_path_mut_never_creates_nodes() {
+	let mut value = json!({"a": {}});
+
+	assert!(value.get_path_mut(&path!["a", "missing", "deeper"]).is_none());
+	assert_eq!(value, json!({"a": {}}));
+
+	*value.get_path_mut(&path!["a"]).unwrap() = json!(true);
+	assert_eq!(value, json!({"a": true}));
+}
+
+#[test]
+fn ensure_path_creates_null_leaves() {
+	let mut value = Value::Null;
+
+	assert_eq!(value.ensure_path(&path!["a", 2, "b"]), Some(&mut Value::Null));
+	assert_eq!(value, json!({"a": [null, null, {"b": null}]}));
+
+	*value.ensure_path(&path!["a", 0]).unwrap() = json!(1.0);
+	assert_eq!(value["a"][0], json!(1.0));
+
+	//Existing values of the wrong type are left alone
+	assert!(value.ensure_path(&path!["a", 0, "c"]).is_none());
+	assert_eq!(value["a"][0], json!(1.0));
+}
@@ -0,0 +1,67 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::Value;
+use jsafe::json::pointer;
+
+#[test]
+fn pointer_lookup() {
+	let value = json!({"users": [{"name": "ana"}], "a/b": 1.0, "m~n": 2.0, "": 3.0});
+
+	assert_eq!(value.pointer(""), Ok(&value));
+	assert_eq!(value.pointer("/users/0/name"), Ok(&json!("ana")));
+	assert_eq!(value.pointer("/a~1b"), Ok(&json!(1.0)));
+	assert_eq!(value.pointer("/m~0n"), Ok(&json!(2.0)));
+	assert_eq!(value.pointer("/"), Ok(&json!(3.0)));
+}
+
+#[test]
+fn pointer_errors_name_the_failing_token() {
+	let value = json!({"users": [{"name": "ana"}]});
+
+	let err = value.pointer("/users/1/name").unwrap_err();
+	assert_eq!(err.position, 1);
+	assert_eq!(err.token, "1");
+	assert_eq!(err.message, "index out of bounds");
+
+	assert_eq!(value.pointer("/users/01").unwrap_err().message, "invalid array index");
+	assert_eq!(value.pointer("/users/-").unwrap_err().message, "index out of bounds");
+	assert_eq!(value.pointer("/users/0/name/x").unwrap_err().message, "can't index into text");
+	assert_eq!(value.pointer("/bad~2").unwrap_err().message, "~ must be followed by 0 or 1");
+	assert_eq!(value.pointer("/bad~2").unwrap_err().token, "bad~2");
+	assert_eq!(value.pointer("/a~1b~0c").unwrap_err().token, "a/b~c");
+	assert!(value.pointer("users").is_err());
+	assert_eq!(
+		value.pointer("/missing").unwrap_err().to_string(),
+		"pointer \"/missing\": token 0 (\"missing\"): key not found"
+	);
+}
+
+#[test]
+fn pointer_set_and_remove() {
+	let mut value = json!({"list": [1.0]});
+
+	assert_eq!(value.pointer_set("/list/-", json!(2.0)), Ok(None));
+	assert_eq!(value.pointer_set("/list/2", json!(3.0)), Ok(None));
+	assert_eq!(value.pointer_set("/list/0", json!(0.0)), Ok(Some(json!(1.0))));
+	assert_eq!(value.pointer_set("/key", json!(true)), Ok(None));
+	assert!(value.pointer_set("/list/9", json!(null)).is_err());
+	assert!(value.pointer_set("/missing/key", json!(null)).is_err());
+	assert_eq!(value, json!({"list": [0.0, 2.0, 3.0], "key": true}));
+
+	*value.pointer_mut("/list/1").unwrap() = json!("two");
+	assert_eq!(value.pointer_remove("/list/1"), Ok(json!("two")));
+	assert_eq!(value.pointer_remove("/key"), Ok(json!(true)));
+	assert!(value.pointer_remove("/key").is_err());
+	assert!(value.pointer_remove("").is_err());
+	assert_eq!(value, json!({"list": [0.0, 3.0]}));
+
+	assert_eq!(value.pointer_set("", Value::Null), Ok(Some(json!({"list": [0.0, 3.0]}))));
+	assert_eq!(value, Value::Null);
+}
+
+#[test]
+fn escaping() {
+	assert_eq!(pointer::escape("a/b~c"), "a~1b~0c");
+	assert_eq!(pointer::parse("/a~1b~0c/~01"), Ok(vec![String::from("a/b~c"), String::from("~1")]));
+}
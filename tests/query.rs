@@ -0,0 +1,109 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::Value;
+use jsafe::json::query::Query;
+use jsafe::json::regex::Regex;
+
+fn store() -> Value {
+	json!({
+		"orders": [
+			{"id": 1.0, "total": 50.0, "tags": ["new"]},
+			{"id": 2.0, "total": 150.0, "tags": []},
+			{"id": 3.0, "total": 250.0, "customer": {"name": "ana"}}
+		],
+		"name": "shop"
+	})
+}
+
+//Objects have no defined order, so compare selections that may come from one sorted
+fn sorted_numbers(nodes: Vec<&Value>) -> Vec<f64> {
+	let mut to_return: Vec<f64> = nodes.iter().map(|x| match x { Value::Number(n) => *n, _ => 0.0 }).collect();
+	to_return.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	to_return
+}
+
+#[test]
+fn basic_selectors() {
+	let value = store();
+
+	assert_eq!(value.query("$").unwrap(), vec![&value]);
+	assert_eq!(value.query("$.name").unwrap(), vec![&json!("shop")]);
+	assert_eq!(value.query("$['name']").unwrap(), vec![&json!("shop")]);
+	assert_eq!(value.query("$.orders[-1].id").unwrap(), vec![&json!(3.0)]);
+	assert_eq!(value.query("$.orders[*].id").unwrap(), vec![&json!(1.0), &json!(2.0), &json!(3.0)]);
+	assert_eq!(value.query("$.orders[0,2].id").unwrap(), vec![&json!(1.0), &json!(3.0)]);
+	assert_eq!(value.query("$.orders[::-1].id").unwrap(), vec![&json!(3.0), &json!(2.0), &json!(1.0)]);
+	assert_eq!(value.query("$.orders[1:].id").unwrap(), vec![&json!(2.0), &json!(3.0)]);
+	assert_eq!(value.query("$..name").unwrap().len(), 2);
+	assert_eq!(sorted_numbers(value.query("$..id").unwrap()), vec![1.0, 2.0, 3.0]);
+	assert!(value.query("$.missing").unwrap().is_empty());
+}
+
+#[test]
+fn filters() {
+	let value = store();
+
+	assert_eq!(value.query("$.orders[?(@.total > 100)].id").unwrap(), vec![&json!(2.0), &json!(3.0)]);
+	assert_eq!(value.query("$.orders[?@.total <= 150 && @.id != 1].id").unwrap(), vec![&json!(2.0)]);
+	assert_eq!(value.query("$.orders[?@.customer].id").unwrap(), vec![&json!(3.0)]);
+	assert_eq!(value.query("$.orders[?!@.customer || @.id == 3].id").unwrap().len(), 3);
+	assert_eq!(value.query("$.orders[?@.customer.name == 'ana'].id").unwrap(), vec![&json!(3.0)]);
+	assert_eq!(value.query("$.orders[?@.total > $.orders[0].total].id").unwrap(), vec![&json!(2.0), &json!(3.0)]);
+
+	//A missing value is only equal to another missing value
+	assert_eq!(value.query("$.orders[?@.missing == @.other].id").unwrap().len(), 3);
+	assert!(value.query("$.orders[?@.missing == null].id").unwrap().is_empty());
+}
+
+#[test]
+fn functions() {
+	let value = store();
+
+	assert_eq!(value.query("$.orders[?length(@.tags) == 1].id").unwrap(), vec![&json!(1.0)]);
+	assert_eq!(value.query("$.orders[?count(@.tags[*]) == 1].id").unwrap(), vec![&json!(1.0)]);
+	assert_eq!(value.query("$.orders[?value(@..name) == 'ana'].id").unwrap(), vec![&json!(3.0)]);
+	assert_eq!(value.query("$.orders[?match(@.customer.name, 'a.a')].id").unwrap(), vec![&json!(3.0)]);
+	assert!(value.query("$.orders[?match(@.customer.name, 'n')].id").unwrap().is_empty());
+	assert_eq!(value.query("$.orders[?search(@.customer.name, 'n')].id").unwrap(), vec![&json!(3.0)]);
+	assert_eq!(value.query("$[?search(@, '^sh')]").unwrap(), vec![&json!("shop")]);
+}
+
+#[test]
+fn compiled_queries_can_be_reused() {
+	let query: Query = "$[?@ > 1]".parse().unwrap();
+
+	assert_eq!(query.select(&json!([1.0, 2.0, 3.0])), vec![&json!(2.0), &json!(3.0)]);
+	assert!(query.select(&json!(["a"])).is_empty());
+}
+
+#[test]
+fn compile_errors() {
+	assert_eq!(Query::compile("orders").unwrap_err().position, 0);
+	assert!(Query::compile("$.orders[").is_err());
+	assert!(Query::compile("$.orders[?@..id == 1]").is_err());
+	assert!(Query::compile("$.orders[?@.* == 1]").is_err());
+	assert!(Query::compile("$[?length(@.*) == 1]").is_err());
+	assert!(Query::compile("$[?count(1) == 1]").is_err());
+	assert!(Query::compile("$[?length(@) == 1 == 2]").is_err());
+	assert!(Query::compile("$[?unknown(@)]").is_err());
+	assert!(Query::compile("$[01]").is_err());
+}
+
+#[test]
+fn regex() {
+	let date = Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap();
+	assert!(date.is_match("2024-01-31"));
+	assert!(!date.is_match("2024-01-31T"));
+	assert!(date.search("on 2024-01-31."));
+
+	let word = Regex::new("(?:ab|c)+[^x]?$").unwrap();
+	assert!(word.is_match("abcab"));
+	assert!(word.is_match("cz"));
+	assert!(!word.is_match("x"));
+
+	assert!(Regex::new(r"\p{Lu}\p{Ll}*").unwrap().is_match("Émile"));
+	assert!(Regex::new("a{2,1}").is_err());
+	assert!(Regex::new("(a").is_err());
+	assert!(Regex::new("*").is_err());
+}
@@ -0,0 +1,129 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::resolve::{self, Resolver};
+
+use std::env;
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+//Temporary directory, removed when the test finishes
+struct Dir(PathBuf);
+
+impl Deref for Dir {
+	type Target = Path;
+
+	fn deref(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl Drop for Dir {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.0);
+	}
+}
+
+//Write files into a fresh directory under the system temp dir
+fn files(name: &str, contents: &[(&str, &str)]) -> Dir {
+	let dir = env::temp_dir().join(format!("jsafe-resolve-{}-{}", name, std::process::id()));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(dir.join("shared")).unwrap();
+
+	for (file, text) in contents {
+		fs::write(dir.join(file), text).unwrap();
+	}
+	Dir(dir)
+}
+
+#[test]
+fn inlines_references() {
+	let dir = files("inline", &[
+		("main.json", r##"{"server": {"$ref": "shared/common.json#/defaults"}, "name": {"$ref": "#/names/0"}, "names": ["main"]}"##),
+		("shared/common.json", r##"{"defaults": {"port": 80, "limits": {"$ref": "limits.json"}}}"##),
+		("shared/limits.json", r##"[1, 2]"##)
+	]);
+
+	let value = resolve::resolve_file(dir.join("main.json")).unwrap();
+	assert_eq!(value, json!({"server": {"port": 80.0, "limits": [1.0, 2.0]}, "name": "main", "names": ["main"]}));
+}
+
+#[test]
+fn loaded_files_are_cached() {
+	let dir = files("cache", &[
+		("a.json", r##"{"x": {"$ref": "b.json"}}"##),
+		("b.json", r##"{"y": 1}"##)
+	]);
+
+	let mut resolver = Resolver::new();
+	assert_eq!(resolver.resolve_file(dir.join("a.json")).unwrap(), json!({"x": {"y": 1.0}}));
+
+	fs::write(dir.join("b.json"), r##"{"y": 2}"##).unwrap();
+	assert_eq!(resolver.resolve(&json!({"$ref": "b.json#/y"}), dir.join("a.json")).unwrap(), json!(1.0));
+	assert_eq!(Resolver::new().resolve(&json!({"$ref": "b.json#/y"}), dir.join("a.json")).unwrap(), json!(2.0));
+}
+
+#[test]
+fn errors_list_the_reference_chain() {
+	let dir = files("errors", &[
+		("a.json", r##"{"x": {"$ref": "b.json#/next"}}"##),
+		("b.json", r##"{"next": {"$ref": "a.json"}, "bad": {"$ref": "#/missing"}}"##),
+		("c.json", r##"{"x": {"$ref": "b.json#/bad"}}"##)
+	]);
+
+	let err = resolve::resolve_file(dir.join("a.json")).unwrap_err();
+	assert_eq!(err.message, "reference cycle");
+	assert_eq!(err.chain.len(), 3);
+	assert!(err.chain[0].ends_with("b.json#/next"));
+	assert!(err.chain[1].ends_with("a.json#"));
+	assert_eq!(err.chain[2], err.chain[0]);
+
+	let err = resolve::resolve_file(dir.join("c.json")).unwrap_err();
+	assert!(err.chain[0].ends_with("b.json#/bad"));
+	assert!(err.chain[1].ends_with("b.json#/missing"));
+	assert!(err.to_string().contains("key not found (while resolving "));
+
+	assert!(resolve::resolve_file(dir.join("missing.json")).unwrap_err().chain.is_empty());
+}
+
+#[test]
+fn parse_errors_give_the_position() {
+	let dir = files("parse", &[
+		("a.json", r##"{"x": {"$ref": "b.json"}}"##),
+		("b.json", "{\n  \"open\": [1,\n}"),
+		("empty.json", "")
+	]);
+
+	let err = resolve::resolve_file(dir.join("a.json")).unwrap_err();
+	let parse = err.parse.clone().unwrap();
+	assert_eq!((parse.line, parse.column), (3, 1));
+	assert!(err.chain[0].ends_with("b.json#"));
+	assert!(err.message.ends_with(&format!("b.json isn't valid JSON: {}", parse)));
+
+	let err = resolve::resolve_file(dir.join("empty.json")).unwrap_err();
+	assert_eq!(err.parse.map(|x| (x.line, x.column)), Some((1, 1)));
+
+	assert!(resolve::resolve_file(dir.join("missing.json")).unwrap_err().parse.is_none());
+}
+
+#[test]
+fn same_document_references_read_the_value() {
+	let dir = files("memory", &[
+		("a.json", r##"{"names": ["on disk"]}"##),
+		("b.json", r##"{"name": {"$ref": "#/names/0"}, "names": ["b"]}"##)
+	]);
+
+	let value = json!({"name": {"$ref": "#/names/0"}, "other": {"$ref": "b.json#/name"}, "names": ["in memory"]});
+	assert_eq!(
+		Resolver::new().resolve(&value, dir.join("a.json")).unwrap(),
+		json!({"name": "in memory", "other": "b", "names": ["in memory"]})
+	);
+
+	//The base file doesn't have to exist
+	let value = json!({"a": {"$ref": "#/b"}, "b": {"$ref": "#/c"}, "c": 1});
+	assert_eq!(Resolver::new().resolve(&value, dir.join("missing.json")).unwrap(), json!({"a": 1.0, "b": 1.0, "c": 1.0}));
+
+	let err = Resolver::new().resolve(&json!({"$ref": "#/x"}), dir.join("missing.json")).unwrap_err();
+	assert!(err.message.ends_with("key not found"));
+}
@@ -0,0 +1,154 @@
+#[macro_use]
+extern crate jsafe;
+
+use jsafe::json::Value;
+use jsafe::json::schema::Schema;
+
+fn violations(schema: &Schema, instance: Value) -> Vec<(String, String)> {
+	match schema.validate(&instance) {
+		Ok(()) => Vec::new(),
+		Err(x) => x.into_iter().map(|v| (v.instance, v.schema)).collect()
+	}
+}
+
+fn pair(instance: &str, schema: &str) -> (String, String) {
+	(instance.to_string(), schema.to_string())
+}
+
+#[test]
+fn objects() {
+	let schema = Schema::compile(&json!({
+		"type": "object",
+		"required": ["id", "name"],
+		"properties": {
+			"id": {"type": "integer", "minimum": 1.0},
+			"name": {"type": "string", "minLength": 1.0, "pattern": "^[a-z]+$"},
+			"tags": {"type": "array", "items": {"type": "string"}, "uniqueItems": true, "maxItems": 3.0}
+		},
+		"additionalProperties": false
+	})).unwrap();
+
+	assert!(schema.is_valid(&json!({"id": 3.0, "name": "ana", "tags": ["a", "b"]})));
+
+	//Every violation is reported, not just the first
+	assert_eq!(violations(&schema, json!({"id": 0.5, "tags": ["a", 1.0, "a"], "extra": true})), vec![
+		pair("/extra", "/additionalProperties"),
+		pair("/id", "/properties/id/minimum"),
+		pair("/id", "/properties/id/type"),
+		pair("/tags/1", "/properties/tags/items/type"),
+		pair("/tags/2", "/properties/tags/uniqueItems"),
+		pair("", "/required")
+	]);
+
+	let err = schema.validate(&json!({"id": 1.0, "name": "Ana"})).unwrap_err();
+	assert_eq!(err[0].message, "text doesn't match \"^[a-z]+$\"");
+	assert_eq!(err[0].to_string(), "\"/name\": text doesn't match \"^[a-z]+$\" (schema \"/properties/name/pattern\")");
+}
+
+#[test]
+fn arrays_and_values() {
+	let schema = Schema::compile(&json!({
+		"type": "array",
+		"prefixItems": [{"const": "point"}, {"enum": [1.0, 2.0]}],
+		"items": {"type": ["number", "null"], "exclusiveMaximum": 10.0, "multipleOf": 0.5},
+		"contains": {"type": "null"}
+	})).unwrap();
+
+	assert!(schema.is_valid(&json!(["point", 2.0, 1.5, null])));
+	assert_eq!(violations(&schema, json!(["line", 3.0, 10.0, 0.3])), vec![
+		pair("", "/contains"),
+		pair("/2", "/items/exclusiveMaximum"),
+		pair("/3", "/items/multipleOf"),
+		pair("/0", "/prefixItems/0/const"),
+		pair("/1", "/prefixItems/1/enum")
+	]);
+}
+
+#[test]
+fn combinators_and_refs() {
+	let schema = Schema::compile(&json!({
+		"$defs": {
+			"node": {
+				"type": "object",
+				"properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/node"}}},
+				"anyOf": [{"required": ["leaf"]}, {"required": ["children"]}]
+			}
+		},
+		"$ref": "#/$defs/node",
+		"not": {"required": ["forbidden"]},
+		"oneOf": [{"required": ["a"]}, {"required": ["b"]}],
+		"if": {"required": ["a"]},
+		"then": {"properties": {"a": {"type": "string"}}},
+		"else": {"properties": {"b": {"type": "boolean"}}}
+	})).unwrap();
+
+	assert!(schema.is_valid(&json!({"a": "x", "children": [{"leaf": 1.0}, {"children": []}]})));
+	assert_eq!(violations(&schema, json!({"a": 1.0, "b": true, "children": [{}], "forbidden": 1.0})), vec![
+		pair("/children/0", "/$defs/node/anyOf"),
+		pair("/a", "/then/properties/a/type"),
+		pair("", "/not"),
+		pair("", "/oneOf")
+	]);
+	assert_eq!(violations(&schema, json!({"b": 1.0, "leaf": 1.0})), vec![pair("/b", "/else/properties/b/type")]);
+}
+
+#[test]
+fn compile_errors() {
+	assert_eq!(Schema::compile(&json!({"type": "text"})).unwrap_err().pointer, "/type");
+	assert_eq!(Schema::compile(&json!({"pattern": "(a"})).unwrap_err().pointer, "/pattern");
+	assert_eq!(Schema::compile(&json!({"items": {"$ref": "#/$defs/missing"}})).unwrap_err().pointer, "/items/$ref");
+	assert!(Schema::compile(&json!({"$ref": "other.json"})).is_err());
+	assert_eq!(Schema::compile(&json!({"$ref": "#/a%2"})).unwrap_err().message, "invalid percent-encoding");
+	assert_eq!(
+		Schema::compile(&json!({"additionalProperties": false, "patternProperties": {"(a": true}})).unwrap_err().pointer,
+		"/patternProperties/(a"
+	);
+	assert!(Schema::compile(&json!({"minLength": -1.0})).is_err());
+	assert!(Schema::compile(&json!(1.0)).is_err());
+	assert!(!Schema::compile(&json!(false)).unwrap().is_valid(&json!(null)));
+}
+
+#[test]
+fn decimal_multiple_of() {
+	let schema = Schema::compile(&json!({"multipleOf": 0.1})).unwrap();
+
+	assert!(schema.is_valid(&json!(0.3)));
+	assert!(schema.is_valid(&json!(2.7)));
+	assert!(schema.is_valid(&json!(-1.1)));
+	assert_eq!(violations(&schema, json!(0.35)), vec![pair("", "/multipleOf")]);
+
+	let schema = Schema::compile(&json!({"multipleOf": 0.01})).unwrap();
+	assert!(schema.is_valid(&json!(19.99)));
+	assert!(!schema.is_valid(&json!(19.995)));
+}
+
+#[test]
+fn ref_cycles_are_rejected() {
+	let error = Schema::compile(&json!({"$ref": "#"})).unwrap_err();
+	assert_eq!(error.pointer, "/$ref");
+	assert_eq!(error.message, "$ref cycle never descends into the instance");
+
+	let error = Schema::compile(&json!({"$defs": {"a": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"})).unwrap_err();
+	assert_eq!(error.pointer, "/$defs/a/$ref");
+
+	//Through combinators too
+	assert!(Schema::compile(&json!({"$defs": {"a": {"anyOf": [{"type": "string"}, {"$ref": "#"}]}}, "allOf": [{"$ref": "#/$defs/a"}]})).is_err());
+
+	//Recursion into a member or item is fine
+	let schema = Schema::compile(&json!({"properties": {"next": {"$ref": "#"}}, "items": {"$ref": "#"}})).unwrap();
+	assert!(schema.is_valid(&json!({"next": {"next": [[{}]]}})));
+}
+
+#[test]
+fn refs_are_percent_decoded() {
+	let schema = Schema::compile(&json!({
+		"$defs": {"a b": {"type": "string"}, "100%": {"type": "number"}},
+		"properties": {"x": {"$ref": "#/$defs/a%20b"}, "y": {"$ref": "#/$defs/100%25"}}
+	})).unwrap();
+
+	assert!(schema.is_valid(&json!({"x": "text", "y": 1.0})));
+	assert_eq!(violations(&schema, json!({"x": 1.0, "y": "text"})), vec![
+		pair("/x", "/$defs/a b/type"),
+		pair("/y", "/$defs/100%/type")
+	]);
+}
@@ -0,0 +1,104 @@
+//Needs the serde feature: cargo test --features serde
+#![cfg(feature = "serde")]
+
+#[macro_use]
+extern crate jsafe;
+#[macro_use]
+extern crate serde;
+
+use jsafe::json::{from_value, to_value, Value};
+
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct User {
+	id: u32,
+	name: String,
+	email: Option<String>,
+	roles: Vec<Role>,
+	limits: BTreeMap<String, f64>
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Role {
+	Admin,
+	Member(String),
+	Guest { until: i64 },
+	Pair(u8, u8)
+}
+
+fn sample() -> User {
+	let mut limits = BTreeMap::new();
+	limits.insert(String::from("rate"), 1.5);
+
+	User {
+		id: 7,
+		name: String::from("ana"),
+		email: None,
+		roles: vec![Role::Admin, Role::Member(String::from("ops")), Role::Guest { until: -1 }, Role::Pair(1, 2)],
+		limits
+	}
+}
+
+#[test]
+fn serde_types_to_value() {
+	let value = to_value(&sample()).unwrap();
+
+	assert_eq!(value, json!({
+		"id": 7.0,
+		"name": "ana",
+		"email": null,
+		"roles": ["Admin", {"Member": "ops"}, {"Guest": {"until": -1.0}}, {"Pair": [1.0, 2.0]}],
+		"limits": {"rate": 1.5}
+	}));
+}
+
+#[test]
+fn value_to_serde_types() {
+	let value = to_value(&sample()).unwrap();
+	let user: User = from_value(value).unwrap();
+	assert_eq!(user, sample());
+
+	let err = from_value::<User>(json!({"id": -1.0})).unwrap_err();
+	assert!(err.to_string().contains("-1"));
+}
+
+#[test]
+fn value_implements_serialize_and_deserialize() {
+	let value = json!({"nested": [true, null, "x", 2.5]});
+
+	let round_trip: Value = from_value(to_value(&value).unwrap()).unwrap();
+	assert_eq!(round_trip, value);
+
+	assert!(to_value(&Value::Invalid).is_err());
+}
+
+#[test]
+fn number_and_bool_keys_round_trip() {
+	let mut ids: HashMap<u32, String> = HashMap::new();
+	ids.insert(1, String::from("one"));
+	ids.insert(20, String::from("twenty"));
+
+	let value = to_value(&ids).unwrap();
+	assert_eq!(value, json!({"1": "one", "20": "twenty"}));
+	assert_eq!(from_value::<HashMap<u32, String>>(value).unwrap(), ids);
+
+	let mut flags: BTreeMap<bool, i64> = BTreeMap::new();
+	flags.insert(true, -3);
+	assert_eq!(from_value::<BTreeMap<bool, i64>>(to_value(&flags).unwrap()).unwrap(), flags);
+
+	let err = from_value::<HashMap<u8, bool>>(json!({"300": true})).unwrap_err();
+	assert_eq!(err.to_string(), "expected a u8 object key, found \"300\"");
+}
+
+#[test]
+fn large_u64_round_trips() {
+	for x in [1u64 << 63, (1u64 << 63) + 2048, 1u64 << 53] {
+		let value = to_value(&x).unwrap();
+		assert_eq!(value, Value::Number(x as f64));
+		assert_eq!(from_value::<u64>(value).unwrap(), x);
+	}
+
+	assert!(from_value::<i64>(json!(9223372036854775808.0)).is_err());
+	assert_eq!(from_value::<i64>(json!(-9223372036854775808.0)).unwrap(), i64::MIN);
+}